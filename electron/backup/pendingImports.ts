@@ -0,0 +1,69 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { importBackup } from './importer';
+import { importJsonBackup } from './jsonImporter';
+
+export interface FailedImport {
+    name: string;
+    error: string;
+    /** Where the backup was kept so it can be imported by hand */
+    keptAt: string;
+}
+
+/**
+ * Backups queued by the installer's migration step (`<userData>/pending-import`).
+ * Each one is imported once on startup, after extensions are loaded so Tachiyomi
+ * sources can be matched, and then removed. A backup that fails to import is moved
+ * to `<userData>/pending-import-failed` instead and reported back.
+ */
+export async function importPendingBackups(userDataPath: string): Promise<FailedImport[]> {
+    const pendingDir = path.join(userDataPath, 'pending-import');
+    const failedDir = path.join(userDataPath, 'pending-import-failed');
+    const failures: FailedImport[] = [];
+    if (!fs.existsSync(pendingDir)) return failures;
+
+    for (const name of fs.readdirSync(pendingDir)) {
+        const filePath = path.join(pendingDir, name);
+        let error: string | null = null;
+        try {
+            if (name.endsWith('.mgb')) {
+                const result = await importJsonBackup(filePath, {
+                    manga: true,
+                    chapters: true,
+                    tags: true,
+                    categories: true,
+                    history: true,
+                    extensions: true,
+                    mergeStrategy: 'keep',
+                }, () => { });
+                if (!result.success) error = result.error ?? 'Import failed';
+            } else {
+                const result = await importBackup(filePath);
+                if (!result.success) error = result.message;
+            }
+        } catch (e) {
+            error = e instanceof Error ? e.message : String(e);
+        }
+
+        if (error === null) {
+            console.log(`[Main] Imported pending backup ${name}`);
+            fs.rmSync(filePath, { force: true });
+            continue;
+        }
+        console.error(`[Main] Failed to import pending backup ${name}: ${error}`);
+        try {
+            fs.mkdirSync(failedDir, { recursive: true });
+            const keptAt = path.join(failedDir, name);
+            fs.renameSync(filePath, keptAt);
+            failures.push({ name, error, keptAt });
+        } catch (e) {
+            // Leave it queued; the next start tries again
+            console.error(`[Main] Failed to move pending backup ${name} aside:`, e);
+            failures.push({ name, error, keptAt: filePath });
+        }
+    }
+    if (fs.readdirSync(pendingDir).length === 0) {
+        fs.rmSync(pendingDir, { recursive: true, force: true });
+    }
+    return failures;
+}
@@ -0,0 +1,42 @@
+import { app } from 'electron';
+import path from 'path';
+import * as fs from 'fs';
+
+/**
+ * Where the installer leaves files for the app: next to the exe, or on macOS in
+ * Application Support, since a file added to the signed bundle would break its seal
+ */
+export function installerStateDir(): string {
+    if (process.platform === 'darwin') {
+        return path.join(app.getPath('appData'), app.getName());
+    }
+    return path.dirname(app.getPath('exe'));
+}
+
+/**
+ * The installer's update cache, in the folder under appData named after the product
+ * (the installer's branding data dir). Unlike userData it doesn't follow a data
+ * folder chosen in the installer.
+ */
+export function installerUpdateCacheDir(): string {
+    return path.join(app.getPath('appData'), app.getName(), 'update-cache');
+}
+
+/**
+ * Data folder chosen in the installer, stored as `data-location.json` in
+ * `installerStateDir()`. Imported first from main.ts: it has to run before anything
+ * (the settings store, the GPU flag, the database) resolves userData.
+ */
+const locationFile = path.join(installerStateDir(), 'data-location.json');
+
+if (app.isPackaged && fs.existsSync(locationFile)) {
+    try {
+        const { dataDir } = JSON.parse(fs.readFileSync(locationFile, 'utf-8'));
+        if (typeof dataDir === 'string' && dataDir) {
+            fs.mkdirSync(dataDir, { recursive: true });
+            app.setPath('userData', dataDir);
+        }
+    } catch (e) {
+        console.error('[Main] Ignoring invalid data-location.json:', e);
+    }
+}
@@ -7,6 +7,8 @@ import * as path from 'path';
 import * as os from 'os';
 import * as memoryMonitor from '../memoryMonitor';
 import { differentialUpdater } from '../updater';
+import { peerCache } from '../peerCache';
+import { getSetting, setSetting } from '../store';
 import { getFormattedMainLogs } from '../logging';
 import { getFormattedMainNetwork } from '../imageProxy';
 import { createLogger, LogLevel } from '../utils/logger';
@@ -51,6 +53,45 @@ export function setupAppHandlers(mainWindow: BrowserWindow) {
         return differentialUpdater.installUpdate();
     });
 
+    // Opt-in sharing of downloaded updates with other installs on the local network
+    peerCache.setEnabled(getSetting('lanUpdateSharing')).catch(e => console.error('[PeerCache] Failed to start:', e));
+
+    ipcMain.handle('app:setLanUpdateSharing', async (_, enabled: boolean) => {
+        setSetting('lanUpdateSharing', enabled);
+        try {
+            await peerCache.setEnabled(enabled);
+            return { success: true };
+        } catch (e) {
+            return { success: false, error: e instanceof Error ? e.message : 'Failed to start sharing' };
+        }
+    });
+
+    // Portable installs (marked `.portable` by the installer) remove themselves through the
+    // uninstaller copy next to the exe, which waits for the app to quit
+    ipcMain.handle('app:uninstallPortable', async () => {
+        const installDir = path.dirname(app.getPath('exe'));
+        if (!app.isPackaged || !fs.existsSync(path.join(installDir, '.portable'))) {
+            return { success: false, error: 'Not a portable install' };
+        }
+        const uninstaller = process.platform === 'win32'
+            ? path.join(installDir, `Uninstall ${app.getName()}.exe`)
+            : path.join(installDir, `uninstall-${path.basename(app.getPath('exe'))}`);
+        if (!fs.existsSync(uninstaller)) {
+            return { success: false, error: `Uninstaller not found: ${uninstaller}` };
+        }
+        const { spawn } = await import('child_process');
+        const child = spawn(uninstaller, ['--uninstall-portable', installDir], {
+            detached: true,
+            stdio: 'ignore',
+            windowsHide: true
+        });
+        child.unref();
+        setTimeout(() => {
+            app.quit();
+        }, 1000);
+        return { success: true };
+    });
+
     // Dump logs for debugging
     ipcMain.handle('app:createDumpLog', async (_, rendererConsoleLogs: string, rendererNetworkActivity: string) => {
         const { shell } = await import('electron');
@@ -1,4 +1,6 @@
-import { app, BrowserWindow, ipcMain, webContents } from 'electron';
+// Must stay first: redirects userData before other modules read it
+import './dataLocation';
+import { app, BrowserWindow, dialog, ipcMain, shell, webContents } from 'electron';
 import path from 'path';
 import * as fs from 'fs';
 import { fileURLToPath } from 'url';
@@ -71,6 +73,67 @@ function checkArgsForFile(args: string[]) {
     }
 }
 
+// Deep links from the installer's extra shortcuts (`--route=<section>`) and its first
+// launch after install (`--first-run`, which opens backup import), opened in the
+// renderer: taken by it once it has mounted, or pushed to it when already running
+const LAUNCH_ROUTES: Record<string, string> = {
+    library: '/',
+    browse: '/browse',
+    history: '/history',
+    downloads: '/downloads',
+    tags: '/tags',
+    stats: '/stats',
+    extensions: '/extensions',
+    settings: '/settings',
+};
+const FIRST_RUN_ROUTE = '/settings?category=backup';
+let pendingLaunchRoute: string | null = null;
+
+function launchRouteFromArgs(args: string[]): string | null {
+    for (const arg of args) {
+        if (arg === '--first-run') return FIRST_RUN_ROUTE;
+        if (!arg.startsWith('--route=')) continue;
+        const route = LAUNCH_ROUTES[arg.slice('--route='.length).toLowerCase()];
+        if (route) return route;
+        console.warn(`[Main] Ignoring unknown launch route: ${arg}`);
+    }
+    return null;
+}
+
+function handleLaunchArgs(args: string[]) {
+    const route = launchRouteFromArgs(args);
+    if (!route) return;
+    if (mainWindow && !mainWindow.isDestroyed() && !mainWindow.webContents.isLoading()) {
+        mainWindow.webContents.send('app:navigate', route);
+    } else {
+        pendingLaunchRoute = route;
+    }
+}
+
+// Clicking the installer's "was updated" toast launches the app with `--whats-new <version>`:
+// open that release's notes
+const RELEASES_URL = 'https://github.com/Mangyomi/mangyomi-application/releases';
+
+function handleWhatsNew(args: string[]) {
+    const i = args.indexOf('--whats-new');
+    if (i < 0) return;
+    const version = args[i + 1] ?? '';
+    // Only stable versions have a tag of their own
+    const url = /^\d+\.\d+\.\d+$/.test(version) ? `${RELEASES_URL}/tag/v${version}` : RELEASES_URL;
+    shell.openExternal(url).catch(e => console.error('[Main] Failed to open release notes:', e));
+}
+
+ipcMain.handle('app:takeLaunchRoute', () => {
+    const route = pendingLaunchRoute;
+    pendingLaunchRoute = null;
+    return route;
+});
+
+// Must match the AppUserModelID the installer stamps on its shortcuts
+if (process.platform === 'win32') {
+    app.setAppUserModelId('com.mangyomi.app');
+}
+
 // Handle second instance (Windows: app already running, user opens another .mgb file)
 const gotTheLock = app.requestSingleInstanceLock();
 if (!gotTheLock) {
@@ -82,6 +145,8 @@ if (!gotTheLock) {
             mainWindow.focus();
         }
         checkArgsForFile(commandLine);
+        handleLaunchArgs(commandLine);
+        handleWhatsNew(commandLine);
     });
 }
 
@@ -409,13 +474,28 @@ app.whenReady().then(async () => {
     const extensionsPath = path.join(app.getPath('userData'), 'extensions');
     await loadExtensions(extensionsPath);
 
+    // Backups queued by the installer's migration step
+    const { importPendingBackups } = await import('./backup/pendingImports');
+    const failedImports = await importPendingBackups(app.getPath('userData'));
+
     setupImageProxy();
     await setupIpcHandlers(extensionsPath);
 
     createWindow();
 
+    if (failedImports.length > 0 && mainWindow) {
+        dialog.showMessageBox(mainWindow, {
+            type: 'error',
+            title: 'Backup import failed',
+            message: 'Some backups from the installer could not be imported.',
+            detail: failedImports.map(f => `${f.name}: ${f.error}\nKept at ${f.keptAt}`).join('\n\n'),
+        });
+    }
+
     // Check startup arguments for .mgb file
     checkArgsForFile(process.argv);
+    handleLaunchArgs(process.argv);
+    handleWhatsNew(process.argv);
 
     // Handle pending file (opened before app was ready)
     if (pendingFilePath && mainWindow) {
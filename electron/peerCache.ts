@@ -0,0 +1,309 @@
+import * as crypto from 'crypto';
+import * as dgram from 'dgram';
+import * as fs from 'fs';
+import * as http from 'http';
+import * as path from 'path';
+import { AddressInfo } from 'net';
+import { installerUpdateCacheDir } from './dataLocation';
+import { sha512 } from './releaseManifest';
+
+/**
+ * Opt-in sharing of downloaded updates between installs on the same network. Each app
+ * with sharing on answers mDNS queries for SERVICE and serves the installers it holds
+ * (its last download and the installer's update cache) over HTTP, by SHA-512. Before
+ * downloading an update from the internet, the updater asks the peers it finds for the
+ * file the signed release manifest lists, and keeps a copy only if its size and hash
+ * match, so a peer can save bandwidth but never change what gets installed.
+ */
+
+const SERVICE = '_mangyomi-updates._tcp.local';
+const MDNS_ADDRESS = '224.0.0.251';
+const MDNS_PORT = 5353;
+const TYPE_PTR = 12;
+const TYPE_TXT = 16;
+const CLASS_IN = 1;
+const DISCOVERY_TIMEOUT_MS = 1500;
+const PEER_TIMEOUT_MS = 10000;
+
+interface Peer {
+    host: string;
+    port: number;
+}
+
+function encodeName(name: string): Buffer {
+    const labels = name.split('.').map(label => {
+        const bytes = Buffer.from(label, 'utf-8');
+        return Buffer.concat([Buffer.from([bytes.length]), bytes]);
+    });
+    return Buffer.concat([...labels, Buffer.from([0])]);
+}
+
+/** A possibly compressed name at `offset`, and the offset after it */
+function readName(packet: Buffer, offset: number): [string, number] {
+    const labels: string[] = [];
+    let next = -1;
+    for (let jumps = 0; jumps < 32; jumps++) {
+        const length = packet[offset];
+        if (length === undefined) throw new Error('Truncated name');
+        if (length === 0) {
+            return [labels.join('.'), next === -1 ? offset + 1 : next];
+        }
+        if ((length & 0xc0) === 0xc0) {
+            if (next === -1) next = offset + 2;
+            offset = packet.readUInt16BE(offset) & 0x3fff;
+            continue;
+        }
+        labels.push(packet.toString('utf-8', offset + 1, offset + 1 + length));
+        offset += 1 + length;
+    }
+    throw new Error('Name compression loop');
+}
+
+function header(id: number, flags: number, questions: number, answers: number, additional: number): Buffer {
+    const buffer = Buffer.alloc(12);
+    buffer.writeUInt16BE(id, 0);
+    buffer.writeUInt16BE(flags, 2);
+    buffer.writeUInt16BE(questions, 4);
+    buffer.writeUInt16BE(answers, 6);
+    buffer.writeUInt16BE(additional, 10);
+    return buffer;
+}
+
+function record(name: string, type: number, rdata: Buffer): Buffer {
+    const fixed = Buffer.alloc(10);
+    fixed.writeUInt16BE(type, 0);
+    fixed.writeUInt16BE(CLASS_IN, 2);
+    fixed.writeUInt32BE(120, 4);
+    fixed.writeUInt16BE(rdata.length, 8);
+    return Buffer.concat([encodeName(name), fixed, rdata]);
+}
+
+function query(): Buffer {
+    const question = Buffer.alloc(4);
+    question.writeUInt16BE(TYPE_PTR, 0);
+    question.writeUInt16BE(CLASS_IN, 2);
+    return Buffer.concat([header(0, 0, 1, 0, 0), encodeName(SERVICE), question]);
+}
+
+/** The PTR answer naming this instance, with its port in a TXT record */
+function response(id: number, instance: string, port: number): Buffer {
+    const txt = Buffer.from(`port=${port}`, 'utf-8');
+    return Buffer.concat([
+        header(id, 0x8400, 0, 1, 1),
+        record(SERVICE, TYPE_PTR, encodeName(instance)),
+        record(instance, TYPE_TXT, Buffer.concat([Buffer.from([txt.length]), txt])),
+    ]);
+}
+
+function asksForService(packet: Buffer): boolean {
+    if (packet.length < 12 || (packet.readUInt16BE(2) & 0x8000) !== 0) return false;
+    let offset = 12;
+    for (let i = 0; i < packet.readUInt16BE(4); i++) {
+        const [name, next] = readName(packet, offset);
+        if (name.toLowerCase() === SERVICE && packet.readUInt16BE(next) === TYPE_PTR) return true;
+        offset = next + 4;
+    }
+    return false;
+}
+
+/** The port a peer advertises in its response, other than `ownInstance` */
+function advertisedPort(packet: Buffer, ownInstance: string): number | null {
+    if (packet.length < 12 || (packet.readUInt16BE(2) & 0x8000) === 0) return null;
+    let offset = 12;
+    for (let i = 0; i < packet.readUInt16BE(4); i++) {
+        offset = readName(packet, offset)[1] + 4;
+    }
+    const records = packet.readUInt16BE(6) + packet.readUInt16BE(8) + packet.readUInt16BE(10);
+    for (let i = 0; i < records; i++) {
+        const [name, next] = readName(packet, offset);
+        const type = packet.readUInt16BE(next);
+        const length = packet.readUInt16BE(next + 8);
+        const rdata = packet.subarray(next + 10, next + 10 + length);
+        offset = next + 10 + length;
+        if (type !== TYPE_TXT || !name.toLowerCase().endsWith(SERVICE) || name === ownInstance) continue;
+        const text = rdata.toString('utf-8', 1, 1 + rdata[0]);
+        const port = Number(text.match(/^port=(\d+)$/)?.[1]);
+        if (port > 0 && port < 65536) return port;
+    }
+    return null;
+}
+
+export class PeerCache {
+    private readonly instance = `${crypto.randomBytes(6).toString('hex')}.${SERVICE}`;
+    private server: http.Server | null = null;
+    private responder: dgram.Socket | null = null;
+    // Files this app can hand out, by base64 SHA-512
+    private files = new Map<string, string>();
+
+    private log(message: string): void {
+        console.log(`[PeerCache] ${message}`);
+    }
+
+    get enabled(): boolean {
+        return this.server !== null;
+    }
+
+    async setEnabled(enabled: boolean): Promise<void> {
+        if (enabled && !this.server) {
+            await this.start();
+        } else if (!enabled && this.server) {
+            this.stop();
+        }
+    }
+
+    private async start(): Promise<void> {
+        const server = http.createServer((req, res) => this.serve(req, res));
+        await new Promise<void>((resolve, reject) => {
+            server.once('error', reject);
+            server.listen(0, '0.0.0.0', () => resolve());
+        });
+        this.server = server;
+        const port = (server.address() as AddressInfo).port;
+
+        const responder = dgram.createSocket({ type: 'udp4', reuseAddr: true });
+        responder.on('error', e => this.log(`mDNS responder failed: ${e.message}`));
+        responder.on('message', (packet, remote) => {
+            try {
+                if (!asksForService(packet)) return;
+                // Queries from an ephemeral port get a unicast reply (RFC 6762, 6.7)
+                const reply = response(packet.readUInt16BE(0), this.instance, port);
+                if (remote.port === MDNS_PORT) responder.send(reply, MDNS_PORT, MDNS_ADDRESS);
+                else responder.send(reply, remote.port, remote.address);
+            } catch {
+                // Malformed packets from other responders are none of our business
+            }
+        });
+        responder.bind(MDNS_PORT, () => {
+            try {
+                responder.addMembership(MDNS_ADDRESS);
+            } catch (e) {
+                this.log(`Joining the mDNS group failed: ${e instanceof Error ? e.message : e}`);
+            }
+        });
+        this.responder = responder;
+        this.log(`Sharing updates on port ${port}`);
+        void this.indexUpdateCache();
+    }
+
+    private stop(): void {
+        this.server?.close();
+        this.responder?.close();
+        this.server = null;
+        this.responder = null;
+        this.log('Stopped sharing updates');
+    }
+
+    /** The installers the installer keeps for differential updates, one per version */
+    private async indexUpdateCache(): Promise<void> {
+        const cacheDir = installerUpdateCacheDir();
+        let versions: string[];
+        try {
+            versions = await fs.promises.readdir(cacheDir);
+        } catch {
+            return;
+        }
+        for (const version of versions) {
+            const installer = path.join(cacheDir, version, 'installer.exe');
+            try {
+                const hash = crypto.createHash('sha512');
+                for await (const chunk of fs.createReadStream(installer)) hash.update(chunk);
+                this.files.set(hash.digest('base64'), installer);
+            } catch {
+                // Not every version keeps its installer
+            }
+        }
+        this.log(`Indexed ${this.files.size} cached installers`);
+    }
+
+    /** Offer a downloaded and verified file to peers */
+    share(filePath: string, hash: string): void {
+        this.files.set(hash, filePath);
+    }
+
+    private serve(req: http.IncomingMessage, res: http.ServerResponse): void {
+        const match = req.method === 'GET' ? req.url?.match(/^\/sha512\/([A-Za-z0-9_-]+)$/) : null;
+        const hash = match ? Buffer.from(match[1], 'base64url').toString('base64') : null;
+        const filePath = hash ? this.files.get(hash) : undefined;
+        if (!filePath || !fs.existsSync(filePath)) {
+            res.writeHead(404).end();
+            return;
+        }
+        this.log(`Serving ${path.basename(filePath)} to ${req.socket.remoteAddress}`);
+        res.writeHead(200, { 'Content-Type': 'application/octet-stream', 'Content-Length': fs.statSync(filePath).size });
+        fs.createReadStream(filePath).pipe(res);
+    }
+
+    /** Peers answering on the local network within the discovery timeout */
+    private discover(): Promise<Peer[]> {
+        return new Promise(resolve => {
+            const peers = new Map<string, Peer>();
+            const socket = dgram.createSocket({ type: 'udp4', reuseAddr: true });
+            const finish = () => {
+                socket.close();
+                resolve([...peers.values()]);
+            };
+            socket.on('error', finish);
+            socket.on('message', (packet, remote) => {
+                try {
+                    const port = advertisedPort(packet, this.instance);
+                    if (port) peers.set(`${remote.address}:${port}`, { host: remote.address, port });
+                } catch {
+                    // Ignore malformed responses
+                }
+            });
+            socket.bind(0, () => {
+                socket.send(query(), MDNS_PORT, MDNS_ADDRESS);
+                setTimeout(finish, DISCOVERY_TIMEOUT_MS);
+            });
+        });
+    }
+
+    private fetchFrom(peer: Peer, expected: { size: number; sha512: string }): Promise<Buffer> {
+        const url = `http://${peer.host}:${peer.port}/sha512/${Buffer.from(expected.sha512, 'base64').toString('base64url')}`;
+        return new Promise((resolve, reject) => {
+            const req = http.get(url, { timeout: PEER_TIMEOUT_MS }, res => {
+                if (res.statusCode !== 200) {
+                    res.resume();
+                    reject(new Error(`HTTP ${res.statusCode}`));
+                    return;
+                }
+                const chunks: Buffer[] = [];
+                let received = 0;
+                res.on('data', (chunk: Buffer) => {
+                    received += chunk.length;
+                    if (received > expected.size) req.destroy(new Error('Larger than expected'));
+                    else chunks.push(chunk);
+                });
+                res.on('end', () => resolve(Buffer.concat(chunks)));
+                res.on('error', reject);
+            });
+            req.on('timeout', () => req.destroy(new Error('Timed out')));
+            req.on('error', reject);
+        });
+    }
+
+    /**
+     * The file with `expected` size and hash from the first peer that has it, or null
+     * when sharing is off or no peer does
+     */
+    async fetch(expected: { size: number; sha512: string }): Promise<Buffer | null> {
+        if (!this.enabled) return null;
+        const peers = await this.discover();
+        this.log(`Found ${peers.length} peers`);
+        for (const peer of peers) {
+            try {
+                const data = await this.fetchFrom(peer, expected);
+                if (data.length === expected.size && sha512(data) === expected.sha512) {
+                    this.log(`Got ${data.length} bytes from ${peer.host}`);
+                    return data;
+                }
+                this.log(`Discarding a mismatching file from ${peer.host}`);
+            } catch (e) {
+                this.log(`${peer.host}: ${e instanceof Error ? e.message : e}`);
+            }
+        }
+        return null;
+    }
+}
+
+export const peerCache = new PeerCache();
@@ -158,6 +158,8 @@ contextBridge.exposeInMainWorld('electronAPI', {
         checkForUpdates: (useBeta: boolean) => ipcRenderer.invoke('app:checkForUpdates', useBeta),
         downloadUpdate: (url: string, fileName: string, blockmapUrl?: string, targetVersion?: string) => ipcRenderer.invoke('app:downloadUpdate', url, fileName, blockmapUrl, targetVersion),
         installUpdate: () => ipcRenderer.invoke('app:installUpdate'),
+        setLanUpdateSharing: (enabled: boolean) => ipcRenderer.invoke('app:setLanUpdateSharing', enabled),
+        uninstallPortable: () => ipcRenderer.invoke('app:uninstallPortable'),
         onDownloadProgress: (callback: (event: any, data: { percent: number; bytesDownloaded: number; totalBytes: number; isDifferential?: boolean }) => void) => {
             ipcRenderer.on('update:downloadProgress', callback);
             return () => ipcRenderer.removeListener('update:downloadProgress', callback);
@@ -170,6 +172,12 @@ contextBridge.exposeInMainWorld('electronAPI', {
             ipcRenderer.on('file-opened', callback);
             return () => ipcRenderer.removeListener('file-opened', callback);
         },
+        // Section of the app the launch arguments asked for, if any; taken once
+        takeLaunchRoute: () => ipcRenderer.invoke('app:takeLaunchRoute') as Promise<string | null>,
+        onNavigate: (callback: (event: any, route: string) => void) => {
+            ipcRenderer.on('app:navigate', callback);
+            return () => ipcRenderer.removeListener('app:navigate', callback);
+        },
         // Forward renderer logs to main process (for terminal output)
         log: (level: 'error' | 'warn' | 'info' | 'debug' | 'verbose', context: string, message: string) =>
             ipcRenderer.invoke('app:log', level, context, message),
@@ -0,0 +1,114 @@
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+
+/**
+ * What a release contains, as published by CI next to the release assets
+ * (`release-manifest.json`) with detached signatures (`release-manifest.json.sig`).
+ * The updater trusts the version, date and file hashes only from a manifest signed by
+ * one of TRUSTED_KEYS, so a compromised CDN or API response can't offer a fake release.
+ */
+export interface ReleaseManifest {
+    version: string;
+    /** `stable` or `nightly`; each channel counts its own sequence */
+    channel: string;
+    /** Raised every time CI signs a manifest for the channel, including refreshes */
+    sequence: number;
+    publishedAt: string;
+    /** After this the manifest is stale and must be re-signed before it's trusted again */
+    expires: string;
+    files: Record<string, { size: number; sha512: string }>;
+    /**
+     * Installers carrying a delta payload, each usable only over the `from` version it was
+     * built against; the file is listed in `files` like any other
+     */
+    deltas?: { from: string; file: string }[];
+}
+
+/** The highest sequence accepted so far per channel, persisted between runs */
+export type SequenceState = Record<string, number>;
+
+interface SignatureFile {
+    signatures: { keyId: string; signature: string }[];
+}
+
+export const MANIFEST_ASSET = 'release-manifest.json';
+export const SIGNATURE_ASSET = `${MANIFEST_ASSET}.sig`;
+
+/**
+ * Ed25519 public keys (SPKI DER, base64) by key ID. During a rotation releases are
+ * signed with both the outgoing and the incoming key, and the old key is removed once
+ * no supported version needs it; see docs/update-signing.md.
+ */
+const TRUSTED_KEYS: Record<string, string> = {
+    'mangyomi-2026-1': 'MCowBQYDK2VwAyEARJYMoeCqUltFvJhtO+6ddHAvgC1Psz/4+LuaYfzEgc0=',
+};
+
+/**
+ * Parse `manifest` if any of the signatures in `signatureFile` is a valid signature by a
+ * trusted key. Signatures by unknown keys (a newer key this version doesn't know yet) are
+ * skipped. Throws when none verifies.
+ */
+export function verifyReleaseManifest(manifest: Buffer, signatureFile: Buffer): ReleaseManifest {
+    let signatures: SignatureFile['signatures'];
+    try {
+        signatures = (JSON.parse(signatureFile.toString('utf-8')) as SignatureFile).signatures ?? [];
+    } catch {
+        throw new Error('Release manifest signature is unreadable');
+    }
+
+    const trusted = signatures.find(({ keyId, signature }) => {
+        const key = TRUSTED_KEYS[keyId];
+        if (!key || typeof signature !== 'string') return false;
+        const publicKey = crypto.createPublicKey({ key: Buffer.from(key, 'base64'), format: 'der', type: 'spki' });
+        return crypto.verify(null, manifest, publicKey, Buffer.from(signature, 'base64'));
+    });
+    if (!trusted) {
+        const keyIds = signatures.map(s => s.keyId).join(', ') || 'none';
+        throw new Error(`Release manifest is not signed by a trusted key (signed by: ${keyIds})`);
+    }
+
+    const parsed = JSON.parse(manifest.toString('utf-8')) as ReleaseManifest;
+    if (typeof parsed.version !== 'string' || typeof parsed.channel !== 'string' ||
+        !Number.isSafeInteger(parsed.sequence) || Number.isNaN(Date.parse(parsed.expires)) ||
+        typeof parsed.files !== 'object' || !parsed.files) {
+        throw new Error('Release manifest is malformed');
+    }
+    console.log(`[Updater] Release manifest ${parsed.version} verified with key ${trusted.keyId}`);
+    return parsed;
+}
+
+/**
+ * Reject a verified manifest that is past its expiry (a mirror withholding newer
+ * releases by serving a frozen copy) or older than one already seen for its channel (a
+ * replayed manifest pointing back at a vulnerable release). The installer repeats both
+ * checks against its own record.
+ */
+export function checkFreshness(manifest: ReleaseManifest, seen: SequenceState, enforceExpiry = true): void {
+    if (enforceExpiry && Date.parse(manifest.expires) <= Date.now()) {
+        throw new Error(`Release manifest for ${manifest.version} expired on ${manifest.expires}`);
+    }
+    const highest = seen[manifest.channel] ?? 0;
+    if (manifest.sequence < highest) {
+        throw new Error(`Release manifest for ${manifest.version} is older than one already seen (${manifest.sequence} < ${highest})`);
+    }
+}
+
+export function readSequenceState(statePath: string): SequenceState {
+    try {
+        return JSON.parse(fs.readFileSync(statePath, 'utf-8')).sequences ?? {};
+    } catch {
+        return {};
+    }
+}
+
+export function recordSequence(statePath: string, manifest: ReleaseManifest): void {
+    const sequences = readSequenceState(statePath);
+    if ((sequences[manifest.channel] ?? 0) >= manifest.sequence) return;
+    sequences[manifest.channel] = manifest.sequence;
+    fs.writeFileSync(statePath, JSON.stringify({ sequences }, null, 2));
+}
+
+/** Base64 SHA-512 of `data`, as the manifest records file hashes */
+export function sha512(data: Buffer): string {
+    return crypto.createHash('sha512').update(data).digest('base64');
+}
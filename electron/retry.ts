@@ -0,0 +1,88 @@
+/**
+ * Retries for update network calls that fail transiently, matching the installer's: the
+ * wait before each retry grows exponentially up to a cap and is picked at random below
+ * it, and a server's Retry-After is honoured unless it asks for more than the cap.
+ * Anything that isn't a network failure or a transient status, such as a manifest that
+ * fails verification, is thrown straight away.
+ */
+
+export interface RetryPolicy {
+    /** Tries in all, the first included */
+    attempts: number;
+    /** Cap on the wait before the first retry; doubled for each one after it */
+    baseDelayMs: number;
+    /** Cap on any single wait */
+    maxDelayMs: number;
+}
+
+export const DEFAULT_RETRY_POLICY: RetryPolicy = { attempts: 4, baseDelayMs: 1000, maxDelayMs: 30000 };
+
+const TRANSIENT_STATUSES = new Set([408, 429, 500, 502, 503, 504]);
+
+/**
+ * Connection failures and timeouts as net.fetch (`net::ERR_*`) and Node's fetch (the
+ * `cause` of its "fetch failed" TypeError) report them. Certificate errors, bad URLs
+ * and the like aren't among them.
+ */
+const NETWORK_FAILURE = /net::ERR_(CONNECTION_\w+|TIMED_OUT|NETWORK_CHANGED|INTERNET_DISCONNECTED|NAME_NOT_RESOLVED|ADDRESS_UNREACHABLE|EMPTY_RESPONSE)\b|ECONNRESET|ECONNREFUSED|ETIMEDOUT|EAI_AGAIN|UND_ERR_SOCKET|UND_ERR_CONNECT_TIMEOUT|socket hang up/;
+
+/** An HTTP response worth asking for again */
+export class TransientHttpError extends Error {
+    constructor(message: string, readonly retryAfterMs: number | null) {
+        super(message);
+    }
+}
+
+/** A Retry-After header in milliseconds: a number of seconds or an HTTP date */
+export function parseRetryAfter(value: string | null): number | null {
+    if (!value) return null;
+    if (/^\d+$/.test(value.trim())) return Number(value.trim()) * 1000;
+    const at = Date.parse(value);
+    return Number.isNaN(at) ? null : Math.max(0, at - Date.now());
+}
+
+/** Throw a TransientHttpError for a response worth asking for again; others are left to the caller */
+export function throwIfTransient(response: Response, what: string): void {
+    if (TRANSIENT_STATUSES.has(response.status)) {
+        throw new TransientHttpError(`${what} failed with HTTP ${response.status}`, parseRetryAfter(response.headers.get('retry-after')));
+    }
+}
+
+/** Connection failures from net.fetch and fetch, and transient statuses */
+function isTransient(error: unknown): boolean {
+    if (error instanceof TransientHttpError) return true;
+    if (!(error instanceof Error)) return false;
+    const cause = (error as Error & { cause?: { code?: string; message?: string } }).cause;
+    return NETWORK_FAILURE.test([error.message, cause?.code, cause?.message].filter(Boolean).join(' '));
+}
+
+function delay(policy: RetryPolicy, retry: number): number {
+    const cap = Math.min(policy.maxDelayMs, policy.baseDelayMs * 2 ** Math.min(retry - 1, 20));
+    return Math.floor(Math.random() * (cap + 1));
+}
+
+/** Run `attempt` until it succeeds, fails for good or runs out of tries, logging each failure as `what` */
+export async function withRetry<T>(what: string, policy: RetryPolicy, attempt: () => Promise<T>): Promise<T> {
+    const attempts = Math.max(1, policy.attempts);
+    for (let n = 1; ; n++) {
+        try {
+            const value = await attempt();
+            if (n > 1) console.log(`[Retry] ${what} succeeded on attempt ${n}`);
+            return value;
+        } catch (error) {
+            const message = error instanceof Error ? error.message : String(error);
+            if (!isTransient(error) || n >= attempts) {
+                console.error(`[Retry] ${what} failed (attempt ${n}/${attempts}), giving up: ${message}`);
+                throw error;
+            }
+            const retryAfter = error instanceof TransientHttpError ? error.retryAfterMs : null;
+            if (retryAfter !== null && retryAfter > policy.maxDelayMs) {
+                console.error(`[Retry] ${what} failed and the server asked to wait ${Math.round(retryAfter / 1000)}s, giving up: ${message}`);
+                throw error;
+            }
+            const wait = Math.max(delay(policy, n), retryAfter ?? 0);
+            console.warn(`[Retry] ${what} failed (attempt ${n}/${attempts}), retrying in ${wait}ms: ${message}`);
+            await new Promise(resolve => setTimeout(resolve, wait));
+        }
+    }
+}
@@ -17,6 +17,7 @@ interface SettingsSchema {
     discordRpcHideNsfw: boolean;
     discordRpcStrictNsfw: boolean;
     developerMode: boolean;
+    lanUpdateSharing: boolean;
     windowBounds?: {
         x: number;
         y: number;
@@ -98,6 +99,10 @@ const schema = {
         type: 'boolean',
         default: false
     },
+    lanUpdateSharing: {
+        type: 'boolean',
+        default: false
+    },
     windowBounds: {
         type: 'object',
         properties: {
@@ -0,0 +1,55 @@
+import { app } from 'electron';
+import path from 'path';
+import * as fs from 'fs';
+import { installerStateDir } from './dataLocation';
+import { DEFAULT_RETRY_POLICY, RetryPolicy } from './retry';
+
+export interface UpdatePolicy {
+    /** Channel the app is locked to; the user's beta setting is ignored */
+    channel: 'stable' | 'beta' | null;
+    /** Releases younger than this many days aren't offered */
+    deferDays: number | null;
+    /**
+     * Self-hosted releases endpoint used instead of the GitHub repository's, answering
+     * `/releases` and `/releases/latest` like the GitHub API, or a folder (usually a UNC
+     * path) holding one release's assets and its signed manifest
+     */
+    updateUrl: string | null;
+    /** How update lookups and downloads retry transient failures */
+    retry: RetryPolicy | null;
+}
+
+/** Whether the update source is a folder rather than an endpoint */
+export function isUpdateFolder(source: string): boolean {
+    return path.isAbsolute(source);
+}
+
+/**
+ * Update settings from a managed deployment, written by the installer as
+ * `update-policy.json` in `installerStateDir()`. The installer removes the file
+ * when the policy no longer sets anything, so its absence means no policy.
+ */
+export function readUpdatePolicy(): UpdatePolicy {
+    const policy: UpdatePolicy = { channel: null, deferDays: null, updateUrl: null, retry: null };
+    const policyFile = path.join(installerStateDir(), 'update-policy.json');
+    if (!app.isPackaged || !fs.existsSync(policyFile)) return policy;
+
+    try {
+        const { channel, deferDays, updateUrl, retry } = JSON.parse(fs.readFileSync(policyFile, 'utf-8'));
+        if (channel === 'stable' || channel === 'beta') policy.channel = channel;
+        if (typeof deferDays === 'number' && deferDays > 0) policy.deferDays = deferDays;
+        if (typeof updateUrl === 'string' && isUpdateFolder(updateUrl)) policy.updateUrl = updateUrl;
+        else if (typeof updateUrl === 'string' && updateUrl.startsWith('https://')) policy.updateUrl = updateUrl.replace(/\/+$/, '');
+        if (retry && typeof retry === 'object') {
+            const number = (value: unknown, fallback: number) => typeof value === 'number' && value >= 0 ? value : fallback;
+            policy.retry = {
+                attempts: number(retry.attempts, DEFAULT_RETRY_POLICY.attempts),
+                baseDelayMs: number(retry.baseDelayMs, DEFAULT_RETRY_POLICY.baseDelayMs),
+                maxDelayMs: number(retry.maxDelayMs, DEFAULT_RETRY_POLICY.maxDelayMs),
+            };
+        }
+    } catch (e) {
+        console.error('[Main] Ignoring invalid update-policy.json:', e);
+    }
+    return policy;
+}
@@ -36,6 +36,7 @@ if (Test-Path "$ResourcesDir\app.zip") {
     Remove-Item "$ResourcesDir\app.zip" -Force
     Write-Host "Removed old app.zip from resources" -ForegroundColor Gray
 }
+Get-ChildItem "$ResourcesDir" -Filter "app-*.*" -ErrorAction SilentlyContinue | Remove-Item -Force
 
 # Copy fresh payload
 if (Test-Path "resources\app.7z") {
@@ -54,6 +55,12 @@ else {
     Write-Error "Payload (app.zip or app.7z) not found!"
 }
 
+# Per-architecture payloads (app-x64.7z, app-arm64.7z); the installer picks the native one
+foreach ($ArchPayload in Get-ChildItem "resources" -Filter "app-*.*" -ErrorAction SilentlyContinue) {
+    Copy-Item $ArchPayload.FullName "$ResourcesDir\$($ArchPayload.Name)" -Force
+    Write-Host "Copied $($ArchPayload.Name) from installer/resources" -ForegroundColor Green
+}
+
 # 2. Locate 7-Zip
 Write-Host "Locating 7-Zip..." -ForegroundColor Cyan
 $7z = $null
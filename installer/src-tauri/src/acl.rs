@@ -0,0 +1,84 @@
+//! Locking down per-machine installs. Extracting into Program Files as an administrator
+//! can leave the directory writable by whatever inherited permissions allow, which lets
+//! a standard user swap binaries that an administrator later runs. Installs there get
+//! an explicit DACL: full control for SYSTEM and Administrators, read and execute for
+//! everyone else.
+
+use std::path::{Component, Path, Prefix};
+
+use crate::known_folders::{self, KnownFolder};
+
+/// Protected (no inherited ACEs) DACL, inherited by everything below the directory
+#[cfg(windows)]
+const INSTALL_DIR_SDDL: &str = "D:PAI(A;OICI;FA;;;SY)(A;OICI;FA;;;BA)(A;OICI;0x1200a9;;;BU)";
+
+/// The components of `path` as Windows compares them: without case, `.` or a `\\?\`
+/// prefix, whichever separators were used
+fn normalized(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Prefix(prefix) => Some(match prefix.kind() {
+                Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => format!("{}:", drive as char),
+                _ => prefix.as_os_str().to_string_lossy().into_owned(),
+            }),
+            Component::CurDir => None,
+            other => Some(other.as_os_str().to_string_lossy().into_owned()),
+        })
+        .map(|part| if cfg!(windows) { part.to_lowercase() } else { part })
+        .collect()
+}
+
+/// True for installs under Program Files, which every user's processes trust
+pub fn is_per_machine(install_path: &Path) -> bool {
+    let install_path = normalized(install_path);
+    [KnownFolder::ProgramFiles, KnownFolder::ProgramFilesX86]
+        .into_iter()
+        .filter_map(known_folders::get)
+        .any(|root| install_path.starts_with(&normalized(&root)))
+}
+
+/// Replace the DACL of `dir`, propagating it to everything already inside
+#[cfg(windows)]
+pub fn restrict(dir: &Path) -> Result<(), String> {
+    use windows::core::{BOOL, HSTRING};
+    use windows::Win32::Foundation::{LocalFree, ERROR_SUCCESS, HLOCAL};
+    use windows::Win32::Security::Authorization::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SetNamedSecurityInfoW, SDDL_REVISION_1, SE_FILE_OBJECT,
+    };
+    use windows::Win32::Security::{
+        GetSecurityDescriptorDacl, ACL, DACL_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR,
+    };
+
+    unsafe {
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(&HSTRING::from(INSTALL_DIR_SDDL), SDDL_REVISION_1, &mut descriptor, None)
+            .map_err(|e| e.to_string())?;
+
+        let (mut present, mut defaulted) = (BOOL::default(), BOOL::default());
+        let mut dacl: *mut ACL = std::ptr::null_mut();
+        let result = GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted)
+            .map_err(|e| e.to_string())
+            .and_then(|()| {
+                let status = SetNamedSecurityInfoW(
+                    &HSTRING::from(dir),
+                    SE_FILE_OBJECT,
+                    DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                    None,
+                    None,
+                    Some(dacl),
+                    None,
+                );
+                if status != ERROR_SUCCESS {
+                    return Err(format!("error {}", status.0));
+                }
+                Ok(())
+            });
+        LocalFree(Some(HLOCAL(descriptor.0)));
+        result.map_err(|e| format!("Failed to set permissions on {}: {}", dir.display(), e))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn restrict(_dir: &Path) -> Result<(), String> {
+    Ok(())
+}
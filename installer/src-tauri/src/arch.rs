@@ -0,0 +1,83 @@
+//! Payload architecture. An installer may bundle `app-x64.7z` and `app-arm64.7z` (or
+//! `.zip`) side by side; the one matching the machine's native architecture is installed,
+//! so ARM64 laptops get the native build instead of the emulated x64 one with its slower
+//! video decoding. `--arch=x64|arm64` overrides the choice. Builds with a single
+//! `app.7z` keep working: it is used when there is no payload for the architecture.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::{debug_log, system_info};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arch {
+    X64,
+    Arm64,
+}
+
+impl Arch {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "x64" | "x86_64" | "amd64" => Some(Arch::X64),
+            "arm64" | "aarch64" => Some(Arch::Arm64),
+            _ => None,
+        }
+    }
+
+    /// The suffix of this architecture's payload, `app-<tag>.7z`
+    pub fn tag(self) -> &'static str {
+        match self {
+            Arch::X64 => "x64",
+            Arch::Arm64 => "arm64",
+        }
+    }
+}
+
+static OVERRIDE: OnceLock<Arch> = OnceLock::new();
+
+/// Read `--arch=<x64|arm64>` (or `--arch <value>`)
+pub fn init(args: &[String]) {
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        arg.strip_prefix("--arch=")
+            .map(str::to_string)
+            .or_else(|| (arg == "--arch").then(|| args.get(i + 1).cloned()).flatten())
+    });
+    match value.as_deref().map(|v| (v, Arch::parse(v))) {
+        Some((_, Some(arch))) => {
+            debug_log(&format!("Payload architecture set to: {}", arch.tag()));
+            let _ = OVERRIDE.set(arch);
+        }
+        Some((value, None)) => debug_log(&format!("Ignoring unknown --arch {}", value)),
+        None => {}
+    }
+}
+
+/// The architecture to install: `--arch`, or the machine's own, which IsWow64Process2
+/// reports even when the installer itself runs emulated
+pub fn target() -> Arch {
+    OVERRIDE
+        .get()
+        .copied()
+        .or_else(|| system_info::native_arch().and_then(Arch::parse))
+        .unwrap_or(Arch::X64)
+}
+
+/// Payload file names in order of preference, with whether each is a 7z archive
+pub fn payload_names() -> Vec<(String, bool)> {
+    let tag = target().tag();
+    vec![
+        (format!("app-{}.7z", tag), true),
+        (format!("app-{}.zip", tag), false),
+        ("app.7z".to_string(), true),
+        ("app.zip".to_string(), false),
+    ]
+}
+
+/// The preferred payload present in `resources`
+pub fn find_payload(resources: &Path) -> Option<(PathBuf, bool)> {
+    let found = payload_names().into_iter().map(|(name, is_7z)| (resources.join(name), is_7z)).find(|(path, _)| path.is_file());
+    if let Some((path, _)) = &found {
+        debug_log(&format!("Payload for {}: {}", target().tag(), path.display()));
+    }
+    found
+}
@@ -0,0 +1,92 @@
+//! The Apps & Features entry (HKCU\Software\Microsoft\Windows\CurrentVersion\Uninstall).
+//! Rewritten after every install and update, silent ones included, so the version and
+//! size it shows never go stale. Other platforms have no equivalent.
+
+#[cfg(windows)]
+use std::path::Path;
+
+#[cfg(windows)]
+use crate::{branding, registry, update_cache};
+
+#[cfg(windows)]
+fn key() -> String {
+    format!("Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\{}", branding::get().app_user_model_id)
+}
+
+#[cfg(windows)]
+pub fn register(install_path: &str, version: Option<&str>) -> Result<(), String> {
+    let branding = branding::get();
+    let key = key();
+    let root = Path::new(install_path);
+    let exe = root.join(&branding.exe_name);
+    let uninstaller = root.join(branding.uninstaller_exe());
+
+    registry::set_string(&key, Some("DisplayName"), &branding.app_name)?;
+    registry::set_string(&key, Some("DisplayVersion"), version.unwrap_or_default())?;
+    registry::set_string(&key, Some("Publisher"), &branding.publisher)?;
+    registry::set_string(&key, Some("DisplayIcon"), &format!("\"{}\",0", exe.display()))?;
+    registry::set_string(&key, Some("InstallLocation"), install_path)?;
+    let uninstall = format!("\"{}\" --uninstall", uninstaller.display());
+    registry::set_string(&key, Some("UninstallString"), &uninstall)?;
+    // For winget, SCCM and other package managers removing the app unattended
+    registry::set_string(&key, Some("QuietUninstallString"), &format!("{} --silent", uninstall))?;
+    if let Some(url) = &branding.about_url {
+        registry::set_string(&key, Some("URLInfoAbout"), url)?;
+    }
+    if let Some(url) = &branding.help_url {
+        registry::set_string(&key, Some("HelpLink"), url)?;
+    }
+    // In KiB, as Apps & Features expects
+    let size = update_cache::dir_size(root).div_ceil(1024);
+    registry::set_dword(&key, "EstimatedSize", size.min(u32::MAX as u64) as u32)?;
+    registry::set_dword(&key, "NoRepair", 1)?;
+
+    // "Modify" reopens the installer this version was installed from, if it is cached, in
+    // maintenance mode for this install
+    match version.and_then(update_cache::installer_path) {
+        Some(installer) => {
+            let modify = format!("\"{}\" --modify --install-path \"{}\"", installer.display(), install_path);
+            registry::set_string(&key, Some("ModifyPath"), &modify)?;
+            registry::set_dword(&key, "NoModify", 0)?;
+        }
+        None => registry::set_dword(&key, "NoModify", 1)?,
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn unregister() -> Result<(), String> {
+    registry::delete_tree(&key())
+}
+
+/// The install the entry points at, if there is one
+#[cfg(windows)]
+pub fn install_location() -> Option<String> {
+    registry::get_string(&key(), "InstallLocation")
+}
+
+/// The version Apps & Features shows
+#[cfg(windows)]
+pub fn display_version() -> Option<String> {
+    registry::get_string(&key(), "DisplayVersion")
+}
+
+#[cfg(not(windows))]
+pub fn register(_install_path: &str, _version: Option<&str>) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn unregister() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn install_location() -> Option<String> {
+    None
+}
+
+#[cfg(not(windows))]
+pub fn display_version() -> Option<String> {
+    None
+}
@@ -0,0 +1,178 @@
+//! Backups of the library database and settings, taken before updates across a major
+//! version so a botched data migration in the new version can be rolled back.
+//!
+//! Backups are zips in `%APPDATA%\<data dir>\backups\<timestamp>-<n>.zip`; only the newest
+//! few are kept.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::data_dir::{self, DATABASE_FILE};
+use crate::debug_log;
+
+/// Files backed up from the data directory: the database (with SQLite's side files)
+/// and the electron-store settings
+const BACKUP_FILES: &[&str] = &[DATABASE_FILE, "mangyomi.db-wal", "mangyomi.db-shm", "mangyomi-config.json"];
+
+/// How many backups to keep
+const RETENTION: usize = 5;
+
+#[cfg(feature = "gui")]
+/// Appended to the files a restore unpacks, until they replace the originals
+const RESTORE_SUFFIX: &str = ".restoring";
+#[cfg(feature = "gui")]
+/// Appended to the originals while a restore swaps the files, so it can roll back
+const ASIDE_SUFFIX: &str = ".before-restore";
+
+pub fn backups_dir() -> Result<PathBuf, String> {
+    Ok(data_dir::default_dir()?.join("backups"))
+}
+
+/// Leading version number, treating `0.x` minor bumps as major ones
+fn major(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim_start_matches('v').split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = if major == 0 { parts.next()?.parse().ok()? } else { 0 };
+    Some((major, minor))
+}
+
+/// True when going from `previous` to `next` crosses a major version
+pub fn is_major_update(previous: &str, next: &str) -> bool {
+    match (major(previous), major(next)) {
+        (Some(a), Some(b)) => a != b,
+        _ => false,
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Zip the database and settings from `data_dir` into a new backup, then apply retention
+pub fn create(data_dir: &Path) -> Result<PathBuf, String> {
+    let dir = backups_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // A counter keeps backups taken within the same second apart
+    let mut sequence = 0u32;
+    let (path, file) = loop {
+        let path = dir.join(format!("{}-{:03}.zip", timestamp, sequence));
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => break (path, file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => sequence += 1,
+            Err(e) => return Err(format!("Failed to create backup {:?}: {}", path, e)),
+        }
+    };
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for name in BACKUP_FILES {
+        let Ok(data) = std::fs::read(data_dir.join(name)) else { continue };
+        zip.start_file(*name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    debug_log(&format!("Backed up user data to {:?}", path));
+
+    prune();
+    Ok(path)
+}
+
+/// Newest first
+pub fn list() -> Result<Vec<BackupInfo>, String> {
+    let Ok(entries) = std::fs::read_dir(backups_dir()?) else { return Ok(Vec::new()) };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "zip"))
+        .map(|path| BackupInfo { size: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0), path })
+        .collect();
+    // Names start with UNIX timestamps of equal length, so they sort chronologically
+    backups.sort_by(|a, b| b.path.cmp(&a.path));
+    Ok(backups)
+}
+
+fn prune() {
+    let Ok(backups) = list() else { return };
+    for old in backups.iter().skip(RETENTION) {
+        if let Err(e) = std::fs::remove_file(&old.path) {
+            debug_log(&format!("Failed to remove old backup {:?}: {}", old.path, e));
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+/// Put the files from `backup` back into `data_dir`. The app must not be running.
+pub fn restore(backup: &Path, data_dir: &Path) -> Result<(), String> {
+    let backups = backups_dir()?;
+    if backup.parent() != Some(backups.as_path()) {
+        return Err(format!("{} is not one of the installer's backups", backup.display()));
+    }
+    let file = std::fs::File::open(backup).map_err(|e| format!("Failed to open backup {:?}: {}", backup, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    if archive.by_name(DATABASE_FILE).is_err() {
+        return Err(format!("{} does not contain a library database", backup.display()));
+    }
+
+    // Unpack everything next to the originals first, so a failure leaves them untouched
+    let mut extracted = Vec::new();
+    let unpacked = BACKUP_FILES.iter().try_for_each(|name| {
+        let Ok(mut entry) = archive.by_name(name) else { return Ok(()) };
+        let temp = data_dir.join(format!("{}{}", name, RESTORE_SUFFIX));
+        extracted.push((temp.clone(), data_dir.join(name)));
+        let mut out = std::fs::File::create(&temp).map_err(|e| format!("Failed to create {:?}: {}", temp, e))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        out.sync_all().map_err(|e| format!("Failed to write {:?}: {}", temp, e))
+    });
+    if let Err(e) = unpacked {
+        for (temp, _) in &extracted {
+            let _ = std::fs::remove_file(temp);
+        }
+        return Err(e);
+    }
+
+    // The side files belong to the database being replaced; stale ones would corrupt it,
+    // so every original is moved aside, not just those the backup has
+    let mut moved = Vec::new();
+    let swapped = BACKUP_FILES
+        .iter()
+        .map(|name| data_dir.join(name))
+        .filter(|original| original.exists())
+        .try_for_each(|original| {
+            let aside = PathBuf::from(format!("{}{}", original.display(), ASIDE_SUFFIX));
+            std::fs::rename(&original, &aside).map_err(|e| format!("Failed to move {:?} aside: {}", original, e))?;
+            moved.push((aside, original));
+            Ok(())
+        })
+        .and_then(|()| {
+            extracted.iter().try_for_each(|(temp, target)| {
+                std::fs::rename(temp, target).map_err(|e| format!("Failed to restore {:?}: {}", target, e))
+            })
+        });
+    if let Err(e) = swapped {
+        for (temp, target) in &extracted {
+            // A temp file that's gone was already renamed over the target
+            match std::fs::remove_file(temp) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    let _ = std::fs::remove_file(target);
+                }
+                _ => {}
+            }
+        }
+        for (aside, original) in &moved {
+            if let Err(e) = std::fs::rename(aside, original) {
+                debug_log(&format!("Failed to put {:?} back: {}", original, e));
+            }
+        }
+        return Err(e);
+    }
+    for (aside, _) in &moved {
+        let _ = std::fs::remove_file(aside);
+    }
+    debug_log(&format!("Restored user data from {:?}", backup));
+    Ok(())
+}
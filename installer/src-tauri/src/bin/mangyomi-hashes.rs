@@ -0,0 +1,6 @@
+//! Build tool that writes the payload's hash manifest (`hashes.json`) for an unpacked app:
+//! `cargo run --release --no-default-features --bin mangyomi-hashes -- <app dir>`
+
+fn main() {
+    mangyomi_installer_lib::generate_hashes()
+}
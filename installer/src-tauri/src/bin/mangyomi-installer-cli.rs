@@ -0,0 +1,7 @@
+//! Console build of the installer for the silent install, update and uninstall paths.
+//! Build it without the webview:
+//! `cargo build --release --no-default-features --features cli --bin mangyomi-installer-cli`
+
+fn main() {
+    mangyomi_installer_lib::run()
+}
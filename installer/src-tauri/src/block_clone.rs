@@ -0,0 +1,104 @@
+//! File copies that share storage with the original where the volume allows it. On ReFS,
+//! which Dev Drive uses, FSCTL_DUPLICATE_EXTENTS_TO_FILE makes the copy point at the
+//! source's clusters instead of writing the data again, so copying a few hundred MB
+//! takes no time and no space. Anywhere else this is a plain copy; `std::fs::copy`
+//! already clones on Linux (copy_file_range) and macOS (clonefile).
+//!
+//! Used for the copies the installer makes of whole files: the installer kept in the
+//! update cache and an install moved by relocation. Updates don't copy anything: files
+//! matching the payload's hash manifest are left in place and only changed ones are
+//! written.
+
+use std::path::Path;
+
+#[cfg(windows)]
+use crate::debug_log;
+
+/// Copy `from` to `to`, cloning the blocks when the volume supports it. Returns the
+/// number of bytes copied, like `std::fs::copy`.
+#[cfg(windows)]
+pub fn copy(from: &Path, to: &Path) -> std::io::Result<u64> {
+    match clone(from, to) {
+        Ok(len) => Ok(len),
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::Unsupported {
+                debug_log(&format!("Block clone of {:?} failed, copying instead: {}", from, e));
+            }
+            std::fs::copy(from, to)
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn copy(from: &Path, to: &Path) -> std::io::Result<u64> {
+    std::fs::copy(from, to)
+}
+
+#[cfg(windows)]
+fn clone(from: &Path, to: &Path) -> std::io::Result<u64> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::GetVolumeInformationByHandleW;
+    use windows::Win32::System::IO::DeviceIoControl;
+    use windows::Win32::System::Ioctl::{
+        DUPLICATE_EXTENTS_DATA, FSCTL_DUPLICATE_EXTENTS_TO_FILE, FSCTL_GET_INTEGRITY_INFORMATION,
+        FSCTL_GET_INTEGRITY_INFORMATION_BUFFER,
+    };
+
+    const FILE_SUPPORTS_BLOCK_REFCOUNTING: u32 = 0x0800_0000;
+    /// Each request must stay under 4 GiB
+    const MAX_CHUNK: u64 = 1 << 31;
+
+    let source = std::fs::File::open(from)?;
+    let source_handle = HANDLE(source.as_raw_handle());
+    let mut flags = 0u32;
+    unsafe { GetVolumeInformationByHandleW(source_handle, None, None, None, Some(&mut flags), None) }?;
+    if flags & FILE_SUPPORTS_BLOCK_REFCOUNTING == 0 {
+        return Err(std::io::ErrorKind::Unsupported.into());
+    }
+
+    // Offsets and lengths must be multiples of the cluster size, except for the tail
+    let mut integrity = FSCTL_GET_INTEGRITY_INFORMATION_BUFFER::default();
+    unsafe {
+        DeviceIoControl(
+            source_handle,
+            FSCTL_GET_INTEGRITY_INFORMATION,
+            None,
+            0,
+            Some(&mut integrity as *mut _ as *mut _),
+            std::mem::size_of_val(&integrity) as u32,
+            None,
+            None,
+        )
+    }?;
+    let cluster = u64::from(integrity.ClusterSizeInBytes).max(1);
+
+    let len = source.metadata()?.len();
+    let target = std::fs::File::create(to)?;
+    target.set_len(len)?;
+    let mut offset = 0;
+    while offset < len {
+        let count = (len - offset).min(MAX_CHUNK);
+        let extents = DUPLICATE_EXTENTS_DATA {
+            FileHandle: source_handle,
+            SourceFileOffset: offset as i64,
+            TargetFileOffset: offset as i64,
+            // The last chunk rounds up past the end of the file, which the call allows
+            ByteCount: count.div_ceil(cluster) as i64 * cluster as i64,
+        };
+        unsafe {
+            DeviceIoControl(
+                HANDLE(target.as_raw_handle()),
+                FSCTL_DUPLICATE_EXTENTS_TO_FILE,
+                Some(&extents as *const _ as *const _),
+                std::mem::size_of_val(&extents) as u32,
+                None,
+                0,
+                None,
+                None,
+            )
+        }?;
+        offset += count;
+    }
+    Ok(len)
+}
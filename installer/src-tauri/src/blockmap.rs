@@ -0,0 +1,88 @@
+//! Blockmaps for differential updates: the gzipped JSON the app's updater
+//! (electron/updater.ts) compares block by block to fetch only what changed.
+//!
+//! `generate-blockmap <file> [--out <path>] [--block-size <bytes>]` writes one for a
+//! release artifact, so release tooling and the installer share this implementation.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::hash;
+
+pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockmapFile {
+    pub name: String,
+    pub offset: u64,
+    pub checksum_block_size: usize,
+    pub sizes: Vec<usize>,
+    pub checksums: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blockmap {
+    pub version: String,
+    pub block_size: usize,
+    pub files: Vec<BlockmapFile>,
+}
+
+/// Hash `path` in fixed-size blocks; the last one may be shorter
+pub fn generate(path: &Path, block_size: usize) -> Result<Blockmap, String> {
+    if block_size == 0 {
+        return Err("Block size must be positive".to_string());
+    }
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut sizes = Vec::new();
+    let mut checksums = Vec::new();
+    let mut buf = vec![0u8; block_size];
+    loop {
+        // read() may return short counts before the end; fill the block unless at EOF
+        let mut filled = 0;
+        while filled < block_size {
+            let n = file.read(&mut buf[filled..]).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        if filled == 0 {
+            break;
+        }
+        sizes.push(filled);
+        checksums.push(hash::sha256_bytes(&buf[..filled]));
+    }
+
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    Ok(Blockmap {
+        version: "2".to_string(),
+        block_size,
+        files: vec![BlockmapFile { name, offset: 0, checksum_block_size: block_size, sizes, checksums }],
+    })
+}
+
+/// Write `blockmap` gzip-compressed, as the updater expects
+pub fn write(blockmap: &Blockmap, out: &Path) -> Result<(), String> {
+    let json = serde_json::to_vec(blockmap).map_err(|e| e.to_string())?;
+    let file = std::fs::File::create(out).map_err(|e| format!("Failed to create {:?}: {}", out, e))?;
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    encoder.write_all(&json).and_then(|_| encoder.finish().map(drop)).map_err(|e| format!("Failed to write {:?}: {}", out, e))
+}
+
+/// Run the `generate-blockmap` subcommand with the arguments after it
+pub fn run(args: &[String]) -> Result<PathBuf, String> {
+    let usage = "Usage: generate-blockmap <file> [--out <path>] [--block-size <bytes>]";
+    let input = args.first().filter(|a| !a.starts_with("--")).ok_or(usage)?;
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+    let block_size = match option("--block-size") {
+        Some(value) => value.parse().map_err(|_| format!("Invalid block size: {}", value))?,
+        None => DEFAULT_BLOCK_SIZE,
+    };
+    let out = option("--out").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(format!("{}.blockmap", input)));
+
+    let blockmap = generate(Path::new(input), block_size)?;
+    write(&blockmap, &out)?;
+    Ok(out)
+}
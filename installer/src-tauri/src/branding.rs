@@ -0,0 +1,121 @@
+//! Product names used throughout the installer, so forks and renamed builds can reuse
+//! it without patching string literals.
+//!
+//! Values come from `branding.json` bundled in `resources/` next to the installer, then
+//! from JSON baked in at compile time through `INSTALLER_BRANDING`, then the defaults below.
+//! Every field is optional; missing ones keep the Mangyomi value.
+
+use std::sync::OnceLock;
+
+use crate::cert_pins;
+
+pub const BRANDING_FILE: &str = "branding.json";
+
+/// Shell links on Windows, symlinks to the bundle on macOS, desktop entries elsewhere
+const SHORTCUT_EXTENSION: &str = if cfg!(windows) {
+    ".lnk"
+} else if cfg!(target_os = "macos") {
+    ""
+} else {
+    ".desktop"
+};
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Branding {
+    /// Display name used in shortcuts, dialogs and notifications
+    pub app_name: String,
+    /// Main executable inside the install directory (inside the bundle on macOS)
+    pub exe_name: String,
+    /// Must match `appId` in electron-builder.config.js, which the app also sets at startup
+    pub app_user_model_id: String,
+    /// Folder under %APPDATA% holding logs and the update cache
+    pub data_dir_name: String,
+    /// Folder created under the install root (and the Start Menu); the .app bundle on macOS
+    pub install_dir_name: String,
+    /// Endpoint for opt-in install telemetry; none means the option isn't offered
+    pub telemetry_url: Option<String>,
+    /// Endpoint log bundles are uploaded to; none means the option isn't offered
+    pub support_url: Option<String>,
+    /// URL scheme the app handles (`<scheme>://`), registered with the desktop on Linux
+    pub url_scheme: String,
+    /// Shown in Apps & Features, with the links below
+    pub publisher: String,
+    pub about_url: Option<String>,
+    pub help_url: Option<String>,
+    /// Public keys expected from download hosts; see cert_pins
+    pub certificate_pins: cert_pins::Pins,
+    /// Subject of the certificate releases are Authenticode-signed with. A replacement
+    /// installer stub must carry it; none means stub updates are refused.
+    pub code_signing_subject: Option<String>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            app_name: "Mangyomi".to_string(),
+            exe_name: match std::env::consts::OS {
+                "windows" => "Mangyomi.exe",
+                "macos" => "Contents/MacOS/Mangyomi",
+                _ => "mangyomi",
+            }
+            .to_string(),
+            app_user_model_id: "com.mangyomi.app".to_string(),
+            // Electron names userData after productName; only Windows ignores the case
+            data_dir_name: if cfg!(windows) { "mangyomi" } else { "Mangyomi" }.to_string(),
+            install_dir_name: match std::env::consts::OS {
+                "windows" => "Mangyomi",
+                "macos" => "Mangyomi.app",
+                _ => "mangyomi",
+            }
+            .to_string(),
+            telemetry_url: None,
+            support_url: None,
+            url_scheme: "mangyomi".to_string(),
+            publisher: "Mangyomi".to_string(),
+            about_url: Some("https://github.com/Mangyomi/mangyomi-application".to_string()),
+            help_url: Some("https://github.com/Mangyomi/mangyomi-application/issues".to_string()),
+            certificate_pins: cert_pins::Pins::new(),
+            code_signing_subject: None,
+        }
+    }
+}
+
+impl Branding {
+    pub fn uninstaller_exe(&self) -> String {
+        if cfg!(windows) {
+            format!("Uninstall {}.exe", self.app_name)
+        } else {
+            format!("uninstall-{}", self.exe_name)
+        }
+    }
+
+    pub fn shortcut_name(&self) -> String {
+        format!("{}{}", self.app_name, SHORTCUT_EXTENSION)
+    }
+
+    /// File name for a shortcut called `name`, without characters file names can't hold
+    pub fn named_shortcut(&self, name: &str) -> String {
+        let name: String = name.chars().map(|c| if "\\/:*?\"<>|".contains(c) { '_' } else { c }).collect();
+        format!("{}{}", name.trim(), SHORTCUT_EXTENSION)
+    }
+
+    pub fn uninstall_shortcut_name(&self) -> String {
+        format!("Uninstall {}{}", self.app_name, SHORTCUT_EXTENSION)
+    }
+}
+
+fn load() -> Branding {
+    // No debug_log here: the log location itself depends on the branding
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("resources").join(BRANDING_FILE)))
+        .and_then(|path| std::fs::read_to_string(path).ok());
+    let data = bundled.or_else(|| option_env!("INSTALLER_BRANDING").map(str::to_string));
+    data.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
+}
+
+pub fn get() -> &'static Branding {
+    static BRANDING: OnceLock<Branding> = OnceLock::new();
+    BRANDING.get_or_init(load)
+}
@@ -0,0 +1,69 @@
+//! `--version [--json]`: what this installer is and what it can do. The app's updater may
+//! find an older installer stub in the update cache, and asks it before relying on a flag
+//! or a payload format it has.
+
+use std::collections::BTreeMap;
+
+use crate::{bundled_payload, payload, silent};
+
+/// Command-line flags this build understands
+const FLAGS: &[&str] = &[
+    "--accept-license",
+    "--all-users-start-menu",
+    "--arch",
+    "--cache-size-limit",
+    "--cached-base",
+    "--components",
+    "--defer-updates",
+    "--delete-installer",
+    "--dry-run",
+    "--event-log",
+    "--finish-pending",
+    "--first-run",
+    "--fix",
+    "--force-install-path",
+    "--install-path",
+    "--kill-timeout",
+    "--lang",
+    "--list-payload",
+    "--modify",
+    "--motw",
+    "--no-desktop-shortcut",
+    "--no-launch",
+    "--purge",
+    "--release-manifest",
+    "--relocate",
+    "--sfx-path",
+    "--silent",
+    "--start-menu-folder",
+    "--telemetry",
+    "--uninstall",
+    "--update-channel",
+    "--update-stub",
+    "--update-url",
+    "--verify",
+    "--verify-only",
+    "--version",
+];
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub installer_version: &'static str,
+    /// Version of the app this installer carries, if it carries one
+    pub payload_version: Option<String>,
+    pub payload_formats: &'static [&'static str],
+    pub flags: &'static [&'static str],
+    /// Exit code names and values
+    pub exit_codes: BTreeMap<String, i32>,
+}
+
+pub fn collect() -> Capabilities {
+    Capabilities {
+        installer_version: env!("CARGO_PKG_VERSION"),
+        payload_version: bundled_payload().and_then(|(path, is_7z)| payload::read_version(&path, is_7z)),
+        payload_formats: &["7z", "zip"],
+        flags: FLAGS,
+        exit_codes: silent::ExitCode::ALL.iter().map(|&code| (format!("{:?}", code), code as i32)).collect(),
+    }
+}
@@ -0,0 +1,58 @@
+//! Certificate pinning for the installer's downloads. A build can pin the public keys it
+//! expects from its update hosts (`certificatePins` in branding.json), so that on a
+//! hostile network a certificate issued by a rogue or compromised CA, or injected by an
+//! intercepting proxy, fails the download even though Windows trusts it. A pin matches any
+//! certificate in the server's chain, so pinning the issuing CA survives leaf renewals.
+//!
+//! Managed networks that inspect TLS set their own pins in the deployment policy, which
+//! replace the build's; an empty object there turns pinning off.
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+use crate::{branding, debug_log, hash, policy};
+
+/// Host patterns (`updates.example.com`, `*.example.com`) to `sha256/<base64>` hashes of
+/// acceptable SubjectPublicKeyInfo structures
+pub type Pins = HashMap<String, Vec<String>>;
+
+fn matches_host(pattern: &str, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    let pattern = pattern.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.strip_suffix(domain).is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+/// The pins for `host`: the policy's when it sets any, otherwise the build's. Empty when
+/// the host isn't pinned.
+pub fn for_host(host: &str) -> Vec<String> {
+    let pins = policy::get().certificate_pins.as_ref().unwrap_or(&branding::get().certificate_pins);
+    pins.iter().filter(|(pattern, _)| matches_host(pattern, host)).flat_map(|(_, pins)| pins.iter().cloned()).collect()
+}
+
+/// The pin of a DER-encoded SubjectPublicKeyInfo, as `openssl x509 -pubkey | openssl pkey
+/// -pubin -outform der | openssl dgst -sha256 -binary | base64` prints it, with `sha256/`
+pub fn spki_pin(spki_der: &[u8]) -> String {
+    format!("sha256/{}", hash::base64(&Sha256::digest(spki_der)))
+}
+
+/// Check the public keys of the certificate chain `host` presented against its pins
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn check(host: &str, chain: &[Vec<u8>]) -> Result<(), String> {
+    let pins = for_host(host);
+    if pins.is_empty() {
+        return Ok(());
+    }
+    let presented: Vec<String> = chain.iter().map(|spki| spki_pin(spki)).collect();
+    if presented.iter().any(|pin| pins.contains(pin)) {
+        return Ok(());
+    }
+    debug_log(&format!("Certificate pin mismatch for {}: presented {:?}, pinned {:?}", host, presented, pins));
+    Err(format!(
+        "The certificate presented by {} doesn't match the keys pinned for it; the connection may be intercepted",
+        host
+    ))
+}
@@ -0,0 +1,66 @@
+//! Removing what the SFX leaves behind. The 7-Zip SFX module extracts the installer into
+//! a folder under %TEMP% and never deletes it; after a successful install a detached
+//! helper removes that folder (and with `--delete-installer` the downloaded installer
+//! too) once this process has exited.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{debug_log, SFX_PATH};
+
+static DELETE_INSTALLER: AtomicBool = AtomicBool::new(false);
+
+/// Also remove the SFX installer itself (`--delete-installer`)
+pub fn delete_installer() {
+    DELETE_INSTALLER.store(true, Ordering::Relaxed);
+}
+
+/// The SFX extraction folder we run from, if any. Only a folder under the temp
+/// directory counts, so a manually unpacked installer is never removed.
+fn extraction_dir() -> Option<PathBuf> {
+    SFX_PATH.lock().ok()?.as_ref()?;
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let temp = std::env::temp_dir();
+    (dir != temp && dir.starts_with(&temp)).then_some(dir)
+}
+
+/// Schedule removal of the extraction folder, and the installer when requested
+pub fn schedule() {
+    let mut paths: Vec<PathBuf> = extraction_dir().into_iter().collect();
+    if DELETE_INSTALLER.load(Ordering::Relaxed) {
+        paths.extend(SFX_PATH.lock().ok().and_then(|p| p.clone()).map(PathBuf::from));
+    }
+    if paths.is_empty() {
+        return;
+    }
+    debug_log(&format!("Scheduling removal of {:?}", paths));
+    if let Err(e) = spawn_helper(&paths) {
+        debug_log(&format!("Failed to schedule cleanup: {}", e));
+    }
+}
+
+/// A hidden PowerShell that waits for us to exit, since files of a running exe can't go
+#[cfg(windows)]
+fn spawn_helper(paths: &[PathBuf]) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let quoted: Vec<String> = paths.iter().map(|p| format!("'{}'", p.display().to_string().replace('\'', "''"))).collect();
+    let script = format!(
+        "Wait-Process -Id {} -ErrorAction SilentlyContinue; Remove-Item -LiteralPath {} -Recurse -Force -ErrorAction SilentlyContinue",
+        std::process::id(),
+        quoted.join(",")
+    );
+    std::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+/// The SFX only exists on Windows
+#[cfg(not(windows))]
+fn spawn_helper(_paths: &[PathBuf]) -> Result<(), String> {
+    Ok(())
+}
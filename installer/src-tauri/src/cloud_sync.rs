@@ -0,0 +1,145 @@
+//! Cloud-synced install locations. An app installed under a OneDrive, Dropbox or Google
+//! Drive folder (often the synced Documents folder, picked without realizing it) gets
+//! every update uploaded, has its files locked by the sync client while it runs and uses
+//! up the user's storage quota. The install isn't refused; the window asks before using
+//! such a folder and a silent run prints a warning.
+
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    OneDrive,
+    Dropbox,
+    GoogleDrive,
+}
+
+impl Provider {
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::OneDrive => "OneDrive",
+            Provider::Dropbox => "Dropbox",
+            Provider::GoogleDrive => "Google Drive",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Risk {
+    /// Every install and update is uploaded, and downloaded again on the user's other machines
+    SyncChurn,
+    /// The sync client holds files open, so updates can't replace them
+    FileLocking,
+    /// The app counts against the user's cloud storage
+    QuotaUsage,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncWarning {
+    pub provider: Provider,
+    pub sync_root: PathBuf,
+    pub risks: Vec<Risk>,
+}
+
+impl SyncWarning {
+    pub fn message(&self, install_path: &str) -> String {
+        format!("{} is inside the {} folder {}", install_path, self.provider.name(), self.sync_root.display())
+    }
+}
+
+/// The sync root `install_path` lies under, if any
+pub fn check(install_path: &str) -> Option<SyncWarning> {
+    let path = Path::new(install_path);
+    let (provider, sync_root) = sync_roots().into_iter().find(|(_, root)| is_under(path, root)).or_else(|| {
+        google_drive_volume(path).map(|root| (Provider::GoogleDrive, root))
+    })?;
+    Some(SyncWarning { provider, sync_root, risks: vec![Risk::SyncChurn, Risk::FileLocking, Risk::QuotaUsage] })
+}
+
+/// Drive letters and folder names compare case-insensitively on Windows
+fn is_under(path: &Path, root: &Path) -> bool {
+    if cfg!(windows) {
+        let lower = |p: &Path| PathBuf::from(p.to_string_lossy().to_lowercase());
+        lower(path).starts_with(lower(root))
+    } else {
+        path.starts_with(root)
+    }
+}
+
+fn home() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var).map(PathBuf::from)
+}
+
+fn sync_roots() -> Vec<(Provider, PathBuf)> {
+    let mut roots = Vec::new();
+    // Set by the OneDrive client for the personal and work accounts
+    for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(root) = std::env::var_os(var).filter(|v| !v.is_empty()) {
+            roots.push((Provider::OneDrive, PathBuf::from(root)));
+        }
+    }
+    roots.extend(dropbox_roots().into_iter().map(|root| (Provider::Dropbox, root)));
+    if let Some(home) = home() {
+        // Google Drive's mirrored folders, and macOS File Provider locations for all three
+        for name in ["Google Drive", "My Drive"] {
+            roots.push((Provider::GoogleDrive, home.join(name)));
+        }
+        if let Ok(entries) = std::fs::read_dir(home.join("Library").join("CloudStorage")) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let provider = match name.split('-').next() {
+                    Some("OneDrive") => Provider::OneDrive,
+                    Some("Dropbox") => Provider::Dropbox,
+                    Some("GoogleDrive") => Provider::GoogleDrive,
+                    _ => continue,
+                };
+                roots.push((provider, entry.path()));
+            }
+        }
+    }
+    roots
+}
+
+/// The folders in Dropbox's `info.json`, one per linked account
+fn dropbox_roots() -> Vec<PathBuf> {
+    let candidates: Vec<PathBuf> = if cfg!(windows) {
+        ["LOCALAPPDATA", "APPDATA"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(|dir| PathBuf::from(dir).join("Dropbox").join("info.json"))
+            .collect()
+    } else {
+        home().map(|home| home.join(".dropbox").join("info.json")).into_iter().collect()
+    };
+    let Some(info) = candidates.iter().find_map(|path| std::fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    let Ok(serde_json::Value::Object(accounts)) = serde_json::from_str(&info) else {
+        return Vec::new();
+    };
+    accounts.values().filter_map(|account| account.get("path")?.as_str()).map(PathBuf::from).collect()
+}
+
+/// Google Drive for desktop streams files through a drive letter of its own, labelled
+/// "Google Drive"
+#[cfg(windows)]
+fn google_drive_volume(path: &Path) -> Option<PathBuf> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetVolumeInformationW;
+
+    let std::path::Component::Prefix(prefix) = path.components().next()? else { return None };
+    let std::path::Prefix::Disk(letter) = prefix.kind() else { return None };
+    let root = format!("{}:\\", char::from(letter));
+    let mut label = [0u16; 261];
+    unsafe { GetVolumeInformationW(&HSTRING::from(root.as_str()), Some(&mut label), None, None, None, None) }.ok()?;
+    let len = label.iter().position(|&c| c == 0).unwrap_or(label.len());
+    (String::from_utf16_lossy(&label[..len]) == "Google Drive").then(|| PathBuf::from(root))
+}
+
+#[cfg(not(windows))]
+fn google_drive_volume(_path: &Path) -> Option<PathBuf> {
+    None
+}
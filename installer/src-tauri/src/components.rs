@@ -0,0 +1,171 @@
+//! Optional parts of the install: bundled ones (locales, themes) that are extracted from
+//! the payload only when selected, and downloaded ones (the media player binaries) that
+//! are fetched at install time instead of being bundled.
+//!
+//! Components are described by `components.json` shipped with the payload (sidecar or
+//! archive root). Downloads are pinned to a SHA-256 so nothing unverified is installed.
+//! The selection is saved with the install options, so updates and repairs keep it.
+
+use std::path::{Path, PathBuf};
+
+use crate::{debug_log, download, extract, hash, payload};
+
+pub const COMPONENTS_FILE: &str = "components.json";
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentKind {
+    Core,
+    Player,
+    Codecs,
+    Locales,
+    Themes,
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Component {
+    /// Identifier used by the UI and `--components=`
+    pub id: String,
+    pub name: String,
+    pub kind: ComponentKind,
+    /// Always installed, whatever the selection
+    pub required: bool,
+    /// Preselected in the installer UI
+    pub selected_by_default: bool,
+    /// Components that must be installed along with this one
+    pub depends_on: Vec<String>,
+    /// Size in bytes (download size for downloaded components), for the UI
+    pub size: u64,
+    /// Archive path prefixes of a bundled component's files
+    pub paths: Vec<String>,
+    /// .zip or .7z archive to download, for components not bundled in the payload
+    pub url: Option<String>,
+    pub sha256: Option<String>,
+    /// Install-relative directory a download is extracted into
+    pub target_dir: String,
+}
+
+impl Component {
+    fn contains(&self, entry_name: &str) -> bool {
+        let entry = payload::normalize_entry_name(entry_name);
+        self.paths.iter().any(|prefix| {
+            let prefix = payload::normalize_entry_name(prefix);
+            let prefix = prefix.trim_end_matches('/');
+            entry == prefix || entry.starts_with(&format!("{}/", prefix))
+        })
+    }
+
+    /// Whether an installed file came from this component, bundled or downloaded
+    fn owns(&self, file: &str) -> bool {
+        let target = payload::normalize_entry_name(&self.target_dir);
+        let target = target.trim_end_matches('/');
+        let downloaded = self.url.is_some() && !target.is_empty() && payload::normalize_entry_name(file).starts_with(&format!("{}/", target));
+        downloaded || self.contains(file)
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct ComponentsManifest {
+    #[serde(default)]
+    pub components: Vec<Component>,
+}
+
+impl ComponentsManifest {
+    /// The components to install for the selected `ids`: required ones, the selection and
+    /// everything they depend on, in manifest order
+    pub fn resolve(&self, ids: &[String]) -> Vec<&Component> {
+        let mut wanted: Vec<&str> = self.components.iter().filter(|c| c.required).map(|c| c.id.as_str()).collect();
+        let mut pending: Vec<&str> = ids.iter().map(String::as_str).collect();
+        while let Some(id) = pending.pop() {
+            if wanted.contains(&id) {
+                continue;
+            }
+            match self.components.iter().find(|c| c.id == id) {
+                Some(component) => {
+                    wanted.push(&component.id);
+                    pending.extend(component.depends_on.iter().map(String::as_str));
+                }
+                None => debug_log(&format!("Unknown component: {}", id)),
+            }
+        }
+        self.components.iter().filter(|c| wanted.contains(&c.id.as_str())).collect()
+    }
+
+    /// False for payload entries that belong to a bundled component outside `selected`
+    pub fn includes_entry(&self, selected: &[&Component], entry_name: &str) -> bool {
+        !self
+            .components
+            .iter()
+            .filter(|c| !selected.iter().any(|s| s.id == c.id))
+            .any(|c| c.contains(entry_name))
+    }
+}
+
+impl ComponentsManifest {
+    /// Files of an earlier install that belong to components outside the selection `ids`,
+    /// for removal when a component is deselected
+    pub fn deselected_files(&self, ids: &[String], installed: &[String]) -> Vec<String> {
+        let selected = self.resolve(ids);
+        let deselected: Vec<&Component> = self.components.iter().filter(|c| !selected.iter().any(|s| s.id == c.id)).collect();
+        installed.iter().filter(|file| deselected.iter().any(|c| c.owns(file))).cloned().collect()
+    }
+}
+
+pub fn read_manifest(payload_path: &Path, is_7z: bool) -> ComponentsManifest {
+    payload::read_json(payload_path, is_7z, COMPONENTS_FILE).unwrap_or_default()
+}
+
+/// Parse `--components=player,codecs` (or `--components player,codecs`)
+pub fn parse_arg(args: &[String]) -> Option<Vec<String>> {
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        arg.strip_prefix("--components=")
+            .map(str::to_string)
+            .or_else(|| (arg == "--components").then(|| args.get(i + 1).cloned()).flatten())
+    })?;
+    Some(value.split(',').map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).collect())
+}
+
+fn download_path(component: &Component, url: &str) -> PathBuf {
+    let extension = if url.ends_with(".7z") { "7z" } else { "zip" };
+    std::env::temp_dir().join(format!("mangyomi-component-{}.{}", component.id, extension))
+}
+
+/// Download, verify and extract a downloaded `component` into the install directory, calling
+/// `progress` with the bytes downloaded so far. Returns the install-relative files written.
+pub fn install(install_path: &str, component: &Component, progress: impl FnMut(u64, Option<u64>)) -> Result<Vec<String>, String> {
+    let (Some(url), Some(sha256)) = (&component.url, &component.sha256) else {
+        return Err(format!("Component {} has no pinned download", component.id));
+    };
+    let archive = download_path(component, url);
+    debug_log(&format!("Downloading component {} from {}", component.id, url));
+    download::download_file(url, &archive, progress)?;
+
+    let result = (|| {
+        let actual = hash::sha256_file(&archive)?;
+        if !actual.eq_ignore_ascii_case(sha256) {
+            return Err(format!("Component {} failed verification: expected {}, got {}", component.id, sha256, actual));
+        }
+
+        let target = Path::new(install_path).join(&component.target_dir);
+        std::fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+        let target = target.to_string_lossy();
+        let stats = if archive.extension().is_some_and(|ext| ext == "7z") {
+            extract::extract_7z(&archive, &target, None, None, None)?
+        } else {
+            extract::extract_zip(&archive, &target, None, None, None)?
+        };
+        let prefix = component.target_dir.trim_end_matches(['/', '\\']).replace('\\', "/");
+        Ok(stats
+            .files
+            .into_iter()
+            .map(|file| if prefix.is_empty() { file } else { format!("{}/{}", prefix, file) })
+            .collect())
+    })();
+
+    let _ = std::fs::remove_file(&archive);
+    result
+}
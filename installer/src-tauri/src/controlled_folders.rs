@@ -0,0 +1,64 @@
+//! Controlled Folder Access, Defender's ransomware protection. With it on, unknown programs
+//! can't write to Documents, Desktop, Pictures and the other protected folders, and every
+//! write fails with a plain "Access denied" that retrying or elevating won't fix. The
+//! install location is probed once before anything is extracted, so a blocked install
+//! stops with instructions to allow the installer instead of a generic error, and silent
+//! runs exit with their own code so the app's updater doesn't keep retrying.
+
+use std::path::Path;
+
+use crate::debug_log;
+
+const ERROR_ACCESS_DENIED: i32 = 5;
+
+/// Whether Controlled Folder Access is on, by policy or in Windows Security
+#[cfg(windows)]
+pub fn is_enabled() -> bool {
+    // 1 blocks; 2 (audit mode) only logs, and the other values leave it off
+    const ENABLED: u32 = 1;
+    const KEYS: &[&str] = &[
+        "SOFTWARE\\Policies\\Microsoft\\Windows Defender\\Windows Defender Exploit Guard\\Controlled Folder Access",
+        "SOFTWARE\\Microsoft\\Windows Defender\\Windows Defender Exploit Guard\\Controlled Folder Access",
+    ];
+    KEYS.iter()
+        .find_map(|key| crate::registry::get_machine_dword(key, "EnableControlledFolderAccess"))
+        .is_some_and(|value| value == ENABLED)
+}
+
+#[cfg(not(windows))]
+pub fn is_enabled() -> bool {
+    false
+}
+
+/// An access-denied error while Controlled Folder Access is on
+pub fn is_blocked(e: &std::io::Error) -> bool {
+    e.raw_os_error() == Some(ERROR_ACCESS_DENIED) && is_enabled()
+}
+
+/// What to tell the user when writing to `install_path` was blocked
+pub fn blocked_message(install_path: &str) -> String {
+    let installer = std::env::current_exe().map(|exe| exe.display().to_string()).unwrap_or_default();
+    format!(
+        "Windows Controlled Folder Access blocked writing to {}. Allow {} in Windows Security > Virus & threat \
+         protection > Ransomware protection > Allow an app through Controlled folder access, or choose a folder \
+         outside Documents and Desktop.",
+        install_path, installer
+    )
+}
+
+/// Create `install_path` and write a file into it. Errors with `blocked_message` only when
+/// Controlled Folder Access is what stopped it; other failures are left to the install
+/// steps to report.
+pub fn probe(install_path: &str) -> Result<(), String> {
+    let dir = Path::new(install_path);
+    let test_file = dir.join(".write-test");
+    let result = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&test_file, b""));
+    let _ = std::fs::remove_file(&test_file);
+    match result {
+        Err(e) if is_blocked(&e) => {
+            debug_log(&format!("Controlled Folder Access blocked {}: {}", install_path, e));
+            Err(blocked_message(install_path))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,55 @@
+//! Panic handling: instead of the raw abort, a panic anywhere in the installer is logged
+//! with its backtrace, reported to the UI as `install-error` when the window is up, and
+//! ends the process with `ExitCode::InternalError`.
+
+#[cfg(feature = "gui")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "gui")]
+use tauri::Emitter;
+
+use crate::debug_log;
+use crate::silent::{self, ExitCode};
+
+/// Set once the Tauri runtime is running, so the hook can reach the UI
+#[cfg(feature = "gui")]
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+#[cfg(feature = "gui")]
+#[derive(Clone, serde::Serialize)]
+struct InstallError {
+    message: String,
+}
+
+#[cfg(feature = "gui")]
+pub fn set_app_handle(app_handle: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(app_handle);
+}
+
+pub fn install_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = match info.location() {
+            Some(location) => format!("Internal error at {}: {}", location, panic_message(info)),
+            None => format!("Internal error: {}", panic_message(info)),
+        };
+        debug_log(&format!("PANIC: {}\n{}", message, std::backtrace::Backtrace::force_capture()));
+
+        #[cfg(feature = "gui")]
+        if let Some(app_handle) = APP_HANDLE.get() {
+            let _ = app_handle.emit("install-error", InstallError { message: message.clone() });
+            // Give the webview a moment to show it before the window goes away
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+        silent::finish(ExitCode::InternalError, Some(&message));
+    }));
+}
+
+fn panic_message(info: &std::panic::PanicHookInfo) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
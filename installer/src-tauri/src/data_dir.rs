@@ -0,0 +1,58 @@
+//! Where the app keeps its library, covers and downloads.
+//!
+//! By default that is Electron's userData folder (`%APPDATA%\<data dir>`). A different
+//! location chosen at install time is written to `data-location.json` in the install
+//! directory (Application Support on macOS), which the app reads before anything touches
+//! userData.
+
+use std::path::PathBuf;
+
+use crate::branding;
+use crate::known_folders::{self, KnownFolder};
+use crate::manifest;
+
+pub const DATA_LOCATION_FILE: &str = "data-location.json";
+
+/// Database file the app keeps in its data directory
+pub const DATABASE_FILE: &str = "mangyomi.db";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataLocation {
+    data_dir: PathBuf,
+}
+
+/// `%APPDATA%\<data dir>`, the app's default userData folder
+pub fn default_dir() -> Result<PathBuf, String> {
+    let appdata = known_folders::get(KnownFolder::RoamingAppData).ok_or("APPDATA not found")?;
+    Ok(appdata.join(&branding::get().data_dir_name))
+}
+
+/// The data directory the install at `install_path` uses
+pub fn resolve(install_path: Option<&str>) -> Result<PathBuf, String> {
+    let configured = install_path
+        .and_then(|p| std::fs::read_to_string(manifest::state_dir(p).join(DATA_LOCATION_FILE)).ok())
+        .and_then(|json| serde_json::from_str::<DataLocation>(&json).ok());
+    match configured {
+        Some(location) => Ok(location.data_dir),
+        None => default_dir(),
+    }
+}
+
+#[cfg(feature = "gui")]
+/// Point the install at `data_dir`, creating it and checking it can be written to
+pub fn configure(install_path: &str, data_dir: &std::path::Path) -> Result<(), String> {
+    if !data_dir.is_absolute() {
+        return Err(format!("Data folder must be an absolute path: {}", data_dir.display()));
+    }
+    std::fs::create_dir_all(data_dir).map_err(|e| format!("Cannot create data folder {}: {}", data_dir.display(), e))?;
+    let probe = data_dir.join(".write-test");
+    std::fs::write(&probe, b"").map_err(|e| format!("Data folder {} is not writable: {}", data_dir.display(), e))?;
+    let _ = std::fs::remove_file(probe);
+
+    let json = serde_json::to_string_pretty(&DataLocation { data_dir: data_dir.to_path_buf() }).map_err(|e| e.to_string())?;
+    let dir = manifest::state_dir(install_path);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    std::fs::write(dir.join(DATA_LOCATION_FILE), json)
+        .map_err(|e| format!("Failed to write {}: {}", DATA_LOCATION_FILE, e))
+}
@@ -0,0 +1,62 @@
+//! Opt-in Microsoft Defender exclusion for the install directory, for users whose
+//! antivirus keeps quarantining the app's binaries. Never enabled by default or by
+//! silent installs: only the checkbox in the installer UI turns it on.
+//!
+//! Defender has no public COM API for this, so the elevated helper (see `elevate`)
+//! runs the Defender PowerShell cmdlets.
+
+use std::path::Path;
+
+use crate::elevate;
+
+/// Task names for `--elevated-task`
+pub const ADD_TASK: &str = "defender-exclude";
+pub const REMOVE_TASK: &str = "defender-unexclude";
+
+/// Exclude `install_path`, dropping the exclusion for `previous` (an earlier location of
+/// the same install) in the same elevated step. Asks for administrator rights.
+pub fn request_exclusion(install_path: &str, previous: Option<&str>) -> Result<(), String> {
+    let mut args = vec![install_path];
+    args.extend(previous);
+    elevate::run_task(ADD_TASK, &args)
+}
+
+/// Remove the exclusion for `install_path`, asking for administrator rights unless `silent`
+pub fn request_removal(install_path: &str, silent: bool) -> Result<(), String> {
+    if silent {
+        elevate::run_task_unattended(REMOVE_TASK, &[install_path])
+    } else {
+        elevate::run_task(REMOVE_TASK, &[install_path])
+    }
+}
+
+#[cfg(windows)]
+fn run_cmdlet(cmdlet: &str, path: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // Single-quoted PowerShell strings only need their quotes doubled
+    let script = format!("{} -ExclusionPath '{}'", cmdlet, path.to_string_lossy().replace('\'', "''"));
+    let status = std::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-ExecutionPolicy", "Bypass", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    if !status.success() {
+        return Err(format!("{} failed for {} ({})", cmdlet, path.display(), status));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn run_cmdlet(_cmdlet: &str, _path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+pub fn add_exclusion(path: &Path) -> Result<(), String> {
+    run_cmdlet("Add-MpPreference", path)
+}
+
+pub fn remove_exclusion(path: &Path) -> Result<(), String> {
+    run_cmdlet("Remove-MpPreference", path)
+}
@@ -0,0 +1,170 @@
+//! Delta payloads: zstd `--patch-from` patches for changed files plus full copies of new ones.
+//!
+//! A delta payload is a regular app.7z/app.zip carrying a `delta.json` (sidecar or at the
+//! archive root). Patches are produced at release time with
+//! `zstd --patch-from=<old file> <new file> --long=31 -o patches/<path>.zst`
+//! and applied here against the files of the existing install.
+
+use std::collections::BTreeMap;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::debug_log;
+use crate::durable;
+use crate::extract;
+use crate::hash;
+use crate::payload;
+
+pub const DELTA_MANIFEST_FILE: &str = "delta.json";
+
+/// Patched and added files are staged here (same volume as the install) before being moved into place
+const STAGING_DIR: &str = ".delta-staging";
+
+/// Largest window zstd allows; `--patch-from` on big files needs long-distance matching
+const PATCH_WINDOW_LOG_MAX: u32 = 31;
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum DeltaOp {
+    /// Full copy stored in the archive at the same path
+    Add,
+    /// zstd patch stored in the archive at `patch`, applied against the installed file
+    Patch { patch: String, base_sha256: String, sha256: String },
+    /// File no longer shipped
+    Remove,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DeltaManifest {
+    pub from_version: String,
+    pub to_version: String,
+    /// Operations keyed by install-relative path with `/` separators
+    pub files: BTreeMap<String, DeltaOp>,
+}
+
+impl DeltaManifest {
+    /// Update an install's file list (from its install manifest) to reflect this delta
+    pub fn apply_to_file_list(&self, files: &mut Vec<String>) {
+        for (name, op) in &self.files {
+            match op {
+                DeltaOp::Remove => files.retain(|f| f != name),
+                _ if !files.contains(name) => files.push(name.clone()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The delta description of a payload, or `None` for a regular full payload
+pub fn read_manifest(payload_path: &Path, is_7z: bool) -> Option<DeltaManifest> {
+    payload::read_json(payload_path, is_7z, DELTA_MANIFEST_FILE)
+}
+
+/// Apply a delta payload on top of the install at `install_path`.
+/// Nothing in the install is modified unless every patch applies and verifies.
+pub fn apply(payload_path: &Path, is_7z: bool, install_path: &str, delta: &DeltaManifest) -> Result<(), String> {
+    let installed = crate::read_installed_version(install_path);
+    if installed.as_deref() != Some(delta.from_version.as_str()) {
+        return Err(format!(
+            "Delta payload updates {} -> {} but the installed version is {}",
+            delta.from_version,
+            delta.to_version,
+            installed.as_deref().unwrap_or("unknown")
+        ));
+    }
+    debug_log(&format!("Applying delta {} -> {} ({} files)", delta.from_version, delta.to_version, delta.files.len()));
+
+    let staging = Path::new(install_path).join(STAGING_DIR);
+    let _ = std::fs::remove_dir_all(&staging);
+    let staging_str = staging.to_string_lossy().to_string();
+    if is_7z {
+        extract::extract_7z(payload_path, &staging_str, None, None, None)?;
+    } else {
+        extract::extract_zip(payload_path, &staging_str, None, None, None)?;
+    }
+
+    let result = apply_staged(&staging, Path::new(install_path), delta);
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+fn apply_staged(staging: &Path, install_root: &Path, delta: &DeltaManifest) -> Result<(), String> {
+    // Validate every path before touching anything, so one bad entry fails the whole delta
+    for (name, op) in &delta.files {
+        extract::check_relative(name)?;
+        if let DeltaOp::Patch { patch, .. } = op {
+            extract::check_relative(patch)?;
+        }
+    }
+
+    // Phase 1: build every new file in staging so a bad patch leaves the install untouched
+    let mut ready: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (name, op) in &delta.files {
+        let target = install_root.join(name);
+        match op {
+            DeltaOp::Add => ready.push((staging.join(name), target)),
+            DeltaOp::Patch { patch, base_sha256, sha256 } => {
+                let base_hash = hash::sha256_file(&target)?;
+                if !base_hash.eq_ignore_ascii_case(base_sha256) {
+                    return Err(format!("Cannot patch {}: installed file does not match the delta base", name));
+                }
+
+                let output = staging.join(name);
+                if let Some(parent) = output.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                apply_patch(&target, &staging.join(patch), &output)
+                    .map_err(|e| format!("Failed to patch {}: {}", name, e))?;
+
+                let patched_hash = hash::sha256_file(&output)?;
+                if !patched_hash.eq_ignore_ascii_case(sha256) {
+                    return Err(format!("Patched {} does not match the expected hash", name));
+                }
+                ready.push((output, target));
+            }
+            DeltaOp::Remove => {}
+        }
+    }
+
+    // Phase 2: move everything into place
+    for (src, dst) in &ready {
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let critical = dst.strip_prefix(install_root).is_ok_and(|rel| durable::is_critical(&rel.to_string_lossy()));
+        if critical {
+            durable::sync(src).and_then(|()| durable::replace(src, dst))
+        } else {
+            std::fs::rename(src, dst)
+        }
+        .map_err(|e| format!("Failed to replace {:?}: {}", dst, e))?;
+    }
+    for (name, op) in &delta.files {
+        if let DeltaOp::Remove = op {
+            match std::fs::remove_file(install_root.join(name)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => debug_log(&format!("Failed to remove {}: {}", name, e)),
+            }
+        }
+    }
+
+    debug_log(&format!("Delta applied: {} files replaced", ready.len()));
+    Ok(())
+}
+
+/// Decompress a zstd patch using the old file as the reference content
+fn apply_patch(base: &Path, patch: &Path, output: &Path) -> Result<(), String> {
+    let mut reference = Vec::new();
+    std::fs::File::open(base)
+        .and_then(|mut f| f.read_to_end(&mut reference))
+        .map_err(|e| format!("Failed to read {:?}: {}", base, e))?;
+
+    let patch_file = std::fs::File::open(patch).map_err(|e| format!("Failed to open {:?}: {}", patch, e))?;
+    let mut decoder = zstd::Decoder::with_dictionary(BufReader::new(patch_file), &reference).map_err(|e| e.to_string())?;
+    decoder.window_log_max(PATCH_WINDOW_LOG_MAX).map_err(|e| e.to_string())?;
+
+    let mut out = std::fs::File::create(output).map_err(|e| e.to_string())?;
+    std::io::copy(&mut decoder, &mut out).map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! The diagnostics bundle: the installer log, the last silent run's result and a system
+//! description, zipped together. Users who can't find their way to AppData can upload it
+//! to the project's support endpoint (`supportUrl` in branding) and paste the reference
+//! ID they get back into a bug report. Nothing is sent unless they ask.
+
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::time::Duration;
+
+use crate::{branding, debug_log, download, log_dir, log_path, silent, system_info, video_decode};
+
+/// Only the end of a long log is sent; that's where the failure is
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(serde::Deserialize)]
+struct UploadResponse {
+    id: String,
+}
+
+fn log_tail() -> Option<Vec<u8>> {
+    let mut file = std::fs::File::open(log_path()?).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(MAX_LOG_BYTES))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    Some(tail)
+}
+
+/// Zip up everything support needs, in memory
+pub fn bundle() -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let system = serde_json::to_vec_pretty(&system_info::collect()).map_err(|e| e.to_string())?;
+    let decode = serde_json::to_vec_pretty(&video_decode::check()).map_err(|e| e.to_string())?;
+    let result = log_dir().and_then(|dir| std::fs::read(dir.join(silent::RESULT_FILE)).ok());
+    let files = [
+        ("installer-debug.log", log_tail()),
+        ("system-info.json", Some(system)),
+        ("video-decode.json", Some(decode)),
+        (silent::RESULT_FILE, result),
+    ];
+    for (name, data) in files {
+        let Some(data) = data else { continue };
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+    }
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+/// Upload the bundle and return the reference ID the endpoint assigned it
+pub fn upload() -> Result<String, String> {
+    let url = branding::get().support_url.as_deref().ok_or("This build has no support endpoint")?;
+    let body = bundle()?;
+    let response = download::post(url, "application/zip", &body, UPLOAD_TIMEOUT)?;
+    let response: UploadResponse =
+        serde_json::from_slice(&response).map_err(|e| format!("Unexpected response from {}: {}", url, e))?;
+    debug_log(&format!("Uploaded diagnostics as {}", response.id));
+    Ok(response.id)
+}
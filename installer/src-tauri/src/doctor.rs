@@ -0,0 +1,256 @@
+//! `doctor [--fix] [--json] [--install-path <dir>]`: look for the ways an install usually
+//! breaks (a missing main executable, files that no longer match the payload's hash
+//! manifest, deleted shortcuts, an Apps & Features entry left at an older version,
+//! corrupted update cache entries, files left behind by an interrupted update) and, with
+//! `--fix`, repair what can be repaired. Without `--fix` nothing is
+//! changed and the report says what `--fix` would do.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::{arp, branding, bundled_payload, debug_log, extract, manifest, payload, update_cache};
+
+/// Suffixes of files an interrupted write leaves next to the real one
+const LEFTOVER_SUFFIXES: &[&str] = &[".old", ".partial"];
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// `executable`, `shortcuts`, `apps_and_features`, `update_cache` or `leftovers`
+    pub check: &'static str,
+    pub problem: String,
+    /// What `--fix` does about it, or why it can't
+    pub fix: String,
+    pub fixable: bool,
+    pub fixed: bool,
+    pub fix_error: Option<String>,
+}
+
+impl Finding {
+    fn new(check: &'static str, problem: String, fix: String, fixable: bool) -> Self {
+        Finding { check, problem, fix, fixable, fixed: false, fix_error: None }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    pub install_path: String,
+    pub version: Option<String>,
+    pub findings: Vec<Finding>,
+}
+
+impl DoctorReport {
+    /// Nothing found, or everything found was fixed
+    pub fn healthy(&self) -> bool {
+        self.findings.iter().all(|f| f.fixed)
+    }
+}
+
+/// Examine the install at `install_path`, fixing what can be fixed when `fix` is set.
+/// `None` when there is no install there.
+pub fn run(install_path: &str, fix: bool) -> Option<DoctorReport> {
+    let root = Path::new(install_path);
+    let exe_name = branding::get().exe_name.clone();
+    let manifest = manifest::load(install_path);
+    if manifest.is_none() && !root.join(&exe_name).exists() {
+        return None;
+    }
+    let version = manifest.as_ref().and_then(|m| m.version.clone());
+    let portable = manifest.as_ref().and_then(|m| m.options.as_ref()).is_some_and(|o| o.portable);
+    let mut findings = Vec::new();
+
+    let restore = restorable_payload(version.as_deref());
+    if !root.join(&exe_name).exists() {
+        findings.push(Finding::new(
+            "executable",
+            format!("{} is missing", exe_name),
+            match &restore {
+                Some(_) => "Extract it again from this installer's payload".to_string(),
+                None => "Reinstall: this installer carries no payload of the installed version".to_string(),
+            },
+            restore.is_some(),
+        ));
+        if let (true, Some((payload_path, is_7z))) = (fix, &restore) {
+            apply(&mut findings, reextract(payload_path, *is_7z, install_path, std::slice::from_ref(&exe_name)));
+        }
+    }
+
+    // Files damaged or deleted since the install, by the payload's hash manifest
+    let hashes = restore.as_ref().and_then(|(payload_path, is_7z)| payload::read_manifest(payload_path, *is_7z));
+    if let (Some(manifest), Some(hashes), Some((payload_path, is_7z))) = (&manifest, &hashes, &restore) {
+        let damaged: Vec<String> = manifest
+            .files
+            .iter()
+            // A missing executable was reported above
+            .filter(|f| **f != exe_name || root.join(f).exists())
+            .filter(|f| hashes.get(f).is_some_and(|expected| !extract::is_unchanged(&root.join(f), expected)))
+            .cloned()
+            .collect();
+        if !damaged.is_empty() {
+            findings.push(Finding::new(
+                "files",
+                format!("{} files are missing or damaged: {:?}", damaged.len(), damaged),
+                "Extract them again from this installer's payload".to_string(),
+                true,
+            ));
+            if fix {
+                apply(&mut findings, reextract(payload_path, *is_7z, install_path, &damaged));
+            }
+        }
+    }
+
+    if let Some(manifest) = &manifest {
+        let missing: Vec<&PathBuf> = manifest.shortcuts.iter().filter(|lnk| !lnk.exists()).collect();
+        if !missing.is_empty() {
+            let options = manifest.options.as_ref();
+            findings.push(Finding::new(
+                "shortcuts",
+                format!("{} shortcuts are missing: {:?}", missing.len(), missing),
+                match options {
+                    Some(_) => "Create the shortcuts again".to_string(),
+                    None => "Reinstall: the install options weren't recorded".to_string(),
+                },
+                options.is_some(),
+            ));
+            if let (true, Some(options)) = (fix, options) {
+                apply(&mut findings, crate::shortcuts::create_shortcuts(install_path, options).map(|_| ()));
+            }
+        }
+    }
+
+    // Portable installs leave nothing in the registry
+    if cfg!(windows) && !portable {
+        let problem = match (arp::install_location(), arp::display_version()) {
+            (None, _) => Some("Apps & Features entry missing".to_string()),
+            (Some(location), _) if !crate::same_path(&location, install_path) => {
+                Some(format!("Apps & Features entry points at {}", location))
+            }
+            (_, shown) if version.is_some() && shown != version => Some(format!(
+                "Apps & Features shows version {}, installed is {}",
+                shown.as_deref().unwrap_or("none"),
+                version.as_deref().unwrap_or("unknown")
+            )),
+            _ => None,
+        };
+        if let Some(problem) = problem {
+            findings.push(Finding::new("apps_and_features", problem, "Rewrite the entry".to_string(), true));
+            if fix {
+                apply(&mut findings, arp::register(install_path, version.as_deref()));
+            }
+        }
+    }
+
+    if !portable {
+        for cached in update_cache::versions() {
+            let Err(e) = update_cache::check(&cached) else { continue };
+            findings.push(Finding::new(
+                "update_cache",
+                format!("Cached installer {} is corrupted: {}", cached, e),
+                "Remove it; the next update downloads in full".to_string(),
+                true,
+            ));
+            if fix {
+                let result = update_cache::cache_dir()
+                    .and_then(|dir| std::fs::remove_dir_all(dir.join(&cached)).map_err(|e| e.to_string()));
+                apply(&mut findings, result);
+            }
+        }
+    }
+
+    let mut leftovers = Vec::new();
+    collect_leftovers(root, &mut leftovers);
+    for leftover in leftovers {
+        findings.push(Finding::new(
+            "leftovers",
+            format!("{} was left behind by an interrupted update", leftover.display()),
+            "Delete it".to_string(),
+            true,
+        ));
+        if fix {
+            apply(&mut findings, std::fs::remove_file(&leftover).map_err(|e| e.to_string()));
+        }
+    }
+
+    for finding in &findings {
+        debug_log(&format!("Doctor: {}: {} (fixed: {})", finding.check, finding.problem, finding.fixed));
+    }
+    Some(DoctorReport { install_path: install_path.to_string(), version, findings })
+}
+
+/// Record the outcome of fixing the last finding
+fn apply(findings: &mut [Finding], result: Result<(), String>) {
+    if let Some(finding) = findings.last_mut() {
+        match result {
+            Ok(()) => finding.fixed = true,
+            Err(e) => finding.fix_error = Some(e),
+        }
+    }
+}
+
+/// Extract `files` from the payload over their installed copies
+fn reextract(payload_path: &Path, is_7z: bool, install_path: &str, files: &[String]) -> Result<(), String> {
+    let wanted: HashSet<String> = files.iter().map(|f| payload::normalize_entry_name(f)).collect();
+    let include = |entry: &str| wanted.contains(&payload::normalize_entry_name(entry));
+    let result = if is_7z {
+        extract::extract_7z(payload_path, install_path, None, Some(&include), None)
+    } else {
+        extract::extract_zip(payload_path, install_path, None, Some(&include), None)
+    };
+    if result.is_ok() {
+        crate::mark_executable(install_path, files);
+    }
+    result.map(|_| ())
+}
+
+/// The bundled payload, if it is the version that is installed; extracting files from a
+/// different version would leave a mix of both
+fn restorable_payload(installed: Option<&str>) -> Option<(PathBuf, bool)> {
+    let (path, is_7z) = bundled_payload()?;
+    let bundled = payload::read_version(&path, is_7z)?;
+    (Some(bundled.as_str()) == installed).then_some((path, is_7z))
+}
+
+fn collect_leftovers(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_symlink() {
+            continue;
+        }
+        if path.is_dir() {
+            collect_leftovers(&path, out);
+        } else if LEFTOVER_SUFFIXES.iter().any(|suffix| path.to_string_lossy().ends_with(suffix)) {
+            out.push(path);
+        }
+    }
+}
+
+/// The report as plain text, one line per finding
+pub fn summary(report: &DoctorReport, fix: bool) -> String {
+    let mut lines = vec![format!(
+        "{} {} at {}",
+        branding::get().app_name,
+        report.version.as_deref().unwrap_or("(unknown version)"),
+        report.install_path
+    )];
+    for finding in &report.findings {
+        let status = match (&finding.fix_error, finding.fixed, finding.fixable) {
+            (Some(e), _, _) => format!("FIX FAILED ({})", e),
+            (None, true, _) => "FIXED".to_string(),
+            (None, false, true) if !fix => format!("FIXABLE: {}", finding.fix),
+            (None, false, _) => format!("NOT FIXED: {}", finding.fix),
+        };
+        lines.push(format!("[{}] {} - {}", finding.check, finding.problem, status));
+    }
+    let open = report.findings.iter().filter(|f| !f.fixed).count();
+    lines.push(match (report.findings.len(), open) {
+        (0, _) => "No problems found".to_string(),
+        (found, 0) => format!("{} problems found, all fixed", found),
+        (found, _) if !fix && report.findings.iter().any(|f| f.fixable) => {
+            format!("{} problems found; run again with --fix to repair", found)
+        }
+        (found, open) => format!("{} problems found, {} not fixed", found, open),
+    });
+    lines.join("\n")
+}
@@ -0,0 +1,611 @@
+//! HTTP(S) downloads through WinHTTP, so the installer gets the system proxy settings
+//! and certificate store without shipping a TLS stack of its own.
+//!
+//! Large files from servers that accept ranges are fetched in several segments at once,
+//! which keeps high-latency links busy where a single stream stalls on its window; each
+//! segment retries on its own and writes straight to its place in the file.
+//!
+//! A download that fails partway leaves `<file>.part` behind with the server's validator
+//! (a strong ETag or Last-Modified) in `<file>.part.json`. The next attempt asks for the
+//! rest with `If-Range`, so a server whose file changed in between sends it whole and the
+//! download starts over instead of splicing two different files together.
+//!
+//! Redirects are followed by hand, one request per hop, so certificate pins apply to each
+//! host a download passes through and not just the one it started at.
+//!
+//! Metadata fetches and downloads go through `retry`, which tries again after connection
+//! failures and transient HTTP statuses; a retried download resumes from its partial file.
+
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::debug_log;
+use crate::retry::{self, Failure};
+
+/// Files smaller than this are downloaded in one stream
+const SEGMENTED_MIN_SIZE: u64 = 16 * 1024 * 1024;
+/// Parallel connections for a segmented download
+const SEGMENTS: u64 = 4;
+/// Attempts per segment before the download fails; each resumes where the last stopped
+const SEGMENT_ATTEMPTS: u32 = 3;
+
+/// Receives each chunk of a response body, with the response it belongs to
+type Sink<'a> = dyn FnMut(&[u8], &Response) -> Result<(), String> + 'a;
+
+#[cfg(windows)]
+mod winhttp {
+    use windows::core::{HRESULT, HSTRING, PCWSTR};
+    use windows::Win32::Networking::WinHttp::*;
+
+    use super::{Request, Response, Sink};
+    use crate::retry::Failure;
+
+    /// WinHTTP errors from the network itself, which may clear up on another try
+    const TRANSIENT_ERRORS: &[u32] = &[
+        ERROR_WINHTTP_TIMEOUT,
+        ERROR_WINHTTP_NAME_NOT_RESOLVED,
+        ERROR_WINHTTP_CANNOT_CONNECT,
+        ERROR_WINHTTP_CONNECTION_ERROR,
+        ERROR_WINHTTP_RESEND_REQUEST,
+    ];
+
+    /// `message` as a failure, transient when `error` is a connection failure or timeout
+    fn network_failure(message: String, error: &windows::core::Error) -> Failure {
+        if TRANSIENT_ERRORS.iter().any(|&code| error.code() == HRESULT::from_win32(code)) {
+            Failure::transient(message, None)
+        } else {
+            Failure::permanent(message)
+        }
+    }
+
+    /// Closes a WinHTTP handle when dropped
+    struct Handle(*mut core::ffi::c_void);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = WinHttpCloseHandle(self.0);
+            }
+        }
+    }
+
+    fn open(handle: *mut core::ffi::c_void, what: &str) -> Result<Handle, String> {
+        if handle.is_null() {
+            return Err(format!("{} failed: {}", what, windows::core::Error::from_win32()));
+        }
+        Ok(Handle(handle))
+    }
+
+    /// Redirects followed before a request gives up
+    const MAX_REDIRECTS: u32 = 10;
+
+    /// Send `request` to its URL, passing the response body to `sink` chunk by chunk along
+    /// with the status and headers. Returns those; non-2xx bodies aren't read.
+    /// Redirects are followed here, one `send_once` per hop.
+    pub fn send(request: &Request, sink: &mut Sink) -> Result<Response, Failure> {
+        let mut url = request.url.to_string();
+        let mut method = request.method;
+        let mut body = request.body;
+        for _ in 0..=MAX_REDIRECTS {
+            let hop = Request { method, url: &url, headers: request.headers, body, timeout: request.timeout };
+            let response = send_once(&hop, sink)?;
+            let location = match response.status {
+                301 | 302 | 303 | 307 | 308 => response.header("Location"),
+                _ => None,
+            };
+            let Some(location) = location else { return Ok(response) };
+            let next = url::Url::parse(&url)
+                .and_then(|base| base.join(location))
+                .map_err(|e| format!("Invalid redirect from {} to {}: {}", url, location, e))?;
+            // Like browsers, only 307 and 308 resend a POST body
+            if response.status == 303 || (method == "POST" && matches!(response.status, 301 | 302)) {
+                method = "GET";
+                body = None;
+            }
+            url = next.to_string();
+        }
+        Err(format!("Too many redirects fetching {}", request.url).into())
+    }
+
+    fn send_once(request: &Request, sink: &mut Sink) -> Result<Response, Failure> {
+        let url = request.url;
+        let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        let secure = match parsed.scheme() {
+            "https" => true,
+            "http" => false,
+            other => return Err(format!("Unsupported URL scheme: {}", other).into()),
+        };
+        let host = parsed.host_str().ok_or_else(|| format!("URL has no host: {}", url))?;
+        // Checked per hop, so a redirect can't downgrade a pinned host to plain HTTP either
+        if !secure && !crate::cert_pins::for_host(host).is_empty() {
+            return Err(format!("Refusing plain HTTP to {}, which has pinned certificates", host).into());
+        }
+        let port = parsed.port_or_known_default().unwrap_or(if secure { 443 } else { 80 });
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+
+        unsafe {
+            let session = open(
+                WinHttpOpen(
+                    &HSTRING::from(concat!("MangyomiInstaller/", env!("CARGO_PKG_VERSION"))),
+                    WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY,
+                    PCWSTR::null(),
+                    PCWSTR::null(),
+                    0,
+                ),
+                "WinHttpOpen",
+            )?;
+            if let Some(timeout) = request.timeout {
+                let ms = timeout.as_millis() as i32;
+                WinHttpSetTimeouts(session.0, ms, ms, ms, ms).map_err(|e| e.to_string())?;
+            }
+            let connection = open(WinHttpConnect(session.0, &HSTRING::from(host), port, 0), "WinHttpConnect")?;
+            let flags = if secure { WINHTTP_FLAG_SECURE } else { WINHTTP_OPEN_REQUEST_FLAGS(0) };
+            let handle = open(
+                WinHttpOpenRequest(connection.0, &HSTRING::from(request.method), &HSTRING::from(path), PCWSTR::null(), PCWSTR::null(), std::ptr::null(), flags),
+                "WinHttpOpenRequest",
+            )?;
+            WinHttpSetOption(handle.0, WINHTTP_OPTION_DISABLE_FEATURE, Some(&WINHTTP_DISABLE_REDIRECTS.to_ne_bytes()))
+                .map_err(|e| format!("Failed to configure the request to {}: {}", host, e))?;
+
+            let headers: Vec<u16> = request.headers.encode_utf16().collect();
+            let headers = (!headers.is_empty()).then_some(headers.as_slice());
+            let body = request.body.unwrap_or_default();
+            WinHttpSendRequest(handle.0, headers, None, 0, body.len() as u32, 0)
+                .map_err(|e| network_failure(format!("Request to {} failed: {}", host, e), &e))?;
+            // The handshake is done: check the pins before the body goes out
+            if secure && !crate::cert_pins::for_host(host).is_empty() {
+                crate::cert_pins::check(host, &server_chain(&handle))?;
+            }
+            if !body.is_empty() {
+                let mut written = 0u32;
+                WinHttpWriteData(handle.0, Some(body.as_ptr() as *const _), body.len() as u32, &mut written)
+                    .map_err(|e| network_failure(format!("Request to {} failed: {}", host, e), &e))?;
+            }
+            WinHttpReceiveResponse(handle.0, std::ptr::null_mut())
+                .map_err(|e| network_failure(format!("No response from {}: {}", host, e), &e))?;
+
+            let response = Response {
+                status: query_number(&handle, WINHTTP_QUERY_STATUS_CODE).unwrap_or(0) as u32,
+                content_length: query_number(&handle, WINHTTP_QUERY_CONTENT_LENGTH),
+                headers: raw_headers(&handle),
+            };
+            if !(200..300).contains(&response.status) {
+                return Ok(response);
+            }
+
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let mut read = 0u32;
+                WinHttpReadData(handle.0, buffer.as_mut_ptr() as *mut _, buffer.len() as u32, &mut read)
+                    .map_err(|e| network_failure(format!("Download from {} interrupted: {}", host, e), &e))?;
+                if read == 0 {
+                    break;
+                }
+                sink(&buffer[..read as usize], &response)?;
+            }
+            Ok(response)
+        }
+    }
+
+    /// The response headers as name/value pairs
+    unsafe fn raw_headers(request: &Handle) -> Vec<(String, String)> {
+        let mut size = 0u32;
+        let mut index = 0u32;
+        unsafe {
+            // The first call fails with the size needed, in bytes
+            let _ = WinHttpQueryHeaders(request.0, WINHTTP_QUERY_RAW_HEADERS_CRLF, PCWSTR::null(), None, &mut size, &mut index);
+            let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+            if WinHttpQueryHeaders(
+                request.0,
+                WINHTTP_QUERY_RAW_HEADERS_CRLF,
+                PCWSTR::null(),
+                Some(buffer.as_mut_ptr() as *mut _),
+                &mut size,
+                &mut index,
+            )
+            .is_err()
+            {
+                return Vec::new();
+            }
+            buffer.truncate(size as usize / 2);
+            String::from_utf16_lossy(&buffer)
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                .collect()
+        }
+    }
+
+    /// DER SubjectPublicKeyInfo of each certificate in the chain the server presented,
+    /// leaf first. Empty when WinHTTP can't report it.
+    unsafe fn server_chain(request: &Handle) -> Vec<Vec<u8>> {
+        use windows::Win32::Security::Cryptography::{
+            CertFreeCertificateChain, CryptEncodeObjectEx, CERT_CHAIN_CONTEXT, CRYPT_ENCODE_OBJECT_FLAGS, X509_ASN_ENCODING,
+            X509_PUBLIC_KEY_INFO,
+        };
+
+        let mut chain: *const CERT_CHAIN_CONTEXT = std::ptr::null();
+        let mut size = std::mem::size_of::<*const CERT_CHAIN_CONTEXT>() as u32;
+        let mut keys = Vec::new();
+        unsafe {
+            let queried = WinHttpQueryOption(
+                request.0,
+                WINHTTP_OPTION_SERVER_CERT_CHAIN_CONTEXT,
+                Some(&mut chain as *mut _ as *mut _),
+                &mut size,
+            );
+            if queried.is_err() || chain.is_null() {
+                return keys;
+            }
+            if (*chain).cChain > 0 {
+                let simple = &**(*chain).rgpChain;
+                for i in 0..simple.cElement as usize {
+                    let element = &**simple.rgpElement.add(i);
+                    let info = &(*(*element.pCertContext).pCertInfo).SubjectPublicKeyInfo as *const _ as *const core::ffi::c_void;
+                    let encode = |out: Option<*mut core::ffi::c_void>, len: &mut u32| {
+                        CryptEncodeObjectEx(X509_ASN_ENCODING, X509_PUBLIC_KEY_INFO, info, CRYPT_ENCODE_OBJECT_FLAGS(0), None, out, len)
+                    };
+                    let mut len = 0u32;
+                    if encode(None, &mut len).is_err() {
+                        continue;
+                    }
+                    let mut der = vec![0u8; len as usize];
+                    if encode(Some(der.as_mut_ptr() as *mut _), &mut len).is_ok() {
+                        der.truncate(len as usize);
+                        keys.push(der);
+                    }
+                }
+            }
+            CertFreeCertificateChain(chain);
+        }
+        keys
+    }
+
+    unsafe fn query_number(request: &Handle, info: u32) -> Option<u64> {
+        let mut value = 0u64;
+        let mut size = std::mem::size_of::<u64>() as u32;
+        let mut index = 0u32;
+        unsafe {
+            WinHttpQueryHeaders(
+                request.0,
+                info | WINHTTP_QUERY_FLAG_NUMBER64,
+                PCWSTR::null(),
+                Some(&mut value as *mut u64 as *mut _),
+                &mut size,
+                &mut index,
+            )
+            .ok()?;
+        }
+        Some(value)
+    }
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+struct Request<'a> {
+    method: &'a str,
+    url: &'a str,
+    /// Extra headers, each terminated by CRLF
+    headers: &'a str,
+    body: Option<&'a [u8]>,
+    /// Applies to resolving, connecting, sending and each receive
+    timeout: Option<std::time::Duration>,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+struct Response {
+    status: u32,
+    content_length: Option<u64>,
+    headers: Vec<(String, String)>,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    /// What identifies this version of the resource for `If-Range`: a strong ETag, or
+    /// else Last-Modified. Weak ETags can't be used for ranges.
+    fn validator(&self) -> Option<String> {
+        self.header("ETag").filter(|etag| !etag.starts_with("W/")).or_else(|| self.header("Last-Modified")).map(str::to_string)
+    }
+
+    /// Whether this is the part of the resource starting at `start`
+    fn is_range_from(&self, start: u64) -> bool {
+        self.status == 206 && self.header("Content-Range").is_some_and(|range| range.starts_with(&format!("bytes {}-", start)))
+    }
+}
+
+#[cfg(windows)]
+use winhttp::send;
+
+#[cfg(not(windows))]
+fn send(_request: &Request, _sink: &mut Sink) -> Result<Response, Failure> {
+    Err(Failure::permanent("Downloads are only supported on Windows".to_string()))
+}
+
+fn get(url: &str, headers: &str, sink: &mut Sink) -> Result<Response, Failure> {
+    send(&Request { method: "GET", url, headers, body: None, timeout: None }, sink)
+}
+
+/// GET bytes `start..=end` of `url`, only if it still matches `validator` when given.
+/// Fails unless the server answers with exactly that range.
+fn get_range(url: &str, start: u64, end: u64, validator: Option<&str>, sink: &mut Sink) -> Result<Response, Failure> {
+    let mut headers = format!("Range: bytes={}-{}\r\n", start, end);
+    if let Some(validator) = validator {
+        headers.push_str(&format!("If-Range: {}\r\n", validator));
+    }
+    let response = get(url, &headers, &mut |chunk, response| {
+        // A changed file comes back whole with a 200; don't write any of it
+        if !response.is_range_from(start) || response.content_length.is_some_and(|length| length != end - start + 1) {
+            return Err(format!("{} didn't send the requested range (HTTP {})", url, response.status));
+        }
+        sink(chunk, response)
+    })?;
+    if !response.is_range_from(start) {
+        return Err(format!("{} didn't send the requested range (HTTP {})", url, response.status).into());
+    }
+    Ok(response)
+}
+
+/// The size and validator of `url` when the server serves ranges of it
+fn ranged_size(url: &str) -> Option<(u64, Option<String>)> {
+    let response = get_range(url, 0, 0, None, &mut |_, _| Ok(())).ok()?;
+    let size = response.header("Content-Range")?.rsplit_once('/')?.1.parse().ok()?;
+    Some((size, response.validator()))
+}
+
+/// POST `body` to `url` as `content_type`, giving up after `timeout`. Returns the
+/// response body.
+pub fn post(url: &str, content_type: &str, body: &[u8], timeout: std::time::Duration) -> Result<Vec<u8>, String> {
+    let headers = format!("Content-Type: {}\r\n", content_type);
+    let request = Request { method: "POST", url, headers: &headers, body: Some(body), timeout: Some(timeout) };
+    let mut response = Vec::new();
+    let status = send(&request, &mut |chunk, _| {
+        response.extend_from_slice(chunk);
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?
+    .status;
+    if !(200..300).contains(&status) {
+        return Err(format!("POST to {} failed with HTTP {}", url, status));
+    }
+    Ok(response)
+}
+
+/// POST a JSON `body` to `url`, giving up after `timeout`
+pub fn post_json(url: &str, body: &str, timeout: std::time::Duration) -> Result<(), String> {
+    post(url, "application/json", body.as_bytes(), timeout).map(|_| ())
+}
+
+fn check_status(url: &str, response: &Response) -> Result<(), Failure> {
+    if (200..300).contains(&response.status) {
+        return Ok(());
+    }
+    let message = format!("Download of {} failed with HTTP {}", url, response.status);
+    if retry::is_transient_status(response.status) {
+        return Err(Failure::transient(message, response.header("Retry-After").and_then(retry::parse_retry_after)));
+    }
+    Err(Failure::permanent(message))
+}
+
+#[cfg(feature = "gui")]
+/// GET `url` into memory, for small metadata files
+pub fn get_bytes(url: &str) -> Result<Vec<u8>, String> {
+    retry::run(&format!("Fetching {}", url), || {
+        let mut body = Vec::new();
+        let response = get(url, "", &mut |chunk, _| {
+            body.extend_from_slice(chunk);
+            Ok(())
+        })?;
+        check_status(url, &response)?;
+        Ok(body)
+    })
+}
+
+/// Download `url` to `dest`, calling `progress` with the bytes received so far and the
+/// total when known. Large files are fetched in segments when the server allows it. A
+/// partial file is removed on failure unless it can be resumed.
+pub fn download_file(url: &str, dest: &Path, mut progress: impl FnMut(u64, Option<u64>)) -> Result<(), String> {
+    retry::run(&format!("Downloading {}", url), || {
+        // A partial download left by an earlier attempt is picked up by the stream instead
+        let ranged = ranged_size(url).filter(|(size, _)| *size >= SEGMENTED_MIN_SIZE && !sibling(dest, ".part").exists());
+        let result = match ranged {
+            Some((size, validator)) => download_segmented(url, dest, size, validator.as_deref(), &mut progress).or_else(|e| {
+                debug_log(&format!("Segmented download of {} failed, retrying as one stream: {}", url, e));
+                download_stream(url, dest, &mut progress)
+            }),
+            None => download_stream(url, dest, &mut progress),
+        };
+        if result.is_err() {
+            let _ = std::fs::remove_file(dest);
+        }
+        result
+    })
+}
+
+/// What's needed to resume a partial download, kept next to it
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Partial {
+    url: String,
+    validator: String,
+}
+
+/// `path` with `suffix` appended to its file name
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// The length of an earlier partial download of `url` into `part` and its validator
+fn resumable(part: &Path, state: &Path, url: &str) -> Option<(u64, String)> {
+    let partial: Partial = serde_json::from_str(&std::fs::read_to_string(state).ok()?).ok()?;
+    let length = std::fs::metadata(part).ok()?.len();
+    (partial.url == url && length > 0).then_some((length, partial.validator))
+}
+
+fn discard_partial(part: &Path, state: &Path) {
+    let _ = std::fs::remove_file(part);
+    let _ = std::fs::remove_file(state);
+}
+
+/// Fetch `url` over one connection into `dest.part`, continuing an earlier partial download
+/// when the server's file hasn't changed since, and move it to `dest` once complete
+fn download_stream(url: &str, dest: &Path, progress: &mut dyn FnMut(u64, Option<u64>)) -> Result<(), Failure> {
+    let part = sibling(dest, ".part");
+    let state = sibling(dest, ".part.json");
+    let resume = resumable(&part, &state, url);
+    if resume.is_none() {
+        discard_partial(&part, &state);
+    }
+    let headers = match &resume {
+        Some((offset, validator)) => {
+            debug_log(&format!("Resuming {} at {} bytes", url, offset));
+            format!("Range: bytes={}-\r\nIf-Range: {}\r\n", offset, validator)
+        }
+        None => String::new(),
+    };
+
+    let mut file = None;
+    let mut received = 0u64;
+    let mut total = None;
+    let mut restart = false;
+    // Continue the partial file if the server sent the rest of it, otherwise start over
+    let open = |response: &Response, received: &mut u64, total: &mut Option<u64>| -> Result<std::fs::File, String> {
+        let file = match resume.as_ref().filter(|(offset, _)| response.is_range_from(*offset)) {
+            Some((offset, _)) => {
+                *received = *offset;
+                *total = response.content_length.map(|length| offset + length);
+                std::fs::OpenOptions::new().append(true).open(&part)
+            }
+            None => {
+                if resume.is_some() {
+                    debug_log(&format!("{} changed since the partial download, starting over", url));
+                }
+                *total = response.content_length;
+                std::fs::File::create(&part)
+            }
+        }
+        .map_err(|e| format!("Failed to open {:?}: {}", part, e))?;
+        match response.validator() {
+            Some(validator) => {
+                let partial = Partial { url: url.to_string(), validator };
+                let _ = std::fs::write(&state, serde_json::to_string(&partial).unwrap_or_default());
+            }
+            None => {
+                let _ = std::fs::remove_file(&state);
+            }
+        }
+        Ok(file)
+    };
+    let result = get(url, &headers, &mut |chunk, response| {
+        let file = match &mut file {
+            Some(file) => file,
+            None => file.insert(open(response, &mut received, &mut total)?),
+        };
+        file.write_all(chunk).map_err(|e| format!("Failed to write {:?}: {}", part, e))?;
+        received += chunk.len() as u64;
+        progress(received, total);
+        Ok(())
+    })
+    .and_then(|response| {
+        // The partial file is longer than the server's, so it can't be the same one
+        if response.status == 416 && resume.is_some() {
+            restart = true;
+            return Err(format!("{} no longer matches the partial download", url).into());
+        }
+        check_status(url, &response)?;
+        if file.is_none() {
+            file = Some(open(&response, &mut received, &mut total)?);
+        }
+        // The connection dropped before the end
+        match total {
+            Some(total) if received != total => {
+                Err(Failure::transient(format!("Download of {} ended after {} of {} bytes", url, received, total), None))
+            }
+            _ => Ok(()),
+        }
+    });
+    drop(file);
+
+    match result {
+        Ok(()) => {
+            std::fs::rename(&part, dest).map_err(|e| format!("Failed to move {:?} to {:?}: {}", part, dest, e))?;
+            let _ = std::fs::remove_file(&state);
+            Ok(())
+        }
+        Err(e) if restart => {
+            debug_log(&format!("{}, starting over", e));
+            discard_partial(&part, &state);
+            download_stream(url, dest, progress)
+        }
+        Err(e) => {
+            // Without a validator there'd be no safe way to resume, so don't keep it
+            if !state.exists() {
+                discard_partial(&part, &state);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Fetch `size` bytes of `url` over SEGMENTS connections into a file of that size.
+/// Progress is reported from this thread while the segments run.
+fn download_segmented(url: &str, dest: &Path, size: u64, validator: Option<&str>, progress: &mut dyn FnMut(u64, Option<u64>)) -> Result<(), String> {
+    let file = std::fs::File::create(dest).map_err(|e| format!("Failed to create {:?}: {}", dest, e))?;
+    file.set_len(size).map_err(|e| format!("Failed to size {:?}: {}", dest, e))?;
+    drop(file);
+    debug_log(&format!("Downloading {} in {} segments ({} bytes)", url, SEGMENTS, size));
+
+    let received = &AtomicU64::new(0);
+    let segment_size = size.div_ceil(SEGMENTS);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..SEGMENTS)
+            .map(|i| (i * segment_size, ((i + 1) * segment_size).min(size)))
+            .filter(|(start, end)| start < end)
+            .map(|(start, end)| scope.spawn(move || download_segment(url, dest, start, end - 1, validator, received)))
+            .collect();
+        while !workers.iter().all(|worker| worker.is_finished()) {
+            progress(received.load(Ordering::Relaxed), Some(size));
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        workers.into_iter().try_for_each(|worker| worker.join().map_err(|_| "Download segment panicked".to_string())?)
+    })?;
+    progress(size, Some(size));
+    Ok(())
+}
+
+/// Fetch bytes `start..=end` into their place in `dest`, resuming after a failed attempt.
+/// Fails if the server's file stops matching `validator`.
+fn download_segment(url: &str, dest: &Path, start: u64, end: u64, validator: Option<&str>, received: &AtomicU64) -> Result<(), String> {
+    let mut file = std::fs::OpenOptions::new().write(true).open(dest).map_err(|e| format!("Failed to open {:?}: {}", dest, e))?;
+    let mut position = start;
+    let mut attempt = 1;
+    loop {
+        file.seek(SeekFrom::Start(position)).map_err(|e| format!("Failed to seek in {:?}: {}", dest, e))?;
+        let result = get_range(url, position, end, validator, &mut |chunk, _| {
+            if position + chunk.len() as u64 > end + 1 {
+                return Err(format!("{} sent more than the range asked for", url));
+            }
+            file.write_all(chunk).map_err(|e| format!("Failed to write {:?}: {}", dest, e))?;
+            position += chunk.len() as u64;
+            received.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            Ok(())
+        });
+        match result {
+            Ok(_) if position == end + 1 => return Ok(()),
+            Ok(_) => debug_log(&format!("Segment {}-{} of {} ended early at {}", start, end, url, position)),
+            Err(e) if !e.is_transient() || attempt >= SEGMENT_ATTEMPTS => return Err(e.to_string()),
+            Err(e) => debug_log(&format!("Segment {}-{} of {} failed at {} (attempt {}): {}", start, end, url, position, attempt, e)),
+        }
+        if attempt >= SEGMENT_ATTEMPTS {
+            return Err(format!("Download of {} kept ending early", url));
+        }
+        attempt += 1;
+    }
+}
@@ -0,0 +1,113 @@
+//! `--dry-run`: report what an install with the given options would do, without touching
+//! the system. The plan follows the same rules as a real install: files matching the
+//! payload's hash manifest are left alone, and files of deselected components go.
+
+use std::path::{Path, PathBuf};
+
+use crate::{branding, components, delta, extract, file_assoc, manifest, payload, policy, read_installed_version, shortcuts};
+
+#[derive(Default, serde::Serialize)]
+pub struct DryRunReport {
+    pub install_path: String,
+    pub payload: String,
+    pub version: Option<String>,
+    pub installed_version: Option<String>,
+    /// Install-relative paths that don't exist yet
+    pub create: Vec<String>,
+    /// Existing files that would be replaced
+    pub overwrite: Vec<String>,
+    /// Existing files already matching the payload
+    pub unchanged: usize,
+    pub delete: Vec<String>,
+    pub shortcuts: Vec<PathBuf>,
+    /// Registry keys (or their equivalents elsewhere) for file associations
+    pub file_associations: Vec<String>,
+    /// Bytes written to the install directory
+    pub required_bytes: u64,
+}
+
+/// The options an install at `install_path` would use: the saved ones for an existing
+/// install, defaults with the bundled components' own defaults otherwise
+fn options_for(install_path: &str, payload_path: &Path, is_7z: bool, args: &[String]) -> manifest::InstallOptions {
+    let saved = manifest::load(install_path).and_then(|m| m.options);
+    let mut options = saved.unwrap_or_else(|| manifest::InstallOptions {
+        components: components::read_manifest(payload_path, is_7z)
+            .components
+            .into_iter()
+            .filter(|c| c.selected_by_default)
+            .map(|c| c.id)
+            .collect(),
+        extra_shortcuts: shortcuts::read_extra_shortcuts(payload_path, is_7z).into_iter().filter(|s| s.selected_by_default).collect(),
+        ..Default::default()
+    });
+    options.install_path = install_path.to_string();
+    if let Some(ids) = components::parse_arg(args) {
+        options.components = ids;
+    }
+    shortcuts::parse_start_menu_args(args, &mut options);
+    policy::get().apply(&mut options);
+    options
+}
+
+pub fn plan(install_path: &str, payload_path: &Path, is_7z: bool, args: &[String]) -> Result<DryRunReport, String> {
+    let options = options_for(install_path, payload_path, is_7z, args);
+    let listing = payload::list(payload_path, is_7z)?;
+    let root = Path::new(install_path);
+    let mut report = DryRunReport {
+        install_path: install_path.to_string(),
+        payload: payload_path.display().to_string(),
+        version: listing.version.clone(),
+        installed_version: read_installed_version(install_path),
+        ..Default::default()
+    };
+
+    if let Some(delta) = delta::read_manifest(payload_path, is_7z) {
+        for (name, op) in &delta.files {
+            let existing = std::fs::metadata(root.join(name)).ok();
+            match op {
+                delta::DeltaOp::Remove => {
+                    report.delete.push(name.clone());
+                    continue;
+                }
+                delta::DeltaOp::Add if existing.is_none() => report.create.push(name.clone()),
+                _ => report.overwrite.push(name.clone()),
+            }
+            // Patched files are rebuilt in full; their current size is the best estimate
+            let size = listing.entries.iter().find(|e| &e.path == name).map(|e| e.size);
+            report.required_bytes += size.or(existing.map(|m| m.len())).unwrap_or(0);
+        }
+    } else {
+        let hashes = payload::read_manifest(payload_path, is_7z);
+        let available = components::read_manifest(payload_path, is_7z);
+        let selected = available.resolve(&options.components);
+        for entry in listing.entries.iter().filter(|e| available.includes_entry(&selected, &e.path)) {
+            let dest = root.join(&entry.path);
+            let expected = hashes.as_ref().and_then(|h| h.get(&entry.path));
+            if expected.is_some_and(|expected| extract::is_unchanged(&dest, expected)) {
+                report.unchanged += 1;
+            } else {
+                report.required_bytes += entry.size;
+                let list = if dest.exists() { &mut report.overwrite } else { &mut report.create };
+                list.push(entry.path.clone());
+            }
+        }
+        report.delete = manifest::load(install_path)
+            .map(|m| m.files)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|file| !available.includes_entry(&selected, file))
+            .collect();
+    }
+
+    if !options.portable {
+        let uninstaller = branding::get().uninstaller_exe();
+        if !cfg!(target_os = "macos") && !root.join(&uninstaller).exists() {
+            report.create.push(uninstaller);
+        }
+        report.shortcuts = shortcuts::planned_shortcuts(install_path, &options)?;
+        if options.register_file_associations {
+            report.file_associations = file_assoc::planned_changes(install_path, &options);
+        }
+    }
+    Ok(report)
+}
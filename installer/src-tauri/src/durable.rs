@@ -0,0 +1,76 @@
+//! Writes that survive a power cut. The files the app can't start without (the main
+//! executable, the asar archive) and the install manifest are written beside their
+//! destination, flushed to disk and then renamed over the old copy, so an interrupted
+//! update leaves either the old file or the new one, never a truncated mix.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::branding;
+
+/// Archive entries written durably, relative to the install root. Compared ignoring case
+/// and separators: the macOS bundle has the asar under `Contents/Resources`.
+pub fn is_critical(entry: &str) -> bool {
+    let normalized = |name: &str| name.replace('\\', "/").to_ascii_lowercase();
+    let entry = normalized(entry);
+    entry == normalized(&branding::get().exe_name) || entry == "resources/app.asar" || entry.ends_with("/resources/app.asar")
+}
+
+/// Where the new copy of `dest` is written before it replaces `dest`
+pub fn temp_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// Move `temp` over `dest` in one step, making sure the rename itself reaches the disk
+#[cfg(windows)]
+pub fn replace(temp: &Path, dest: &Path) -> std::io::Result<()> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{MoveFileExW, MOVEFILE_REPLACE_EXISTING, MOVEFILE_WRITE_THROUGH};
+
+    let flags = MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH;
+    unsafe { MoveFileExW(&HSTRING::from(temp), &HSTRING::from(dest), flags) }?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn replace(temp: &Path, dest: &Path) -> std::io::Result<()> {
+    std::fs::rename(temp, dest)?;
+    // The rename lives in the directory, which has to be synced too
+    if let Some(parent) = dest.parent() {
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Flush an already written file to disk
+pub fn sync(path: &Path) -> std::io::Result<()> {
+    std::fs::File::options().write(true).open(path)?.sync_all()
+}
+
+/// Replace `dest` with `contents` durably
+pub fn write(dest: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp = temp_path(dest);
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    replace(&temp, dest).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_asar_is_critical_wherever_the_platform_puts_it() {
+        assert!(is_critical("resources/app.asar"));
+        assert!(is_critical("resources\\app.asar"));
+        assert!(is_critical("Contents/Resources/app.asar"));
+        assert!(!is_critical("resources/app.asar.unpacked/addon.node"));
+        assert!(!is_critical("myresources/app.asar"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Running single steps with administrator rights. The installer itself runs per-user;
+//! the few opt-in steps that change machine-wide settings re-launch it elevated with
+//! `--elevated-task <task> [args]`, which shows one UAC prompt and does only that step.
+
+#[cfg(windows)]
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
+
+/// Run `--elevated-task <task> <args>` in an elevated copy of the installer and wait for it
+#[cfg(windows)]
+pub fn run_task(task: &str, args: &[&str]) -> Result<(), String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{GetExitCodeProcess, WaitForSingleObject, INFINITE};
+    use windows::Win32::UI::Shell::{ShellExecuteExW, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW};
+    use windows::Win32::UI::WindowsAndMessaging::SW_HIDE;
+
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let parameters = ["--elevated-task", task]
+        .iter()
+        .chain(args)
+        .map(|a| quote(a))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (verb, file, parameters) = (HSTRING::from("runas"), HSTRING::from(exe.as_path()), HSTRING::from(parameters));
+
+    let mut info = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        fMask: SEE_MASK_NOCLOSEPROCESS,
+        lpVerb: PCWSTR(verb.as_ptr()),
+        lpFile: PCWSTR(file.as_ptr()),
+        lpParameters: PCWSTR(parameters.as_ptr()),
+        nShow: SW_HIDE.0,
+        ..Default::default()
+    };
+    unsafe {
+        // Fails with ERROR_CANCELLED when the user declines the UAC prompt
+        ShellExecuteExW(&mut info).map_err(|e| format!("Could not get administrator rights for {}: {}", task, e))?;
+        WaitForSingleObject(info.hProcess, INFINITE);
+        let mut code = 1u32;
+        let result = GetExitCodeProcess(info.hProcess, &mut code);
+        let _ = CloseHandle(info.hProcess);
+        result.map_err(|e| e.to_string())?;
+        if code != 0 {
+            return Err(format!("Elevated step {} failed with code {}", task, code));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn run_task(task: &str, _args: &[&str]) -> Result<(), String> {
+    Err(format!("{} needs Windows", task))
+}
+
+/// Like `run_task`, for silent runs: without administrator rights already, the step is
+/// skipped with an error rather than showing a UAC prompt nobody is there to answer
+pub fn run_task_unattended(task: &str, args: &[&str]) -> Result<(), String> {
+    if !is_elevated() {
+        return Err(format!("Skipped {}: it needs administrator rights, which a silent run doesn't ask for", task));
+    }
+    run_task(task, args)
+}
+
+/// Whether this process already runs with administrator rights
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+            return false;
+        }
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = 0u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut TOKEN_ELEVATION as *mut _),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        );
+        let _ = CloseHandle(token);
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
+#[cfg(not(windows))]
+pub fn is_elevated() -> bool {
+    false
+}
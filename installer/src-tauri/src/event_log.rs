@@ -0,0 +1,107 @@
+//! Install, update and uninstall events in the Windows Application event log, enabled
+//! with `--event-log` so admins can monitor machines without collecting our log files.
+//!
+//! The event source isn't registered (that needs HKLM), so Event Viewer shows the
+//! message text as an insertion string rather than from a message table.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::branding;
+#[cfg(windows)]
+use crate::debug_log;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The operation `started` was last called for, finished by `finish_current`
+static CURRENT: Mutex<Option<Operation>> = Mutex::new(None);
+
+#[derive(Clone, Copy)]
+pub enum Operation {
+    Install,
+    Update,
+    Uninstall,
+}
+
+impl Operation {
+    fn name(self) -> &'static str {
+        match self {
+            Operation::Install => "Install",
+            Operation::Update => "Update",
+            Operation::Uninstall => "Uninstall",
+        }
+    }
+
+    /// Event IDs: 1000 + 100 * operation + 0 (started), 1 (succeeded) or 2 (failed)
+    fn event_id(self, outcome: u32) -> u32 {
+        let base = match self {
+            Operation::Install => 1000,
+            Operation::Update => 1100,
+            Operation::Uninstall => 1200,
+        };
+        base + outcome
+    }
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+#[cfg(windows)]
+fn write(event_id: u32, error: bool, message: &str) {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::System::EventLog::{DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE};
+
+    let source = HSTRING::from(format!("{} Installer", branding::get().app_name));
+    let text = HSTRING::from(message);
+    let kind = if error { EVENTLOG_ERROR_TYPE } else { EVENTLOG_INFORMATION_TYPE };
+    unsafe {
+        let handle = match RegisterEventSourceW(PCWSTR::null(), &source) {
+            Ok(handle) => handle,
+            Err(e) => return debug_log(&format!("Failed to open the event log: {}", e)),
+        };
+        if let Err(e) = ReportEventW(handle, kind, 0, event_id, None, 0, Some(&[PCWSTR(text.as_ptr())]), None) {
+            debug_log(&format!("Failed to write event {}: {}", event_id, e));
+        }
+        let _ = DeregisterEventSource(handle);
+    }
+}
+
+#[cfg(not(windows))]
+fn write(_event_id: u32, _error: bool, _message: &str) {}
+
+pub fn started(operation: Operation, version: Option<&str>) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(operation);
+    }
+    if ENABLED.load(Ordering::Relaxed) {
+        let target = match version {
+            Some(version) => format!("{} {}", branding::get().app_name, version),
+            None => branding::get().app_name.clone(),
+        };
+        let message = format!("{} of {} started", operation.name(), target);
+        write(operation.event_id(0), false, &message);
+    }
+}
+
+/// Record the outcome of `operation`; `code` is the process exit code (0 for success)
+fn finished(operation: Operation, code: i32, message: Option<&str>) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let app_name = &branding::get().app_name;
+    if code == 0 {
+        write(operation.event_id(1), false, &format!("{} of {} succeeded", operation.name(), app_name));
+    } else {
+        let detail = message.unwrap_or("see installer-debug.log");
+        write(operation.event_id(2), true, &format!("{} of {} failed with code {}: {}", operation.name(), app_name, code, detail));
+    }
+}
+
+/// Record the outcome of the operation last started, if any
+pub fn finish_current(code: i32, message: Option<&str>) {
+    let operation = CURRENT.lock().ok().and_then(|mut current| current.take());
+    if let Some(operation) = operation {
+        finished(operation, code, message);
+    }
+}
@@ -0,0 +1,71 @@
+//! Extension packages dropped into the app's extensions folder on a first install, so a
+//! build can ship a curated set of sources.
+//!
+//! Packages come from an `extensions` folder next to the payload and, optionally, a
+//! folder the user picks. A package is a folder holding `manifest.json` and `index.js`,
+//! the layout the app loads; it is installed as `<data dir>\extensions\<id>`. Extensions
+//! the user already has are left alone.
+
+use std::path::{Path, PathBuf};
+
+use crate::debug_log;
+use crate::migrate::copy_recursive;
+
+/// Folder under the data directory the app loads extensions from, and the name of the
+/// folder of bundled packages next to the payload
+pub const EXTENSIONS_DIR: &str = "extensions";
+
+#[derive(Default, serde::Serialize)]
+pub struct PreinstallReport {
+    pub installed: Vec<String>,
+    /// Already installed, so not touched
+    pub skipped: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct PackageManifest {
+    id: String,
+}
+
+/// The extension id of the package in `dir`, if it is one
+fn package_id(dir: &Path) -> Option<String> {
+    if !dir.join("index.js").is_file() {
+        return None;
+    }
+    let json = std::fs::read_to_string(dir.join("manifest.json")).ok()?;
+    let manifest: PackageManifest = serde_json::from_str(&json).ok()?;
+    // The id becomes a folder name
+    let valid = !manifest.id.is_empty() && !manifest.id.contains(['/', '\\', ':']) && manifest.id != "." && manifest.id != "..";
+    valid.then_some(manifest.id)
+}
+
+/// Packages in `source`: the folder itself, or its immediate subfolders
+fn find_packages(source: &Path) -> Vec<(String, PathBuf)> {
+    if let Some(id) = package_id(source) {
+        return vec![(id, source.to_path_buf())];
+    }
+    let Ok(entries) = std::fs::read_dir(source) else { return Vec::new() };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter_map(|p| Some((package_id(&p)?, p)))
+        .collect()
+}
+
+/// Copy the packages found in `sources` into the extensions folder of `data_dir`
+pub fn preinstall(sources: &[PathBuf], data_dir: &Path) -> Result<PreinstallReport, String> {
+    let target = data_dir.join(EXTENSIONS_DIR);
+    let mut report = PreinstallReport::default();
+    for (id, package) in sources.iter().flat_map(|s| find_packages(s)) {
+        let destination = target.join(&id);
+        if destination.exists() || report.installed.contains(&id) {
+            report.skipped.push(id);
+            continue;
+        }
+        copy_recursive(&package, &destination)?;
+        report.installed.push(id);
+    }
+    debug_log(&format!("Preinstalled extensions: {:?} (skipped {:?})", report.installed, report.skipped));
+    Ok(report)
+}
@@ -0,0 +1,582 @@
+//! Payload extraction for both archive formats.
+//!
+//! When a hash manifest is available, entries whose file on disk already matches
+//! are skipped, so small updates only rewrite what actually changed.
+
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::debug_log;
+use crate::durable;
+use crate::hash;
+use crate::network_share;
+use crate::pending;
+use crate::system_info;
+use crate::payload::{self, PayloadFile, PayloadManifest};
+
+#[derive(Default)]
+pub struct ExtractStats {
+    pub written: usize,
+    pub skipped: usize,
+    /// Every file entry in the archive (written or unchanged), with `/` separators
+    pub files: Vec<String>,
+    /// Symlink entries and their targets, created once every other entry is in place
+    links: Vec<(String, String)>,
+}
+
+/// Read buffer over the archive file
+const READ_BUFFER: usize = 1024 * 1024;
+
+static PAUSED: Mutex<bool> = Mutex::new(false);
+static RESUMED: Condvar = Condvar::new();
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Error returned by an extraction stopped with `cancel`
+pub const CANCELLED_MESSAGE: &str = "Installation cancelled";
+
+#[cfg(feature = "gui")]
+/// Hold extraction at its next read until `resume` is called
+pub fn pause() {
+    if let Ok(mut paused) = PAUSED.lock() {
+        *paused = true;
+    }
+}
+
+#[cfg(feature = "gui")]
+pub fn resume() {
+    if let Ok(mut paused) = PAUSED.lock() {
+        *paused = false;
+    }
+    RESUMED.notify_all();
+}
+
+#[cfg(feature = "gui")]
+/// Stop extraction at its next read, paused or not
+pub fn cancel() {
+    CANCELLED.store(true, Ordering::SeqCst);
+    // Taking the lock first means a thread about to wait sees the flag instead of missing the wakeup
+    drop(PAUSED.lock());
+    RESUMED.notify_all();
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+#[cfg(feature = "gui")]
+/// Clear a pause or cancel left over from an earlier attempt, which would hold or stop this one
+pub fn reset() {
+    CANCELLED.store(false, Ordering::SeqCst);
+    resume();
+}
+
+/// Called between entries and between reads within one: blocks while paused and fails
+/// once cancelled, so either takes effect within one read buffer
+fn checkpoint() -> std::io::Result<()> {
+    if let Ok(paused) = PAUSED.lock() {
+        if *paused && !is_cancelled() {
+            debug_log("Extraction paused");
+            drop(RESUMED.wait_while(paused, |paused| *paused && !is_cancelled()));
+            debug_log("Extraction resumed");
+        }
+    }
+    if is_cancelled() {
+        return Err(std::io::Error::other(CANCELLED_MESSAGE));
+    }
+    Ok(())
+}
+
+/// True when `dest` already holds exactly the file described by the manifest
+pub fn is_unchanged(dest: &Path, expected: &PayloadFile) -> bool {
+    match std::fs::metadata(dest) {
+        Ok(meta) if meta.is_file() && meta.len() == expected.size => {
+            hash::sha256_file(dest).is_ok_and(|actual| actual.eq_ignore_ascii_case(&expected.sha256))
+        }
+        _ => false,
+    }
+}
+
+/// Decides which archive entries are extracted; entries it rejects are left out entirely
+pub type EntryFilter<'a> = &'a dyn Fn(&str) -> bool;
+
+/// Called with the entry being processed and the uncompressed bytes processed so far,
+/// skipped and filtered entries included
+pub type ByteProgress<'a> = &'a mut dyn FnMut(&str, u64);
+
+/// Counts the bytes read through it into `done` and reports the running total along
+/// with the entry they belong to
+struct Counted<'a, R: ?Sized, F> {
+    inner: &'a mut R,
+    name: &'a str,
+    done: &'a mut u64,
+    report: &'a mut F,
+}
+
+impl<R: Read + ?Sized, F: FnMut(&str, u64)> Read for Counted<'_, R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        checkpoint()?;
+        let n = self.inner.read(buf)?;
+        *self.done += n as u64;
+        (self.report)(self.name, *self.done);
+        Ok(n)
+    }
+}
+
+/// Modification time and read-only flag an archive entry records, restored once the file
+/// is written so tools comparing mtimes see the build's times rather than the install's
+struct EntryMetadata {
+    modified: Option<SystemTime>,
+    readonly: bool,
+    /// Unix permission bits, for archives made on Unix
+    #[cfg_attr(not(unix), allow(dead_code))]
+    unix_mode: Option<u32>,
+}
+
+/// File-type bits of a Unix mode, and the value marking a symlink
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+fn is_symlink(unix_mode: Option<u32>) -> bool {
+    unix_mode.is_some_and(|mode| mode & S_IFMT == S_IFLNK)
+}
+
+/// Unix mode p7zip and 7-Zip store in the high word of the attributes of entries
+/// archived on Unix
+fn sevenz_unix_mode(entry: &sevenz_rust::SevenZArchiveEntry) -> Option<u32> {
+    const FILE_ATTRIBUTE_UNIX_EXTENSION: u32 = 0x8000;
+    let attributes = entry.windows_attributes();
+    (entry.has_windows_attributes && attributes & FILE_ATTRIBUTE_UNIX_EXTENSION != 0).then_some(attributes >> 16)
+}
+
+fn restore_metadata(path: &Path, metadata: &EntryMetadata) -> Result<(), String> {
+    if let Some(modified) = metadata.modified {
+        let file = std::fs::File::options().write(true).open(path).map_err(|e| e.to_string())?;
+        file.set_modified(modified).map_err(|e| format!("Failed to set the time of {:?}: {}", path, e))?;
+    }
+    #[cfg(unix)]
+    if let Some(mode) = metadata.unix_mode {
+        use std::os::unix::fs::PermissionsExt;
+        // Permission bits only: never setuid or setgid from a payload
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & 0o777)).map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    if metadata.readonly {
+        let mut permissions = std::fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(path, permissions).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Clear the read-only flag an earlier install restored, so the file can be replaced
+pub fn make_writable(path: &Path) {
+    let Ok(metadata) = std::fs::metadata(path) else { return };
+    let mut permissions = metadata.permissions();
+    if permissions.readonly() {
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        let _ = std::fs::set_permissions(path, permissions);
+    }
+}
+
+/// The time in a zip entry's DOS date fields, read as UTC (zip doesn't record a zone).
+/// None for the 1980-01-01 00:00 placeholder of entries without one.
+fn zip_modified(time: zip::DateTime) -> Option<SystemTime> {
+    let (year, month, day) = (time.year() as i64, time.month() as i64, time.day() as i64);
+    if (year, month, day, time.hour(), time.minute(), time.second()) == (1980, 1, 1, 0, 0, 0) {
+        return None;
+    }
+    utc_time(year, month, day, time.hour() as i64 * 3600 + time.minute() as i64 * 60 + time.second() as i64)
+}
+
+/// The UTC calendar date plus `seconds` into the day, or None before 1970
+pub fn utc_time(year: i64, month: i64, day: i64, seconds: i64) -> Option<SystemTime> {
+    // Days since 1970-01-01 in the proleptic Gregorian calendar
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(days * 86400 + seconds).ok()?))
+}
+
+/// Create `path` for writing `size` bytes, reserving the space up front so the file
+/// system can lay it out in one piece instead of growing it write by write. Not on
+/// network shares (`local` false), where SMB servers may not support it.
+fn create_preallocated(path: &Path, size: u64, local: bool) -> std::io::Result<std::fs::File> {
+    let file = std::fs::File::create(path)?;
+    if size > 0 && local {
+        if let Err(e) = preallocate(&file, size) {
+            debug_log(&format!("Could not preallocate {:?}: {}", path, e));
+        }
+    }
+    Ok(file)
+}
+
+/// Reserves clusters without moving the end of file, so nothing has to be zero-filled
+#[cfg(windows)]
+fn preallocate(file: &std::fs::File, size: u64) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::{FileAllocationInfo, SetFileInformationByHandle, FILE_ALLOCATION_INFO};
+
+    let info = FILE_ALLOCATION_INFO { AllocationSize: size as i64 };
+    unsafe {
+        SetFileInformationByHandle(
+            HANDLE(file.as_raw_handle()),
+            FileAllocationInfo,
+            &info as *const _ as *const _,
+            std::mem::size_of_val(&info) as u32,
+        )
+    }?;
+    Ok(())
+}
+
+/// Extending the file with `set_len` would only make it sparse; leave allocation to the
+/// file system
+#[cfg(not(windows))]
+fn preallocate(_file: &std::fs::File, _size: u64) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Check that the archive entry `name` stays below the folder it's extracted into: only
+/// plain file and folder names, no `..`, drive or root (Zip Slip). A payload naming
+/// anything else is rejected outright.
+pub fn check_relative(name: &str) -> Result<(), String> {
+    let path = Path::new(name);
+    if name.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Payload names a path outside the install folder: {}", name));
+    }
+    Ok(())
+}
+
+/// Where the link `name` pointing at `target` resolves to, relative to the install root.
+/// None when the target is absolute or climbs out of the root, which a payload has no
+/// business doing.
+fn resolve_link(name: &str, target: &str) -> Option<PathBuf> {
+    let mut resolved = Vec::new();
+    let parent = Path::new(name).parent().unwrap_or(Path::new(""));
+    for component in parent.components().chain(Path::new(target).components()) {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::ParentDir => {
+                resolved.pop()?;
+            }
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(resolved.iter().collect())
+}
+
+/// Recreate the archive's symlinks under `root`, replacing whatever is at each location
+fn create_links(root: &Path, links: &[(String, String)]) -> Result<(), String> {
+    for (name, target) in links {
+        let resolved = resolve_link(name, target)
+            .ok_or_else(|| format!("Link {} points outside the install folder: {}", name, target))?;
+        let dest = root.join(name);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        if dest.symlink_metadata().is_ok_and(|m| !m.is_dir()) {
+            make_writable(&dest);
+            std::fs::remove_file(&dest).map_err(|e| format!("Failed to replace {:?}: {}", dest, e))?;
+        }
+        symlink(&root.join(resolved), Path::new(target), &dest)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink(_resolved: &Path, target: &Path, dest: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(target, dest).map_err(|e| format!("Failed to link {:?}: {}", dest, e))
+}
+
+/// Creating symlinks needs Developer Mode or administrator rights on Windows; without
+/// them a file link falls back to a copy of its target
+#[cfg(windows)]
+fn symlink(resolved: &Path, target: &Path, dest: &Path) -> Result<(), String> {
+    let linked = if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    };
+    match linked {
+        Ok(()) => Ok(()),
+        Err(_) if resolved.is_file() => {
+            debug_log(&format!("Cannot create symlink {:?}; copying its target instead", dest));
+            std::fs::copy(resolved, dest).map(|_| ()).map_err(|e| format!("Failed to copy {:?}: {}", resolved, e))
+        }
+        Err(e) => Err(format!("Failed to link {:?}: {}", dest, e)),
+    }
+}
+
+/// Largest LZMA/LZMA2 dictionary among the archive's solid blocks, which is what the
+/// decoder holds in memory while unpacking them
+fn decoder_window(archive: &sevenz_rust::Archive) -> Option<u64> {
+    const LZMA: &[u8] = &[3, 1, 1];
+    const LZMA2: &[u8] = &[0x21];
+    archive
+        .folders
+        .iter()
+        .flat_map(|folder| &folder.coders)
+        .filter_map(|coder| match coder.decompression_method_id() {
+            LZMA => Some(u32::from_le_bytes(coder.properties.get(1..5)?.try_into().ok()?) as u64),
+            LZMA2 => {
+                let bits = *coder.properties.first()? as u64;
+                Some(if bits >= 40 { u32::MAX as u64 } else { (2 | (bits & 1)) << (bits / 2 + 11) })
+            }
+            _ => None,
+        })
+        .max()
+}
+
+pub fn extract_7z(archive_path: &Path, output_path: &str, manifest: Option<&PayloadManifest>, filter: Option<EntryFilter>, mut progress: Option<ByteProgress>) -> Result<ExtractStats, String> {
+    let mut stats = ExtractStats::default();
+    let mut done = 0;
+    let mut report = |name: &str, done| {
+        if let Some(progress) = progress.as_mut() {
+            progress(name, done);
+        }
+    };
+
+    // Entries are decoded one at a time straight into their files, so memory stays at the
+    // decoder's window plus a read buffer however large the archive is
+    let file = std::fs::File::open(archive_path).map_err(|e| format!("Failed to open {:?}: {}", archive_path, e))?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let source = std::io::BufReader::with_capacity(READ_BUFFER, file);
+    let mut archive = sevenz_rust::SevenZReader::new(source, len, sevenz_rust::Password::empty()).map_err(|e| e.to_string())?;
+    if let Some(window) = decoder_window(archive.archive()) {
+        debug_log(&format!("7z decoder window: {} MiB", window / (1024 * 1024)));
+    }
+    let root = Path::new(output_path);
+    std::fs::create_dir_all(root).map_err(|e| e.to_string())?;
+    let local = !network_share::is_network(root);
+
+    archive.for_each_entries(|entry, reader| {
+        checkpoint().map_err(sevenz_rust::Error::io)?;
+        check_relative(entry.name()).map_err(sevenz_rust::Error::other)?;
+        let dest = &root.join(entry.name());
+        // Filtered and unchanged entries are still decompressed, so they count too
+        let reader = &mut Counted { inner: reader, name: entry.name(), done: &mut done, report: &mut report };
+        if filter.is_some_and(|include| !include(entry.name())) {
+            std::io::copy(reader, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+            return Ok(true);
+        }
+        if !entry.is_directory() {
+            stats.files.push(payload::normalize_entry_name(entry.name()));
+            let unix_mode = sevenz_unix_mode(entry);
+            if is_symlink(unix_mode) {
+                let mut target = String::new();
+                reader.read_to_string(&mut target).map_err(sevenz_rust::Error::io)?;
+                stats.links.push((entry.name().to_string(), target));
+                return Ok(true);
+            }
+            if let Some(expected) = manifest.and_then(|m| m.get(entry.name())) {
+                if is_unchanged(dest, expected) {
+                    // Still drain the entry: it may share a solid block with the next one
+                    std::io::copy(reader, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+                    stats.skipped += 1;
+                    return Ok(true);
+                }
+            }
+            stats.written += 1;
+            make_writable(dest);
+            // A file that stays locked gets a pending copy, moved into place later
+            let target = pending::writable_target(dest);
+            // Files the app can't start without replace the old copy only once on disk
+            let critical = durable::is_critical(entry.name()) && target == *dest;
+            let written = if critical { durable::temp_path(&target) } else { target.clone() };
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(sevenz_rust::Error::io)?;
+            }
+            let outfile = create_preallocated(&written, entry.size(), local).map_err(sevenz_rust::Error::io)?;
+            let mut writer = std::io::BufWriter::new(outfile);
+            std::io::copy(reader, &mut writer).map_err(sevenz_rust::Error::io)?;
+            writer.flush().map_err(sevenz_rust::Error::io)?;
+            if critical {
+                writer.get_ref().sync_all().map_err(sevenz_rust::Error::io)?;
+            }
+            drop(writer);
+            const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+            let metadata = EntryMetadata {
+                // Without a recorded time the extraction time is the honest one
+                modified: Some(if entry.has_last_modified_date { entry.last_modified_date().into() } else { SystemTime::now() }),
+                readonly: entry.has_windows_attributes && entry.windows_attributes() & FILE_ATTRIBUTE_READONLY != 0,
+                unix_mode,
+            };
+            restore_metadata(&written, &metadata).map_err(sevenz_rust::Error::other)?;
+            if critical {
+                durable::replace(&written, &target).map_err(sevenz_rust::Error::io)?;
+            }
+            return Ok(true);
+        }
+        std::fs::create_dir_all(dest).map_err(sevenz_rust::Error::io)?;
+        Ok(true)
+    })
+    // The reader's error comes back wrapped; report the cancel as itself
+    .map_err(|e| if is_cancelled() { CANCELLED_MESSAGE.to_string() } else { e.to_string() })?;
+    create_links(Path::new(output_path), &stats.links)?;
+
+    debug_log(&format!("7z extraction: {} files written, {} unchanged", stats.written, stats.skipped));
+    if let Some(peak) = system_info::peak_memory_bytes() {
+        debug_log(&format!("Peak memory: {} MiB", peak / (1024 * 1024)));
+    }
+    Ok(stats)
+}
+
+/// Computes the CRC-32 of everything written through it
+struct CrcWriter<W> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> Write for CrcWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Check a written zip entry against the CRC-32 and size its header records, so a bad
+/// disk or an interrupted write fails the install instead of the app's first launch
+fn verify_written(path: &Path, name: &str, expected_crc: u32, actual_crc: u32, expected_size: u64) -> Result<(), String> {
+    if actual_crc != expected_crc {
+        return Err(format!("CRC mismatch for {}: expected {:08x}, wrote {:08x}", name, expected_crc, actual_crc));
+    }
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size != expected_size {
+        return Err(format!("Size mismatch for {}: expected {} bytes, found {}", name, expected_size, size));
+    }
+    Ok(())
+}
+
+pub fn extract_zip(archive_path: &Path, output_path: &str, manifest: Option<&PayloadManifest>, filter: Option<EntryFilter>, mut progress: Option<ByteProgress>) -> Result<ExtractStats, String> {
+    let file = std::fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open zip file at {:?}: {}", archive_path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let local = !network_share::is_network(Path::new(output_path));
+    let mut stats = ExtractStats::default();
+    let mut done = 0;
+    let mut report = |name: &str, done| {
+        if let Some(progress) = progress.as_mut() {
+            progress(name, done);
+        }
+    };
+
+    for i in 0..archive.len() {
+        checkpoint().map_err(|e| e.to_string())?;
+        let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
+        let file_name = file.name().to_string();
+        check_relative(&file_name)?;
+        let outpath = Path::new(output_path).join(&file_name);
+        if filter.is_some_and(|include| !include(&file_name)) {
+            done += file.size();
+            report(&file_name, done);
+            continue;
+        }
+
+        if file.is_dir() || file_name.ends_with('/') {
+            std::fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+        } else {
+            stats.files.push(payload::normalize_entry_name(&file_name));
+            if is_symlink(file.unix_mode()) {
+                let mut target = String::new();
+                let mut reader = Counted { inner: &mut file, name: &file_name, done: &mut done, report: &mut report };
+                reader.read_to_string(&mut target).map_err(|e| e.to_string())?;
+                stats.links.push((file_name, target));
+                continue;
+            }
+            if let Some(expected) = manifest.and_then(|m| m.get(&file_name)) {
+                if is_unchanged(&outpath, expected) {
+                    stats.skipped += 1;
+                    done += file.size();
+                    report(&file_name, done);
+                    continue;
+                }
+            }
+            if let Some(p) = outpath.parent() {
+                if !p.exists() {
+                    std::fs::create_dir_all(p).map_err(|e| e.to_string())?;
+                }
+            }
+            make_writable(&outpath);
+            let target = pending::writable_target(&outpath);
+            let critical = durable::is_critical(&file_name) && target == outpath;
+            let written = if critical { durable::temp_path(&target) } else { target.clone() };
+            let outfile = create_preallocated(&written, file.size(), local).map_err(|e| e.to_string())?;
+            let mut writer = CrcWriter { inner: outfile, hasher: crc32fast::Hasher::new() };
+            let mut reader = Counted { inner: &mut file, name: &file_name, done: &mut done, report: &mut report };
+            std::io::copy(&mut reader, &mut writer).map_err(|e| e.to_string())?;
+            let CrcWriter { mut inner, hasher } = writer;
+            inner.flush().map_err(|e| e.to_string())?;
+            if critical {
+                inner.sync_all().map_err(|e| e.to_string())?;
+            }
+            // Closed before the time is restored, or closing it would touch the time again
+            drop(inner);
+            verify_written(&written, &file_name, file.crc32(), hasher.finalize(), file.size())?;
+            let metadata = EntryMetadata {
+                modified: zip_modified(file.last_modified()),
+                readonly: file.unix_mode().is_some_and(|mode| mode & 0o222 == 0),
+                unix_mode: file.unix_mode(),
+            };
+            restore_metadata(&written, &metadata)?;
+            if critical {
+                durable::replace(&written, &target).map_err(|e| format!("Failed to replace {:?}: {}", target, e))?;
+            }
+            stats.written += 1;
+        }
+    }
+
+    create_links(Path::new(output_path), &stats.links)?;
+
+    debug_log(&format!("Zip extraction: {} files written, {} unchanged", stats.written, stats.skipped));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_plain_relative_names_are_accepted() {
+        for name in ["Mangyomi.exe", "resources/app.asar", "locales/en-US.pak", "tools/"] {
+            assert!(check_relative(name).is_ok(), "{}", name);
+        }
+        for name in ["", "../evil", "resources/../../evil", "./app.asar", "/etc/passwd"] {
+            assert!(check_relative(name).is_err(), "{}", name);
+        }
+        #[cfg(windows)]
+        for name in ["C:\\Windows\\evil.dll", "C:evil", "\\\\server\\share\\evil", "..\\evil"] {
+            assert!(check_relative(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn zip_entries_outside_the_output_folder_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("extract-zip-slip-{}", std::process::id()));
+        let output = dir.join("install");
+        std::fs::create_dir_all(&output).unwrap();
+        let archive = dir.join("payload.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        zip.start_file("../evil", zip::write::FileOptions::default()).unwrap();
+        zip.write_all(b"escaped").unwrap();
+        zip.finish().unwrap();
+
+        let result = extract_zip(&archive, &output.to_string_lossy(), None, None, None);
+        let escaped = dir.join("evil").exists();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(result.is_err_and(|e| e.contains("../evil")));
+        assert!(!escaped);
+    }
+}
@@ -0,0 +1,193 @@
+//! Per-user file associations (HKCU\Software\Classes), mirroring the `fileAssociations`
+//! in electron-builder.config.js so the Tauri installer registers the same types.
+//!
+//! On Linux the backup type is declared through a shared-mime-info package and the
+//! desktop entry from `shortcuts` becomes the default handler for it and the URL scheme.
+//! On macOS the bundle's Info.plist declares both, so it only needs registering with
+//! LaunchServices.
+
+#[cfg(not(target_os = "macos"))]
+use std::path::Path;
+
+use crate::branding;
+use crate::manifest::InstallOptions;
+#[cfg(windows)]
+use crate::registry;
+
+/// Backup files exported by the app
+#[cfg(not(target_os = "macos"))]
+const BACKUP_EXTENSION: &str = ".mgb";
+/// Must match `mimeType` of the backup association in electron-builder.config.js
+#[cfg(not(any(windows, target_os = "macos")))]
+pub const BACKUP_MIME_TYPE: &str = "application/x-mangyomi-backup";
+
+#[cfg(windows)]
+fn backup_prog_id() -> String {
+    format!("{}.Backup", branding::get().app_name)
+}
+
+#[cfg(windows)]
+fn notify_shell() {
+    use windows::Win32::UI::Shell::{SHChangeNotify, SHCNE_ASSOCCHANGED, SHCNF_IDLIST};
+    // Explorer caches associations until told they changed
+    unsafe { SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, None, None) };
+}
+
+/// The registry keys `register` writes (under HKCU), for `--dry-run` and `--verify`
+#[cfg(windows)]
+fn keys() -> Vec<String> {
+    let classes = "Software\\Classes";
+    vec![format!("{}\\{}", classes, BACKUP_EXTENSION), format!("{}\\{}", classes, backup_prog_id())]
+}
+
+#[cfg(windows)]
+pub fn planned_changes(_install_path: &str, _options: &InstallOptions) -> Vec<String> {
+    keys().into_iter().map(|key| format!("HKCU\\{}", key)).collect()
+}
+
+/// Planned changes that aren't in place
+#[cfg(windows)]
+pub fn missing_changes(_install_path: &str) -> Vec<String> {
+    keys().into_iter().filter(|key| !registry::key_exists(key)).map(|key| format!("HKCU\\{}", key)).collect()
+}
+
+#[cfg(windows)]
+pub fn register(install_path: &str, _options: &InstallOptions) -> Result<(), String> {
+    let exe_path = Path::new(install_path).join(&branding::get().exe_name);
+    let prog_id = backup_prog_id();
+    let classes = "Software\\Classes";
+
+    registry::set_string(&format!("{}\\{}", classes, BACKUP_EXTENSION), None, &prog_id)?;
+    registry::set_string(&format!("{}\\{}", classes, prog_id), None, &format!("{} Backup File", branding::get().app_name))?;
+    registry::set_string(&format!("{}\\{}\\DefaultIcon", classes, prog_id), None, &format!("\"{}\",0", exe_path.display()))?;
+    registry::set_string(
+        &format!("{}\\{}\\shell\\open\\command", classes, prog_id),
+        None,
+        &format!("\"{}\" \"%1\"", exe_path.display()),
+    )?;
+
+    notify_shell();
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn unregister(_install_path: &str) -> Result<(), String> {
+    let classes = "Software\\Classes";
+    registry::delete_tree(&format!("{}\\{}", classes, backup_prog_id()))?;
+    registry::delete_tree(&format!("{}\\{}", classes, BACKUP_EXTENSION))?;
+    notify_shell();
+    Ok(())
+}
+
+/// The user's shared-mime-info package declaring the backup type
+#[cfg(not(any(windows, target_os = "macos")))]
+fn mime_package() -> Option<std::path::PathBuf> {
+    crate::known_folders::get(crate::known_folders::KnownFolder::UserProgramFiles)
+        .map(|dir| dir.join("mime").join("packages").join(format!("{}.xml", branding::get().install_dir_name)))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn update_mime_database(package: &Path) {
+    if let Some(mime_dir) = package.parent().and_then(Path::parent) {
+        let _ = std::process::Command::new("update-mime-database").arg(mime_dir).status();
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn planned_changes(_install_path: &str, options: &InstallOptions) -> Vec<String> {
+    let mut changes: Vec<String> = mime_package().map(|p| p.display().to_string()).into_iter().collect();
+    changes.push(format!(
+        "xdg-mime default {} {} x-scheme-handler/{}",
+        crate::shortcuts::desktop_file_id(options),
+        BACKUP_MIME_TYPE,
+        branding::get().url_scheme
+    ));
+    changes
+}
+
+/// The mime package is what `register` leaves behind; the xdg-mime defaults can't be
+/// told apart from the user's own choices
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn missing_changes(_install_path: &str) -> Vec<String> {
+    mime_package().filter(|p| !p.is_file()).map(|p| p.display().to_string()).into_iter().collect()
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn register(_install_path: &str, options: &InstallOptions) -> Result<(), String> {
+    let branding = branding::get();
+    let package = mime_package().ok_or("Data folder not found")?;
+    let xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <mime-info xmlns=\"http://www.freedesktop.org/standards/shared-mime-info\">\n  \
+         <mime-type type=\"{}\">\n    <comment>{} Backup File</comment>\n    <glob pattern=\"*{}\"/>\n  </mime-type>\n\
+         </mime-info>\n",
+        BACKUP_MIME_TYPE, branding.app_name, BACKUP_EXTENSION
+    );
+    if let Some(dir) = package.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&package, xml).map_err(|e| format!("Failed to write {:?}: {}", package, e))?;
+    update_mime_database(&package);
+
+    let status = std::process::Command::new("xdg-mime")
+        .arg("default")
+        .arg(crate::shortcuts::desktop_file_id(options))
+        .arg(BACKUP_MIME_TYPE)
+        .arg(format!("x-scheme-handler/{}", branding.url_scheme))
+        .status()
+        .map_err(|e| format!("Failed to run xdg-mime: {}", e))?;
+    if !status.success() {
+        return Err(format!("xdg-mime exited with {}", status));
+    }
+    Ok(())
+}
+
+/// The xdg-mime defaults point at the desktop entry, which goes with the shortcuts
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn unregister(_install_path: &str) -> Result<(), String> {
+    let Some(package) = mime_package() else { return Ok(()) };
+    if package.exists() {
+        std::fs::remove_file(&package).map_err(|e| format!("Failed to remove {:?}: {}", package, e))?;
+        update_mime_database(&package);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+const LSREGISTER: &str =
+    "/System/Library/Frameworks/CoreServices.framework/Frameworks/LaunchServices.framework/Support/lsregister";
+
+#[cfg(target_os = "macos")]
+fn lsregister(flag: &str, install_path: &str) -> Result<(), String> {
+    let status = std::process::Command::new(LSREGISTER)
+        .arg(flag)
+        .arg(install_path)
+        .status()
+        .map_err(|e| format!("Failed to run lsregister: {}", e))?;
+    if !status.success() {
+        return Err(format!("lsregister exited with {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn planned_changes(install_path: &str, _options: &InstallOptions) -> Vec<String> {
+    vec![format!("LaunchServices registration of {}", install_path)]
+}
+
+/// LaunchServices has no per-app query short of dumping its whole database
+#[cfg(target_os = "macos")]
+pub fn missing_changes(_install_path: &str) -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+pub fn register(install_path: &str, _options: &InstallOptions) -> Result<(), String> {
+    lsregister("-f", install_path)
+}
+
+/// Deregister before the bundle goes, so LaunchServices doesn't keep offering it
+#[cfg(target_os = "macos")]
+pub fn unregister(install_path: &str) -> Result<(), String> {
+    lsregister("-u", install_path)
+}
@@ -0,0 +1,87 @@
+//! Opt-in Windows Defender Firewall rule letting the app accept connections on private
+//! networks, so local network sharing works without the first-run firewall prompt.
+//!
+//! Changing firewall rules needs administrator rights; these run in the elevated
+//! helper (see `elevate`).
+
+use std::path::Path;
+
+use crate::{branding, elevate};
+
+/// Task names for `--elevated-task`
+pub const ADD_TASK: &str = "firewall-add";
+pub const REMOVE_TASK: &str = "firewall-remove";
+
+#[cfg(windows)]
+fn rule_name() -> String {
+    format!("{} (local network sharing)", branding::get().app_name)
+}
+
+/// Add the rule for the install at `install_path`, asking for administrator rights
+pub fn request_rule(install_path: &str) -> Result<(), String> {
+    let exe = Path::new(install_path).join(&branding::get().exe_name);
+    elevate::run_task(ADD_TASK, &[&exe.to_string_lossy()])
+}
+
+/// Remove the rule, asking for administrator rights unless `silent`
+pub fn request_removal(silent: bool) -> Result<(), String> {
+    if silent {
+        elevate::run_task_unattended(REMOVE_TASK, &[])
+    } else {
+        elevate::run_task(REMOVE_TASK, &[])
+    }
+}
+
+#[cfg(windows)]
+fn with_policy<T>(f: impl FnOnce(&windows::Win32::NetworkManagement::WindowsFirewall::INetFwPolicy2) -> windows::core::Result<T>) -> Result<T, String> {
+    use windows::Win32::NetworkManagement::WindowsFirewall::{INetFwPolicy2, NetFwPolicy2};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+
+    unsafe {
+        let initialized = CoInitializeEx(None, COINIT_APARTMENTTHREADED).is_ok();
+        let result = CoCreateInstance::<_, INetFwPolicy2>(&NetFwPolicy2, None, CLSCTX_INPROC_SERVER).and_then(|policy| f(&policy));
+        if initialized {
+            CoUninitialize();
+        }
+        result.map_err(|e| format!("Firewall update failed: {}", e))
+    }
+}
+
+/// Allow inbound connections to `exe` on private networks, replacing an earlier rule
+#[cfg(windows)]
+pub fn add_rule(exe: &Path) -> Result<(), String> {
+    use windows::core::BSTR;
+    use windows::Win32::Foundation::VARIANT_TRUE;
+    use windows::Win32::NetworkManagement::WindowsFirewall::{INetFwRule, NetFwRule, NET_FW_ACTION_ALLOW, NET_FW_PROFILE2_PRIVATE, NET_FW_RULE_DIR_IN};
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+
+    let name = BSTR::from(rule_name());
+    with_policy(|policy| unsafe {
+        let rules = policy.Rules()?;
+        let _ = rules.Remove(&name);
+        let rule: INetFwRule = CoCreateInstance(&NetFwRule, None, CLSCTX_INPROC_SERVER)?;
+        rule.SetName(&name)?;
+        rule.SetApplicationName(&BSTR::from(exe.to_string_lossy().as_ref()))?;
+        rule.SetDirection(NET_FW_RULE_DIR_IN)?;
+        rule.SetAction(NET_FW_ACTION_ALLOW)?;
+        rule.SetProfiles(NET_FW_PROFILE2_PRIVATE.0)?;
+        rule.SetEnabled(VARIANT_TRUE)?;
+        rules.Add(&rule)
+    })
+}
+
+#[cfg(windows)]
+pub fn remove_rule() -> Result<(), String> {
+    let name = windows::core::BSTR::from(rule_name());
+    with_policy(|policy| unsafe { policy.Rules()?.Remove(&name) })
+}
+
+#[cfg(not(windows))]
+pub fn add_rule(_exe: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn remove_rule() -> Result<(), String> {
+    Ok(())
+}
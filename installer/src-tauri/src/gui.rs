@@ -0,0 +1,688 @@
+//! The installer window: the Tauri commands behind the setup UI and the runtime that
+//! hosts it. Left out of headless builds (`--no-default-features`), which only run the
+//! silent install, update and uninstall paths.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tauri::{Emitter, Manager};
+
+use crate::{
+    arch, arp, backup, backup_before_major_update, branding, cache_installer, cleanup, cloud_sync, components,
+    controlled_folders, crash, data_dir, debug_log, diagnostics, defender, event_log, extensions, extract,
+    extract_payload, file_assoc, firewall, install_components, install_paths, install_record, license, locale, log_dir,
+    manifest, migrate, network_share, payload, payload_hashes, pending, policy, portable, processes, progress,
+    quarantine, read_installed_version, relocate, remove_deselected_components, requirements, secure_install_dir,
+    shortcuts, spawn_app, system_info, taskbar, telemetry, uninstall, update_cache, verify_extracted, video_decode,
+    write_update_policy, ytdlp,
+};
+
+/// How long running copies of the app get to close before the user is asked to
+const CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Where to install: the default location and alternatives on other drives with their
+/// free space, the first one the payload fits on marked as recommended
+#[tauri::command]
+async fn suggest_install_paths(app_handle: tauri::AppHandle) -> Result<Vec<install_paths::Suggestion>, String> {
+    let payload = resolve_payload(&app_handle).ok();
+    tauri::async_runtime::spawn_blocking(move || {
+        let required = payload.and_then(|(path, is_7z)| payload::uncompressed_size(&path, is_7z).ok()).map(|size| size.total_bytes);
+        install_paths::suggest(required)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// The install to change, when started by "Modify" in Apps & Features
+/// (`--modify [--install-path <dir>]`); None for a normal install
+#[tauri::command]
+async fn get_maintenance_path() -> Result<Option<String>, String> {
+    let args: Vec<String> = std::env::args().collect();
+    if !args.iter().any(|a| a == "--modify") {
+        return Ok(None);
+    }
+    let path = args
+        .iter()
+        .position(|a| a == "--install-path")
+        .and_then(|i| args.get(i + 1).map(PathBuf::from))
+        .or_else(uninstall::default_install_path);
+    Ok(path.map(|p| p.to_string_lossy().into_owned()))
+}
+
+/// Product names for the UI, so renamed builds don't show "Mangyomi"
+#[tauri::command]
+async fn get_branding() -> Result<branding::Branding, String> {
+    Ok(branding::get().clone())
+}
+
+/// The OS display language, so the UI can pick its default translation
+#[tauri::command]
+async fn get_locale() -> Result<String, String> {
+    Ok(locale::user_locale())
+}
+
+/// Show a directory in Explorer
+fn open_in_explorer(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("Folder not found: {}", dir.display()));
+    }
+    // A trailing separator would escape the closing quote Command adds around paths with spaces
+    let dir = dir.components().collect::<PathBuf>();
+    // explorer.exe exits with 1 even on success, so only a failed spawn is an error
+    let opener = if cfg!(windows) { "explorer.exe" } else { "xdg-open" };
+    Command::new(opener)
+        .arg(&dir)
+        .spawn()
+        .map_err(|e| format!("Failed to open {}: {}", dir.display(), e))?;
+    Ok(())
+}
+
+/// OS, hardware and display details, for tailored warnings and diagnostics
+#[tauri::command]
+async fn get_system_info(window: tauri::Window) -> Result<system_info::SystemInfo, String> {
+    let mut info = tauri::async_runtime::spawn_blocking(system_info::collect).await.map_err(|e| e.to_string())?;
+    info.display_scale = window.scale_factor().ok();
+    Ok(info)
+}
+
+#[tauri::command]
+async fn open_install_dir(install_path: String) -> Result<(), String> {
+    open_in_explorer(Path::new(&install_path))
+}
+
+#[tauri::command]
+async fn open_logs_dir() -> Result<(), String> {
+    let dir = log_dir().ok_or("Log folder not found")?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    open_in_explorer(&dir)
+}
+
+/// Send the diagnostics bundle to the support endpoint; returns its reference ID
+#[tauri::command]
+async fn upload_logs() -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(diagnostics::upload)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Hard-link identical files across cached versions and report the space saved
+#[tauri::command]
+async fn dedupe_update_cache() -> Result<update_cache::DedupeReport, String> {
+    tauri::async_runtime::spawn_blocking(update_cache::dedupe)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+
+/// Launch the installed app, then close the installer unless `exit` is false.
+/// The installer stays open when the launch fails so the error can be shown.
+#[tauri::command]
+async fn launch_app(app_handle: tauri::AppHandle, exe_path: String, args: Option<Vec<String>>, exit: Option<bool>) -> Result<(), String> {
+    spawn_app(Path::new(&exe_path), &args.unwrap_or_default())?;
+    if exit.unwrap_or(true) {
+        app_handle.exit(0);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn install_app(app_handle: tauri::AppHandle, options: manifest::InstallOptions) -> Result<(), String> {
+    if options.send_telemetry {
+        telemetry::enable();
+    }
+    extract::reset();
+    let result = run_install(&app_handle, options).await;
+    match result {
+        Ok(()) => cleanup::schedule(),
+        Err(_) => taskbar::set_state(&app_handle, taskbar::TaskbarState::Error),
+    }
+    let code = if result.is_ok() { 0 } else { 1 };
+    event_log::finish_current(code, result.as_ref().err().map(String::as_str));
+    let _ = tauri::async_runtime::spawn_blocking(move || telemetry::finish_current(code)).await;
+    result
+}
+
+/// Suspend extraction at its next read, to free up disk I/O for a while
+#[tauri::command]
+async fn pause_install(app_handle: tauri::AppHandle) -> Result<(), String> {
+    extract::pause();
+    taskbar::set_state(&app_handle, taskbar::TaskbarState::Paused);
+    Ok(())
+}
+
+#[tauri::command]
+async fn resume_install(app_handle: tauri::AppHandle) -> Result<(), String> {
+    extract::resume();
+    taskbar::set_state(&app_handle, taskbar::TaskbarState::Normal);
+    Ok(())
+}
+
+/// Stop extraction at its next read; `install_app` then fails with the cancel message
+#[tauri::command]
+async fn cancel_install() -> Result<(), String> {
+    extract::cancel();
+    Ok(())
+}
+
+/// Options saved by the last install at `install_path`, or the defaults for a fresh one
+#[tauri::command]
+async fn get_install_options(app_handle: tauri::AppHandle, install_path: String) -> Result<manifest::InstallOptions, String> {
+    if let Some(saved) = manifest::load(&install_path).and_then(|m| m.options) {
+        let mut options = manifest::InstallOptions { install_path, ..saved };
+        policy::get().apply(&mut options);
+        return Ok(options);
+    }
+    let (payload_path, is_7z) = resolve_payload(&app_handle)?;
+    let (components, extra_shortcuts, codecs_missing) = tauri::async_runtime::spawn_blocking(move || {
+        let components = components::read_manifest(&payload_path, is_7z).components;
+        let codecs_missing = !video_decode::missing_extensions().is_empty();
+        (components, shortcuts::read_extra_shortcuts(&payload_path, is_7z), codecs_missing)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    // Without the HEVC/AV1 extensions, the bundled software decoders are what plays those videos
+    let components = components
+        .into_iter()
+        .filter(|c| c.selected_by_default || (codecs_missing && c.kind == components::ComponentKind::Codecs))
+        .map(|c| c.id)
+        .collect();
+    let extra_shortcuts = extra_shortcuts.into_iter().filter(|s| s.selected_by_default).collect();
+    let mut options = manifest::InstallOptions { install_path, components, extra_shortcuts, ..Default::default() };
+    policy::get().apply(&mut options);
+    Ok(options)
+}
+
+/// The deployment policy in effect, so the UI can lock the choices it controls
+#[tauri::command]
+async fn get_policy() -> Result<policy::Policy, String> {
+    Ok(policy::get().clone())
+}
+
+/// What is already at the chosen install location
+#[derive(serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum InstallDirCheck {
+    /// Missing or empty: safe to install
+    Empty,
+    /// A previous install of the app: installing updates it in place
+    ExistingInstall { version: Option<String> },
+    /// Holds unrelated files that would end up mixed with ours; the UI should confirm
+    NotEmpty { entries: usize },
+}
+
+/// Number of entries in `dir`, 0 when it doesn't exist yet
+fn count_entries(dir: &Path) -> Result<usize, String> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => Ok(entries.count()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(format!("Cannot read {}: {}", dir.display(), e)),
+    }
+}
+
+#[tauri::command]
+async fn check_install_dir(install_path: String) -> Result<InstallDirCheck, String> {
+    let dir = Path::new(&install_path);
+    let entries = count_entries(dir)?;
+    if entries == 0 {
+        return Ok(InstallDirCheck::Empty);
+    }
+    if manifest::manifest_path(&install_path).exists() || dir.join(&branding::get().exe_name).exists() {
+        return Ok(InstallDirCheck::ExistingInstall { version: read_installed_version(&install_path) });
+    }
+    Ok(InstallDirCheck::NotEmpty { entries })
+}
+
+/// The cloud sync folder `install_path` is under, for the UI to warn about
+#[tauri::command]
+async fn check_cloud_sync(install_path: String) -> Result<Option<cloud_sync::SyncWarning>, String> {
+    Ok(cloud_sync::check(&install_path))
+}
+
+/// Hardware decoders and GPU drivers, for the UI to warn about software video decoding
+#[tauri::command]
+async fn check_video_decode() -> Result<video_decode::DecodeReport, String> {
+    tauri::async_runtime::spawn_blocking(video_decode::check).await.map_err(|e| e.to_string())
+}
+
+/// The free codec extensions Windows is missing, for the UI to offer
+#[tauri::command]
+async fn get_missing_codecs() -> Result<Vec<video_decode::CodecExtension>, String> {
+    tauri::async_runtime::spawn_blocking(video_decode::missing_extensions).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn open_codec_store(codec: String) -> Result<(), String> {
+    video_decode::open_store(&codec)
+}
+
+/// Why this machine can't run the app, if it can't, for the UI to explain before install
+#[tauri::command]
+async fn check_requirements() -> Result<Option<requirements::Unsupported>, String> {
+    Ok(requirements::check().err())
+}
+
+/// Close copies of the app running from `install_path` before installing over it, and
+/// terminate those that don't respond when `force` is set. Returns the processes that
+/// didn't exit, for the UI to list.
+#[tauri::command]
+async fn close_running_apps(install_path: String, force: Option<bool>) -> Result<Vec<processes::RunningProcess>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let remaining = processes::close_gracefully(&install_path, CLOSE_TIMEOUT);
+        if force.unwrap_or(false) {
+            return processes::force_close(&install_path, &remaining);
+        }
+        remaining
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Move an existing install to `to`, updating its shortcuts and file associations
+#[tauri::command]
+async fn relocate_install(from: String, to: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || relocate::relocate(&from, &to))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Older data directories that could be imported during setup
+#[tauri::command]
+async fn detect_migration_sources() -> Result<Vec<migrate::MigrationSource>, String> {
+    Ok(migrate::detect_sources())
+}
+
+/// Import an older data directory or a backup file into the data directory of the install
+/// at `install_path`, emitting `migration-progress` events. With `dry_run` only the report
+/// of what would happen is returned.
+#[tauri::command]
+async fn migrate_data(app_handle: tauri::AppHandle, source_path: String, dry_run: bool, install_path: Option<String>) -> Result<migrate::MigrationReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let source = migrate::source_from_path(Path::new(&source_path))?;
+        let data_dir = data_dir::resolve(install_path.as_deref())?;
+        migrate::migrate(&source, &data_dir, dry_run, |item, percent| {
+            let item = item.to_string_lossy();
+            let params = [("item".to_string(), item.to_string())].into_iter().collect();
+            app_handle.emit("migration-progress", Payload { key: "migrating".into(), params, percent, transfer: None }).ok();
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Same check as `check_install_dir` for a data folder; an existing library counts as ours
+#[tauri::command]
+async fn check_data_dir(data_dir: String) -> Result<InstallDirCheck, String> {
+    let dir = Path::new(&data_dir);
+    let entries = count_entries(dir)?;
+    if entries == 0 {
+        return Ok(InstallDirCheck::Empty);
+    }
+    if dir.join(data_dir::DATABASE_FILE).exists() {
+        return Ok(InstallDirCheck::ExistingInstall { version: None });
+    }
+    Ok(InstallDirCheck::NotEmpty { entries })
+}
+
+/// Backups taken before major updates, newest first
+#[tauri::command]
+async fn list_backups() -> Result<Vec<backup::BackupInfo>, String> {
+    backup::list()
+}
+
+/// Roll the library and settings of the install at `install_path` back to `backup_path`
+#[tauri::command]
+async fn restore_backup(backup_path: String, install_path: Option<String>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let data_dir = data_dir::resolve(install_path.as_deref())?;
+        backup::restore(Path::new(&backup_path), &data_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Optional components offered by the bundled payload
+#[tauri::command]
+async fn get_components(app_handle: tauri::AppHandle) -> Result<Vec<components::Component>, String> {
+    let (payload_path, is_7z) = resolve_payload(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || components::read_manifest(&payload_path, is_7z).components)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Extra shortcuts into sections of the app that the payload offers
+#[tauri::command]
+async fn get_extra_shortcuts(app_handle: tauri::AppHandle) -> Result<Vec<shortcuts::ExtraShortcut>, String> {
+    let (payload_path, is_7z) = resolve_payload(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || shortcuts::read_extra_shortcuts(&payload_path, is_7z))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn ytdlp_pin(app_handle: &tauri::AppHandle) -> Result<Option<ytdlp::YtDlpPin>, String> {
+    let (payload_path, is_7z) = resolve_payload(app_handle)?;
+    Ok(ytdlp::read_pin(&payload_path, is_7z))
+}
+
+/// Installed and pinned yt-dlp versions, and whether the executable is intact
+#[tauri::command]
+async fn verify_ytdlp(app_handle: tauri::AppHandle, install_path: String) -> Result<ytdlp::YtDlpStatus, String> {
+    let pin = ytdlp_pin(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || ytdlp::status(&install_path, pin.as_ref()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// (Re)install the yt-dlp version pinned by this release
+#[tauri::command]
+async fn install_ytdlp(app_handle: tauri::AppHandle, install_path: String) -> Result<ytdlp::YtDlpStatus, String> {
+    let pin = ytdlp_pin(&app_handle)?.ok_or("This release does not pin a yt-dlp version")?;
+    tauri::async_runtime::spawn_blocking(move || ytdlp::install(&install_path, &pin))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Move yt-dlp to the version pinned by this release, if it isn't there already
+#[tauri::command]
+async fn update_ytdlp(app_handle: tauri::AppHandle, install_path: String) -> Result<ytdlp::YtDlpStatus, String> {
+    let pin = ytdlp_pin(&app_handle)?.ok_or("This release does not pin a yt-dlp version")?;
+    tauri::async_runtime::spawn_blocking(move || ytdlp::update(&install_path, &pin))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Locate the bundled payload, preferring app.7z over app.zip. Returns the path and whether it is 7z.
+fn resolve_payload(app_handle: &tauri::AppHandle) -> Result<(PathBuf, bool), String> {
+    // The payload for this machine's architecture, else the generic one
+    let resources = app_handle
+        .path()
+        .resolve("resources", tauri::path::BaseDirectory::Resource)
+        .map_err(|e| e.to_string())?;
+    arch::payload_names()
+        .into_iter()
+        .map(|(name, is_7z)| (resources.join(name), is_7z))
+        // Development builds carry a placeholder app.7z of a few bytes
+        .find(|(path, _)| std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() > 1000))
+        .ok_or_else(|| "Installer payload not found (app.7z or app.zip)".to_string())
+}
+
+/// The license agreement bundled with the payload, if this build has one
+#[tauri::command]
+async fn get_license(app_handle: tauri::AppHandle) -> Result<Option<license::License>, String> {
+    let (payload_path, is_7z) = resolve_payload(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || license::read(&payload_path, is_7z))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Record that the user accepted the license `version`; written to the manifest by the install
+#[tauri::command]
+async fn accept_license(version: String) -> Result<(), String> {
+    license::accept(&version, "gui");
+    Ok(())
+}
+
+/// Uncompressed size and file count of the payload, read from the archive headers
+#[tauri::command]
+async fn estimate_install_size(app_handle: tauri::AppHandle) -> Result<payload::PayloadSize, String> {
+    let (payload_path, is_7z) = resolve_payload(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || payload::uncompressed_size(&payload_path, is_7z))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The version the bundled payload will install, if it records one
+#[tauri::command]
+async fn get_payload_version(app_handle: tauri::AppHandle) -> Result<Option<String>, String> {
+    let (payload_path, is_7z) = resolve_payload(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || payload::read_version(&payload_path, is_7z))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+
+async fn run_install(app_handle: &tauri::AppHandle, mut options: manifest::InstallOptions) -> Result<(), String> {
+    policy::get().apply(&mut options);
+    let install_path = options.install_path.clone();
+    if install_path.trim().is_empty() {
+        return Err("No install location given".to_string());
+    }
+    let (resource_path, is_7z) = resolve_payload(app_handle)?;
+    let license = license::read(&resource_path, is_7z);
+    let acceptance = license.as_ref().and_then(license::accepted);
+    if license.is_some() && acceptance.is_none() {
+        return Err("The license agreement has not been accepted".to_string());
+    }
+
+    debug_log(&format!("Installing from: {:?} to {}", resource_path, install_path));
+    let payload_version = payload::read_version(&resource_path, is_7z);
+    debug_log(&format!("Payload version: {}", payload_version.as_deref().unwrap_or("unknown")));
+    let previous_version = read_installed_version(&install_path);
+    let operation = if previous_version.is_some() { event_log::Operation::Update } else { event_log::Operation::Install };
+    event_log::started(operation, payload_version.as_deref());
+    telemetry::started(payload_version.as_deref());
+    backup_before_major_update(&install_path, previous_version.as_deref(), payload_version.as_deref());
+
+    taskbar::set_state(app_handle, taskbar::TaskbarState::Normal);
+
+    requirements::check().map_err(|reason| reason.message())?;
+
+    // 1. Create directory
+    network_share::probe(&install_path)?;
+    controlled_folders::probe(&install_path)?;
+    std::fs::create_dir_all(&install_path).map_err(|e| e.to_string())?;
+    secure_install_dir(&install_path)?;
+
+    // 2. Extract
+    emit_progress(app_handle, "extracting", &[], 10);
+    
+    let path_clone = install_path.clone();
+    let res_clone = resource_path.clone();
+    let selection = options.components.clone();
+    let handle = app_handle.clone();
+    
+    // Extraction is heavy, run in blocking thread
+    let files = tauri::async_runtime::spawn_blocking(move || {
+        let mut throughput = progress::Throughput::default();
+        extract_payload(&res_clone, is_7z, &path_clone, &selection, |file, done, total| {
+            // Throttled along with the rate, so the file shown is a recent one, not every one
+            if let Some(transfer) = throughput.sample(done, total) {
+                let percent = 10 + progress::percent(done, total) * 60 / 100;
+                let file = payload::normalize_entry_name(file);
+                emit_transfer(&handle, "extracting_file", &[("file", &file)], percent, Some(transfer));
+            }
+        })
+    }).await.map_err(|e| e.to_string())??;
+
+    emit_progress(app_handle, "verifying", &[], 70);
+    let (res_clone, path_clone, files) = (resource_path.clone(), install_path.clone(), files);
+    let files = tauri::async_runtime::spawn_blocking(move || {
+        // Re-extracting a file antivirus took away would only have it taken again
+        let affected = quarantine::affected(&path_clone, &files, None);
+        if !affected.is_empty() {
+            return Err(quarantine::message(&path_clone, &affected));
+        }
+        verify_extracted(&res_clone, is_7z, &path_clone, &files)?;
+        let affected = quarantine::affected(&path_clone, &files, payload::read_manifest(&res_clone, is_7z).as_ref());
+        if !affected.is_empty() {
+            return Err(quarantine::message(&path_clone, &affected));
+        }
+        Ok(files)
+    }).await.map_err(|e| e.to_string())??;
+    if pending::deferred_count() > 0 {
+        pending::schedule(&install_path)?;
+    }
+
+    // Files of components deselected since the last install (from "Modify") go
+    remove_deselected_components(&install_path, &resource_path, is_7z, &options.components);
+
+    // Downloaded components are fetched now; a failed one doesn't undo the install
+    let component_files = if options.components.is_empty() {
+        Vec::new()
+    } else {
+        let handle = app_handle.clone();
+        let (payload, path, ids) = (resource_path.clone(), install_path.clone(), options.components.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            // One estimate per component: each download starts from zero
+            let mut current = (String::new(), progress::Throughput::default());
+            install_components(&path, &payload, is_7z, &ids, |name, received, total| {
+                if current.0 != name {
+                    current = (name.to_string(), progress::Throughput::default());
+                }
+                if let Some(transfer) = current.1.sample(received, total) {
+                    let percent = 70 + progress::percent(received, total) / 10;
+                    emit_transfer(&handle, "downloading_component", &[("name", name)], percent, Some(transfer));
+                }
+            })
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    };
+
+    emit_progress(app_handle, "creating_shortcuts", &[], 80);
+
+    // 3. Shortcuts (Desktop & Start Menu), plus the uninstaller they can point at.
+    // A portable install leaves no trace outside its own directory, and removes itself
+    // with the uninstaller copy and --uninstall-portable.
+    if let Err(e) = uninstall::install_uninstaller(&install_path) {
+        debug_log(&e);
+    }
+    let mut shortcuts = Vec::new();
+    if options.portable {
+        if let Err(e) = portable::mark(&install_path) {
+            debug_log(&e);
+        }
+    } else {
+        shortcuts = shortcuts::create_shortcuts(&install_path, &options).map_err(|e| format!("Shortcut creation failed: {}", e))?;
+        if options.register_file_associations {
+            if let Err(e) = file_assoc::register(&install_path, &options) {
+                debug_log(&format!("File association failed: {}", e));
+            }
+        }
+        if options.firewall_rule {
+            if let Err(e) = firewall::request_rule(&install_path) {
+                debug_log(&format!("Firewall rule failed: {}", e));
+            }
+        }
+        if options.defender_exclusion {
+            if let Err(e) = defender::request_exclusion(&install_path, None) {
+                debug_log(&format!("Defender exclusion failed: {}", e));
+            }
+        }
+    }
+    // 4. Custom data folder, read by the app before it touches its default userData
+    let mut files = files;
+    if let Some(dir) = options.data_dir.as_deref().filter(|d| !d.trim().is_empty()) {
+        data_dir::configure(&install_path, Path::new(dir))?;
+        files.push(data_dir::DATA_LOCATION_FILE.to_string());
+    }
+    write_update_policy(&install_path, options.update_url.as_deref(), &mut files);
+
+    // A first install starts with the bundled extensions, plus any from a folder the user chose
+    if previous_version.is_none() {
+        let mut sources = vec![resource_path.with_file_name(extensions::EXTENSIONS_DIR)];
+        sources.extend(options.extensions_dir.as_deref().filter(|d| !d.trim().is_empty()).map(PathBuf::from));
+        let result = data_dir::resolve(Some(&install_path)).and_then(|data| extensions::preinstall(&sources, &data));
+        if let Err(e) = result {
+            debug_log(&format!("Preinstalling extensions failed: {}", e));
+        }
+    }
+
+    let version = read_installed_version(&install_path);
+    let portable = options.portable;
+    let hashes = payload_hashes(&resource_path, is_7z, &files);
+    let recorded_options = options.clone();
+    manifest::update(&install_path, |m| {
+        m.hashes = hashes;
+        m.version = version.clone();
+        m.app_user_model_id = Some(branding::get().app_user_model_id.clone());
+        m.shortcuts = shortcuts;
+        m.files = files;
+        m.component_files = component_files;
+        m.options = Some(options);
+        m.licenses.extend(acceptance);
+    })?;
+    
+    // 5. Cache installer for differential updates
+    emit_progress(app_handle, "setting_up_updates", &[], 90);
+    if !portable {
+        if let Err(e) = cache_for_differential_updates(app_handle, &install_path, previous_version.as_deref()) {
+            debug_log(&format!("Caching failed: {}", e)); // Don't fail install if caching fails
+        }
+        if let Err(e) = arp::register(&install_path, version.as_deref()) {
+            debug_log(&format!("Apps & Features entry failed: {}", e));
+        }
+        if let Err(e) = install_record::save(&install_path, version.as_deref(), Some(&recorded_options)) {
+            debug_log(&format!("Recording the install failed: {}", e));
+        }
+    }
+    
+    let version = version.unwrap_or_default();
+    let done = if pending::deferred_count() > 0 { "done_pending_restart" } else { "done" };
+    emit_progress(app_handle, done, &[("version", &version)], 100);
+    
+    Ok(())
+}
+
+
+/// Cache the installer and blockmap for differential updates
+/// This allows the app to download only changed blocks on future updates
+fn cache_for_differential_updates(app_handle: &tauri::AppHandle, install_path: &str, previous_version: Option<&str>) -> Result<(), String> {
+    debug_log("cache_for_differential_updates: Starting (GUI install)");
+
+    let blockmap = app_handle.path().resolve("resources/installer.blockmap", tauri::path::BaseDirectory::Resource).ok();
+    cache_installer(install_path, blockmap.as_deref(), previous_version)?;
+
+    debug_log("cache_for_differential_updates: Finished");
+    Ok(())
+}
+
+
+/// Progress event payload. `key` names a message in the frontend's translations and
+/// `params` fills its placeholders, so no user-facing text is baked in here. Byte-counted
+/// stages add bytes processed, throughput and time left.
+#[derive(Clone, serde::Serialize)]
+struct Payload {
+    key: String,
+    params: std::collections::HashMap<String, String>,
+    percent: u32,
+    #[serde(flatten)]
+    transfer: Option<progress::Transfer>,
+}
+
+/// Emit an `install-progress` event and mirror it on the taskbar button
+fn emit_progress(app_handle: &tauri::AppHandle, key: &str, params: &[(&str, &str)], percent: u32) {
+    emit_transfer(app_handle, key, params, percent, None);
+}
+
+fn emit_transfer(app_handle: &tauri::AppHandle, key: &str, params: &[(&str, &str)], percent: u32, transfer: Option<progress::Transfer>) {
+    let params = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    app_handle.emit("install-progress", Payload { key: key.into(), params, percent, transfer }).ok();
+    if percent >= 100 {
+        taskbar::set_state(app_handle, taskbar::TaskbarState::Off);
+    } else {
+        taskbar::set_progress(app_handle, percent);
+    }
+}
+
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            crash::set_app_handle(app.handle().clone());
+            Ok(())
+        })
+        // Closing the window (or the session ending) mustn't leave extraction writing behind it
+        .on_window_event(|_window, event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                extract::cancel();
+            }
+        })
+        .invoke_handler(tauri::generate_handler![install_app, pause_install, resume_install, cancel_install, suggest_install_paths, get_maintenance_path, check_install_dir, check_cloud_sync,
+            check_requirements,
+            check_video_decode,
+            get_missing_codecs,
+            open_codec_store, close_running_apps, check_data_dir, get_install_options, get_policy, relocate_install, detect_migration_sources, migrate_data, list_backups, restore_backup, get_components, get_extra_shortcuts, verify_ytdlp, install_ytdlp, update_ytdlp, get_branding, get_locale, get_system_info, launch_app, dedupe_update_cache, estimate_install_size, get_payload_version, get_license, accept_license, open_install_dir, open_logs_dir, upload_logs])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,59 @@
+//! File hashing shared by the update cache and payload verification.
+
+use sha2::{Digest, Sha256, Sha512};
+use std::io::Read;
+use std::path::Path;
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Standard padded base64, as certificate pins and release manifests write hashes
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Lowercase hex SHA-256 of everything readable from `reader`
+pub fn sha256_reader(reader: &mut impl Read) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Lowercase hex SHA-256 of an in-memory buffer
+pub fn sha256_bytes(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// Lowercase hex SHA-256 of a file on disk
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    sha256_reader(&mut file).map_err(|e| format!("Failed to hash {:?}: {}", path, e))
+}
+
+/// Base64 SHA-512 of a file on disk, as release manifests record it
+pub fn sha512_file_base64(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut hasher = Sha512::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Failed to hash {:?}: {}", path, e))?;
+    Ok(base64(&hasher.finalize()))
+}
@@ -0,0 +1,62 @@
+//! Build-time generation of the payload's hash manifest (`hashes.json`), the per-file
+//! SHA-256 and size list that extraction uses to skip unchanged files, that verification
+//! and `doctor --fix` check installed files against, and that install manifests copy for
+//! later repairs and orphan cleanup.
+//!
+//! `mangyomi-hashes <app dir> [--out <path>] [--version <version>]` hashes the unpacked
+//! app before it is packed; by default the manifest is written into the directory so it
+//! ends up at the archive root. The version defaults to the directory's `version.txt`.
+
+use std::path::{Path, PathBuf};
+
+use crate::hash;
+use crate::payload::{self, PayloadFile, PayloadManifest};
+
+/// Hash every file under `dir`, keyed by its path relative to `dir` with `/` separators.
+/// An existing manifest in `dir` is left out.
+pub fn generate(dir: &Path, version: Option<String>) -> Result<PayloadManifest, String> {
+    let mut manifest = PayloadManifest { version, ..Default::default() };
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current).map_err(|e| format!("Failed to read {:?}: {}", current, e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| format!("Failed to read {:?}: {}", current, e))?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(dir).map_err(|e| e.to_string())?;
+            let name = payload::normalize_entry_name(&relative.to_string_lossy());
+            if name == payload::MANIFEST_FILE {
+                continue;
+            }
+            let size = std::fs::metadata(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?.len();
+            manifest.files.insert(name, PayloadFile { sha256: hash::sha256_file(&path)?, size });
+        }
+    }
+    Ok(manifest)
+}
+
+/// Run the tool with the arguments after the program name, returning where the manifest
+/// was written
+pub fn run(args: &[String]) -> Result<PathBuf, String> {
+    let usage = "Usage: mangyomi-hashes <app dir> [--out <path>] [--version <version>]";
+    let input = Path::new(args.first().filter(|a| !a.starts_with("--")).ok_or(usage)?);
+    if !input.is_dir() {
+        return Err(format!("Not a directory: {}", input.display()));
+    }
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+    let version = option("--version").cloned().or_else(|| {
+        let version = std::fs::read_to_string(input.join(payload::VERSION_FILE)).ok()?;
+        Some(version.trim().to_string()).filter(|v| !v.is_empty())
+    });
+    let out = option("--out").map(PathBuf::from).unwrap_or_else(|| input.join(payload::MANIFEST_FILE));
+
+    let manifest = generate(input, version)?;
+    // Sorted keys keep the file stable between builds of the same app
+    let sorted: std::collections::BTreeMap<_, _> = manifest.files.iter().collect();
+    let json = serde_json::json!({ "version": manifest.version, "files": sorted });
+    let json = serde_json::to_vec_pretty(&json).map_err(|e| e.to_string())?;
+    std::fs::write(&out, json).map_err(|e| format!("Failed to write {:?}: {}", out, e))?;
+    Ok(out)
+}
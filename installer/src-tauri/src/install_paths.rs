@@ -0,0 +1,106 @@
+//! Install location suggestions. The per-user default (`%LOCALAPPDATA%\Programs`) lives on
+//! the system drive, which on small SSD laptops may not have room for the app; other fixed
+//! drives are offered alongside it with their free space, and the first location the
+//! payload fits on is marked as the recommended one.
+
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+use crate::branding;
+use crate::{default_install_path, policy};
+
+/// Headroom on top of the payload size: the update cache and the app's first writes
+const MARGIN_BYTES: u64 = 200 * 1024 * 1024;
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub path: String,
+    /// Space available to the current user on the path's volume
+    pub free_bytes: Option<u64>,
+    /// The usual per-user location, as opposed to an alternative drive
+    pub is_default: bool,
+    pub recommended: bool,
+}
+
+/// The default location followed by one on each other fixed drive, roomiest first.
+/// `required_bytes` (the payload's uncompressed size) picks the recommended one; without
+/// it the default is recommended.
+pub fn suggest(required_bytes: Option<u64>) -> Vec<Suggestion> {
+    let default = default_install_path();
+    let free_bytes = free_space(Path::new(&default));
+    let mut suggestions = vec![Suggestion { path: default.clone(), free_bytes, is_default: true, recommended: false }];
+    // A path set by policy is the only choice there is
+    if policy::get().install_path.is_none() {
+        let mut others: Vec<Suggestion> = other_drive_paths(&default)
+            .into_iter()
+            .map(|path| Suggestion {
+                free_bytes: free_space(&path),
+                path: path.to_string_lossy().into_owned(),
+                is_default: false,
+                recommended: false,
+            })
+            .collect();
+        others.sort_by_key(|s| std::cmp::Reverse(s.free_bytes));
+        suggestions.extend(others);
+    }
+
+    let fits = |s: &Suggestion| match (required_bytes, s.free_bytes) {
+        (Some(required), Some(free)) => free >= required + MARGIN_BYTES,
+        _ => s.is_default,
+    };
+    let recommended = suggestions.iter().position(fits).unwrap_or(0);
+    suggestions[recommended].recommended = true;
+    suggestions
+}
+
+/// `<drive>:\Programs\<install dir>` on every fixed drive except the one holding `default`
+#[cfg(windows)]
+fn other_drive_paths(default: &str) -> Vec<PathBuf> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{GetDriveTypeW, GetLogicalDrives};
+
+    const DRIVE_FIXED: u32 = 3;
+
+    let default_drive = default.chars().next().map(|c| c.to_ascii_uppercase());
+    let mask = unsafe { GetLogicalDrives() };
+    (0..26u8)
+        .filter(|i| mask & (1 << i) != 0)
+        .map(|i| char::from(b'A' + i))
+        .filter(|&letter| Some(letter) != default_drive)
+        .filter(|letter| unsafe { GetDriveTypeW(&HSTRING::from(format!("{}:\\", letter))) } == DRIVE_FIXED)
+        .map(|letter| PathBuf::from(format!("{}:\\Programs", letter)).join(&branding::get().install_dir_name))
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn other_drive_paths(_default: &str) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// The nearest existing ancestor of `path`, since the install directory itself usually
+/// doesn't exist yet
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let dir = existing_ancestor(path)?;
+    let mut available = 0u64;
+    unsafe { GetDiskFreeSpaceExW(&HSTRING::from(dir), Some(&mut available), None, None) }.ok()?;
+    Some(available)
+}
+
+#[cfg(not(windows))]
+pub fn free_space(path: &Path) -> Option<u64> {
+    // Filesystem 1024-blocks Used Available Capacity Mounted on
+    let dir = existing_ancestor(path)?;
+    let output = std::process::Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let kib: u64 = stdout.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,70 @@
+//! Where the app is installed and how, kept in `HKCU\Software\<app>` next to the install
+//! manifest. Updates started with nothing but `--silent` read it to find the install and
+//! its options, and it survives a damaged or deleted manifest. Rewritten after every
+//! install, update and relocation; removed on uninstall. Other platforms rely on the
+//! manifest alone.
+
+use crate::manifest::InstallOptions;
+
+/// What the installer reads back. `Version` and `Channel` (the update channel the
+/// deployment policy locked the app to, if any) are also written, for the app and for
+/// administrators' inventory scripts.
+#[derive(Default)]
+pub struct InstallRecord {
+    pub install_path: Option<String>,
+    pub options: Option<InstallOptions>,
+}
+
+#[cfg(windows)]
+fn key() -> String {
+    format!("Software\\{}", crate::branding::get().app_name)
+}
+
+#[cfg(windows)]
+pub fn save(install_path: &str, version: Option<&str>, options: Option<&InstallOptions>) -> Result<(), String> {
+    use crate::{policy, registry};
+
+    let key = key();
+    registry::set_string(&key, Some("InstallLocation"), install_path)?;
+    registry::set_string(&key, Some("Version"), version.unwrap_or_default())?;
+    if let Some(channel) = &policy::get().update_channel {
+        registry::set_string(&key, Some("Channel"), channel)?;
+    }
+    if let Some(options) = options {
+        let json = serde_json::to_string(options).map_err(|e| e.to_string())?;
+        registry::set_string(&key, Some("Options"), &json)?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn load() -> InstallRecord {
+    use crate::registry;
+
+    let key = key();
+    let get = |name: &str| registry::get_string(&key, name).filter(|v| !v.is_empty());
+    InstallRecord {
+        install_path: get("InstallLocation"),
+        options: get("Options").and_then(|json| serde_json::from_str(&json).ok()),
+    }
+}
+
+#[cfg(windows)]
+pub fn remove() -> Result<(), String> {
+    crate::registry::delete_tree(&key())
+}
+
+#[cfg(not(windows))]
+pub fn save(_install_path: &str, _version: Option<&str>, _options: Option<&InstallOptions>) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn load() -> InstallRecord {
+    InstallRecord::default()
+}
+
+#[cfg(not(windows))]
+pub fn remove() -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,206 @@
+//! Shell folder locations resolved through the Known Folders API.
+//!
+//! Deriving them from `USERPROFILE`/`APPDATA` breaks with OneDrive Desktop redirection
+//! and roaming profiles; the shell always knows where these folders really are.
+//! When the API call fails (stripped-down images, services running without a loaded
+//! profile) the folders are found, in order, from:
+//!
+//! 1. their environment variable (`APPDATA`, `LOCALAPPDATA`, ...)
+//! 2. the standard layout under the user profile (`USERPROFILE`, or `HOMEDRIVE` + `HOMEPATH`)
+//! 3. for the app data folder only, the temp directory, so logging and the update cache
+//!    keep working for this run at least
+//!
+//! Every fallback taken is recorded; `fallbacks` lists them for the log, the silent
+//! run's warnings and the system info the installer window shows.
+//!
+//! Elsewhere the same roles map onto the XDG base directories: the per-user install root
+//! is `~/.local/share`, Start Menu entries go to `~/.local/share/applications` and the
+//! app's settings to `~/.config`. On macOS the install roots are `~/Applications` and
+//! `/Applications`, settings live in `~/Library/Application Support`, and there is no
+//! Start Menu.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub enum KnownFolder {
+    Desktop,
+    /// Current user's Start Menu\Programs
+    Programs,
+    /// All users' Start Menu\Programs
+    CommonPrograms,
+    /// %ProgramData%, machine-wide settings such as the deployment policy
+    ProgramData,
+    ProgramFiles,
+    ProgramFilesX86,
+    RoamingAppData,
+    /// %LOCALAPPDATA%\Programs, the per-user install root
+    UserProgramFiles,
+}
+
+#[cfg(windows)]
+fn query(folder: KnownFolder) -> Option<PathBuf> {
+    use windows::Win32::System::Com::CoTaskMemFree;
+    use windows::Win32::UI::Shell::{
+        SHGetKnownFolderPath, FOLDERID_CommonPrograms, FOLDERID_Desktop, FOLDERID_ProgramData, FOLDERID_ProgramFiles,
+        FOLDERID_ProgramFilesX86, FOLDERID_Programs, FOLDERID_RoamingAppData, FOLDERID_UserProgramFiles,
+        KF_FLAG_DEFAULT,
+    };
+
+    let id = match folder {
+        KnownFolder::Desktop => FOLDERID_Desktop,
+        KnownFolder::Programs => FOLDERID_Programs,
+        KnownFolder::CommonPrograms => FOLDERID_CommonPrograms,
+        KnownFolder::ProgramData => FOLDERID_ProgramData,
+        KnownFolder::ProgramFiles => FOLDERID_ProgramFiles,
+        KnownFolder::ProgramFilesX86 => FOLDERID_ProgramFilesX86,
+        KnownFolder::RoamingAppData => FOLDERID_RoamingAppData,
+        KnownFolder::UserProgramFiles => FOLDERID_UserProgramFiles,
+    };
+
+    unsafe {
+        let raw = SHGetKnownFolderPath(&id, KF_FLAG_DEFAULT, None).ok()?;
+        let path = raw.to_string().ok();
+        // The shell allocates the string; we own it even when conversion fails
+        CoTaskMemFree(Some(raw.0 as *const _));
+        path.map(PathBuf::from)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn query(folder: KnownFolder) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    match folder {
+        KnownFolder::Desktop => Some(home.join("Desktop")),
+        KnownFolder::Programs | KnownFolder::CommonPrograms => None,
+        KnownFolder::ProgramData => Some(PathBuf::from("/Library/Application Support")),
+        KnownFolder::ProgramFiles => Some(PathBuf::from("/Applications")),
+        KnownFolder::ProgramFilesX86 => None,
+        KnownFolder::RoamingAppData => Some(home.join("Library/Application Support")),
+        KnownFolder::UserProgramFiles => Some(home.join("Applications")),
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn query(folder: KnownFolder) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    let xdg = |name: &str, default: &str| {
+        std::env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .unwrap_or_else(|| home.join(default))
+    };
+    match folder {
+        KnownFolder::Desktop => {
+            let output = std::process::Command::new("xdg-user-dir").arg("DESKTOP").output().ok();
+            let dir = output.map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string()).filter(|d| !d.is_empty());
+            Some(dir.map(PathBuf::from).unwrap_or_else(|| home.join("Desktop")))
+        }
+        KnownFolder::Programs => Some(xdg("XDG_DATA_HOME", ".local/share").join("applications")),
+        KnownFolder::CommonPrograms => Some(PathBuf::from("/usr/local/share/applications")),
+        KnownFolder::ProgramData => Some(PathBuf::from("/etc")),
+        KnownFolder::ProgramFiles => Some(PathBuf::from("/opt")),
+        KnownFolder::ProgramFilesX86 => None,
+        KnownFolder::RoamingAppData => Some(xdg("XDG_CONFIG_HOME", ".config")),
+        KnownFolder::UserProgramFiles => Some(xdg("XDG_DATA_HOME", ".local/share")),
+    }
+}
+
+/// Where a folder came from when the shell couldn't say
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+    Environment,
+    Profile,
+    Temp,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fallback {
+    pub folder: KnownFolder,
+    pub source: Source,
+    pub path: PathBuf,
+}
+
+static FALLBACKS: Mutex<Vec<Fallback>> = Mutex::new(Vec::new());
+
+/// The old environment-variable derivation, used when the shell can't answer
+#[cfg(windows)]
+fn from_env(folder: KnownFolder) -> Option<PathBuf> {
+    let env = |name: &str| std::env::var(name).ok().map(PathBuf::from);
+    match folder {
+        KnownFolder::Desktop => env("USERPROFILE").map(|p| p.join("Desktop")),
+        KnownFolder::Programs => env("APPDATA").map(|p| p.join("Microsoft\\Windows\\Start Menu\\Programs")),
+        KnownFolder::CommonPrograms => env("ProgramData").map(|p| p.join("Microsoft\\Windows\\Start Menu\\Programs")),
+        KnownFolder::ProgramData => env("ProgramData"),
+        KnownFolder::ProgramFiles => env("ProgramFiles"),
+        KnownFolder::ProgramFilesX86 => env("ProgramFiles(x86)"),
+        KnownFolder::RoamingAppData => env("APPDATA"),
+        KnownFolder::UserProgramFiles => env("LOCALAPPDATA").map(|p| p.join("Programs")),
+    }
+}
+
+#[cfg(not(windows))]
+fn from_env(_folder: KnownFolder) -> Option<PathBuf> {
+    None
+}
+
+/// The folder's default place in the user profile, for when its variable is missing too
+#[cfg(windows)]
+fn from_profile(folder: KnownFolder) -> Option<PathBuf> {
+    let profile = std::env::var_os("USERPROFILE").map(PathBuf::from).or_else(|| {
+        let mut home = std::env::var_os("HOMEDRIVE")?;
+        home.push(std::env::var_os("HOMEPATH")?);
+        Some(PathBuf::from(home))
+    })?;
+    match folder {
+        KnownFolder::Desktop => Some(profile.join("Desktop")),
+        KnownFolder::Programs => Some(profile.join("AppData\\Roaming\\Microsoft\\Windows\\Start Menu\\Programs")),
+        KnownFolder::RoamingAppData => Some(profile.join("AppData\\Roaming")),
+        KnownFolder::UserProgramFiles => Some(profile.join("AppData\\Local\\Programs")),
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+fn from_profile(_folder: KnownFolder) -> Option<PathBuf> {
+    None
+}
+
+fn from_temp(folder: KnownFolder) -> Option<PathBuf> {
+    (folder == KnownFolder::RoamingAppData).then(std::env::temp_dir)
+}
+
+pub fn get(folder: KnownFolder) -> Option<PathBuf> {
+    // A relative answer would land wherever the installer happens to run from
+    if let Some(path) = query(folder).filter(|p| p.is_absolute()) {
+        return Some(path);
+    }
+    let (path, source) = [
+        (from_env as fn(KnownFolder) -> Option<PathBuf>, Source::Environment),
+        (from_profile, Source::Profile),
+        (from_temp, Source::Temp),
+    ]
+    .into_iter()
+    .find_map(|(resolve, source)| Some((resolve(folder).filter(|p| p.is_absolute())?, source)))?;
+    record(Fallback { folder, source, path: path.clone() });
+    Some(path)
+}
+
+fn record(fallback: Fallback) {
+    let Ok(mut fallbacks) = FALLBACKS.lock() else { return };
+    if fallbacks.iter().any(|f| f.folder == fallback.folder) {
+        return;
+    }
+    let message = format!("{:?} folder not available from the shell; using {:?} ({:?})", fallback.folder, fallback.path, fallback.source);
+    fallbacks.push(fallback);
+    // Logging resolves the app data folder again, which must find it already recorded
+    drop(fallbacks);
+    crate::debug_log(&message);
+}
+
+/// Every fallback taken so far, once per folder
+pub fn fallbacks() -> Vec<Fallback> {
+    FALLBACKS.lock().map(|f| f.clone()).unwrap_or_default()
+}
@@ -0,0 +1,1026 @@
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use known_folders::KnownFolder;
+
+mod acl;
+mod arch;
+mod arp;
+mod backup;
+mod block_clone;
+mod blockmap;
+mod branding;
+mod capabilities;
+mod cert_pins;
+mod cleanup;
+mod cloud_sync;
+mod components;
+mod controlled_folders;
+mod crash;
+mod data_dir;
+mod defender;
+mod delta;
+#[cfg(feature = "gui")]
+mod diagnostics;
+mod doctor;
+mod download;
+mod dry_run;
+mod durable;
+mod event_log;
+#[cfg(feature = "gui")]
+mod extensions;
+mod elevate;
+mod extract;
+mod file_assoc;
+mod firewall;
+#[cfg(feature = "gui")]
+mod gui;
+mod hash;
+mod hash_manifest;
+#[cfg(feature = "gui")]
+mod install_paths;
+mod install_record;
+mod license;
+mod known_folders;
+mod locale;
+mod manifest;
+#[cfg(feature = "gui")]
+mod migrate;
+mod motw;
+mod network_share;
+mod payload;
+mod pending;
+mod policy;
+mod portable;
+mod processes;
+mod progress;
+mod quarantine;
+#[cfg(windows)]
+mod registry;
+mod release_manifest;
+mod relocate;
+mod requirements;
+mod retry;
+mod shortcuts;
+mod signature;
+mod silent;
+mod system_info;
+#[cfg(feature = "gui")]
+mod taskbar;
+mod telemetry;
+mod toast;
+mod uninstall;
+mod update_cache;
+mod verify;
+#[cfg(feature = "gui")]
+mod video_decode;
+#[cfg(feature = "gui")]
+mod ytdlp;
+
+// Global storage for the SFX installer path (passed via --sfx-path argument)
+static SFX_PATH: Mutex<Option<String>> = Mutex::new(None);
+
+// Update-cache size limit in bytes (passed via --cache-size-limit <MB>)
+static CACHE_SIZE_LIMIT: Mutex<Option<u64>> = Mutex::new(None);
+
+// What to do with the Mark-of-the-Web on extracted executables (passed via --motw <strip|keep>)
+static MOTW_POLICY: Mutex<Option<motw::MotwPolicy>> = Mutex::new(None);
+
+/// Directory holding installer-debug.log
+fn log_dir() -> Option<PathBuf> {
+    known_folders::get(KnownFolder::RoamingAppData).map(|appdata| appdata.join(&branding::get().data_dir_name))
+}
+
+const LOG_FILE: &str = "installer-debug.log";
+
+fn log_path() -> Option<PathBuf> {
+    log_dir().map(|dir| dir.join(LOG_FILE))
+}
+
+// Write debug info to a log file for production diagnosis
+fn debug_log(message: &str) {
+    if let Some(log_dir) = log_dir() {
+        let _ = std::fs::create_dir_all(&log_dir);
+        let log_path = log_dir.join(LOG_FILE);
+        use std::io::Write;
+        if let Ok(mut file) = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+        {
+            // Use system time as simple timestamp
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let _ = writeln!(file, "[{}] {}", timestamp, message);
+        }
+    }
+    println!("{}", message);
+}
+
+/// Passed to the app to open its onboarding/import screen instead of the library. A silent
+/// install given it passes it on to the app it launches at the end.
+const FIRST_RUN_ARG: &str = "--first-run";
+
+/// Start the installed app detached from the installer, from its own directory so
+/// relative paths resolve the same as when launched from a shortcut
+fn spawn_app(exe_path: &Path, args: &[String]) -> Result<(), String> {
+    if !exe_path.is_file() {
+        return Err(format!("App not found at {}", exe_path.display()));
+    }
+    let mut command = Command::new(exe_path);
+    command
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    if let Some(dir) = exe_path.parent() {
+        command.current_dir(dir);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x00000008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+    command.spawn().map_err(|e| format!("Failed to launch {}: {}", exe_path.display(), e))?;
+    Ok(())
+}
+
+/// Per-machine installs get a DACL only administrators can write through, set before
+/// extraction so every file inherits it
+fn secure_install_dir(install_path: &str) -> Result<(), String> {
+    let path = Path::new(install_path);
+    if !acl::is_per_machine(path) {
+        return Ok(());
+    }
+    debug_log(&format!("Restricting permissions on per-machine install {}", install_path));
+    acl::restrict(path)
+}
+
+/// Hand the policy's update settings to the app, recording the file for uninstall
+fn write_update_policy(install_path: &str, recorded_url: Option<&str>, files: &mut Vec<String>) {
+    match policy::get().write_update_policy(install_path, recorded_url) {
+        Ok(true) => files.push(policy::UPDATE_POLICY_FILE.to_string()),
+        Ok(false) => {}
+        Err(e) => debug_log(&e),
+    }
+}
+
+/// Download and extract the selected components that aren't bundled, calling `progress`
+/// with the component's name and its download percentage. Returns the files written;
+/// failures are logged.
+fn install_components(install_path: &str, payload_path: &Path, is_7z: bool, ids: &[String], mut progress: impl FnMut(&str, u64, Option<u64>)) -> Vec<String> {
+    let available = components::read_manifest(payload_path, is_7z);
+    let mut files = Vec::new();
+    for component in available.resolve(ids).into_iter().filter(|c| c.url.is_some()) {
+        let id = &component.id;
+        let result = components::install(install_path, component, |received, total| progress(&component.name, received, total));
+        match result {
+            Ok(written) => files.extend(written),
+            Err(e) => debug_log(&format!("Component {} failed: {}", id, e)),
+        }
+    }
+    files
+}
+
+/// Back up the library and settings before an update that crosses a major version.
+/// A failed backup is logged but doesn't block the update.
+fn backup_before_major_update(install_path: &str, previous_version: Option<&str>, next_version: Option<&str>) {
+    let (Some(previous), Some(next)) = (previous_version, next_version) else { return };
+    if !backup::is_major_update(previous, next) {
+        return;
+    }
+    debug_log(&format!("Major update {} -> {}: backing up user data", previous, next));
+    if let Err(e) = data_dir::resolve(Some(install_path)).and_then(|dir| backup::create(&dir)) {
+        debug_log(&format!("Backup failed: {}", e));
+    }
+}
+
+/// Extract a full or delta payload into the install directory, leaving out bundled
+/// components that aren't in `selection`, and return the files the install now consists
+/// of (recorded in the install manifest for uninstall). Files of components deselected
+/// since the last install are left to `remove_deselected_components`. `progress` gets
+/// the current entry, the uncompressed bytes processed and the payload's total (full
+/// payloads only).
+fn extract_payload(payload_path: &Path, is_7z: bool, install_path: &str, selection: &[String], mut progress: impl FnMut(&str, u64, Option<u64>)) -> Result<Vec<String>, String> {
+    if let Some(delta) = delta::read_manifest(payload_path, is_7z) {
+        delta::apply(payload_path, is_7z, install_path, &delta)
+            .map_err(|e| format!("Delta update failed for {:?}: {}", payload_path, e))?;
+        let mut files = manifest::load(install_path).map(|m| m.files).unwrap_or_default();
+        delta.apply_to_file_list(&mut files);
+        apply_motw_policy(install_path, &files);
+        mark_executable(install_path, &files);
+        return Ok(files);
+    }
+
+    // Files already matching the payload's hash manifest are left untouched
+    let manifest = payload::read_manifest(payload_path, is_7z);
+    let available = components::read_manifest(payload_path, is_7z);
+    let selected = available.resolve(selection);
+    let include = |entry: &str| available.includes_entry(&selected, entry);
+    let total = payload::uncompressed_size(payload_path, is_7z).ok().map(|size| size.total_bytes);
+    let mut report = |name: &str, done| progress(name, done, total);
+    let stats = if is_7z {
+        extract::extract_7z(payload_path, install_path, manifest.as_ref(), Some(&include), Some(&mut report))
+            .map_err(|e| format!("7z extraction failed for {:?}: {}", payload_path, e))?
+    } else {
+        extract::extract_zip(payload_path, install_path, manifest.as_ref(), Some(&include), Some(&mut report))
+            .map_err(|e| format!("Zip extraction failed for {:?}: {}", payload_path, e))?
+    };
+    apply_motw_policy(install_path, &stats.files);
+    mark_executable(install_path, &stats.files);
+    Ok(stats.files)
+}
+
+/// Re-hash the extracted files against the payload's hash manifest. Files that don't
+/// match are extracted again once; any still wrong after that fail the install. Delta
+/// payloads only carry patches, so there is nothing to re-extract from and they're skipped.
+fn verify_extracted(payload_path: &Path, is_7z: bool, install_path: &str, files: &[String]) -> Result<(), String> {
+    if delta::read_manifest(payload_path, is_7z).is_some() {
+        return Ok(());
+    }
+    let Some(manifest) = payload::read_manifest(payload_path, is_7z) else { return Ok(()) };
+    let root = Path::new(install_path);
+    // Files left locked still hold the old version until the pending copy moves in
+    let mismatched = |files: &[String]| -> Vec<String> {
+        files
+            .iter()
+            .filter(|f| !pending::is_deferred(&root.join(f)))
+            // Links were checked against the install root when they were created
+            .filter(|f| !root.join(f).is_symlink())
+            .filter(|f| manifest.get(f).is_some_and(|expected| !extract::is_unchanged(&root.join(f), expected)))
+            .cloned()
+            .collect()
+    };
+
+    let bad = mismatched(files);
+    if bad.is_empty() {
+        return Ok(());
+    }
+    debug_log(&format!("Verification: {} files don't match, extracting them again: {:?}", bad.len(), bad));
+    let include = |entry: &str| bad.contains(&payload::normalize_entry_name(entry));
+    if is_7z {
+        extract::extract_7z(payload_path, install_path, None, Some(&include), None)?;
+    } else {
+        extract::extract_zip(payload_path, install_path, None, Some(&include), None)?;
+    }
+    apply_motw_policy(install_path, &bad);
+    mark_executable(install_path, &bad);
+    let still_bad = mismatched(&bad);
+    if !still_bad.is_empty() {
+        return Err(format!("Installed files don't match the payload: {}", still_bad.join(", ")));
+    }
+    Ok(())
+}
+
+/// Archives built on Windows don't carry Unix permissions, so the Electron binaries would
+/// come out non-executable. In a macOS bundle those are everything under `MacOS/` and `Helpers/`
+/// directories, including the helper apps'.
+#[cfg(unix)]
+fn mark_executable(install_path: &str, files: &[String]) {
+    use std::os::unix::fs::PermissionsExt;
+    let root = Path::new(install_path);
+    let is_executable = |file: &str| {
+        let path = Path::new(file);
+        if cfg!(target_os = "macos") {
+            path.parent().and_then(Path::file_name).is_some_and(|dir| dir == "MacOS" || dir == "Helpers")
+        } else {
+            [branding::get().exe_name.as_str(), "chrome_crashpad_handler", "chrome-sandbox"].contains(&file)
+        }
+    };
+    for file in files.iter().filter(|f| is_executable(f)) {
+        let path = root.join(file);
+        if path.is_file() {
+            if let Err(e) = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)) {
+                debug_log(&format!("Failed to mark {:?} executable: {}", path, e));
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn mark_executable(_install_path: &str, _files: &[String]) {}
+
+/// Strip (or keep) the zone identifier on extracted executables so SmartScreen
+/// doesn't prompt on first launch
+fn apply_motw_policy(install_path: &str, files: &[String]) {
+    // Zone identifiers are NTFS streams, which shares don't all keep
+    if network_share::is_network(Path::new(install_path)) {
+        return;
+    }
+    let policy = MOTW_POLICY.lock().ok().and_then(|p| *p).unwrap_or_default();
+    motw::apply(install_path, files, policy);
+}
+
+/// Remove the files an earlier install put in place for components no longer in `ids`.
+/// Returns the files removed.
+fn remove_deselected_components(install_path: &str, payload_path: &Path, is_7z: bool, ids: &[String]) -> Vec<String> {
+    let Some(previous) = manifest::load(install_path) else { return Vec::new() };
+    let stale = components::read_manifest(payload_path, is_7z).deselected_files(ids, &previous.all_files());
+    if !stale.is_empty() {
+        debug_log(&format!("Removing {} file(s) of deselected components", stale.len()));
+        uninstall::remove_files(Path::new(install_path), &stale);
+    }
+    stale
+}
+
+/// Expected size and hash of each of `files`, from the payload's hash manifest. Empty
+/// when the payload has none (delta payloads may not), leaving the files unverified.
+fn payload_hashes(payload_path: &Path, is_7z: bool, files: &[String]) -> std::collections::BTreeMap<String, payload::PayloadFile> {
+    let Some(manifest) = payload::read_manifest(payload_path, is_7z) else { return Default::default() };
+    files.iter().filter_map(|f| Some((f.clone(), manifest.get(f)?.clone()))).collect()
+}
+
+/// The existing install recorded by the last install or update, if it is still there
+fn recorded_install_path() -> Option<String> {
+    let path = install_record::load().install_path?;
+    let exists = manifest::manifest_path(&path).exists() || Path::new(&path).join(&branding::get().exe_name).exists();
+    if !exists {
+        debug_log(&format!("Recorded install at {} is gone", path));
+        return None;
+    }
+    debug_log(&format!("Found recorded install at {}", path));
+    Some(path)
+}
+
+/// Whether two paths name the same directory, ignoring case on Windows and trailing separators
+fn same_path(a: &str, b: &str) -> bool {
+    let normalize = |p: &str| {
+        let p = p.trim_end_matches(['\\', '/']).replace('\\', "/");
+        if cfg!(windows) { p.to_lowercase() } else { p }
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Where a fresh install goes unless the user picks something else
+fn default_install_path() -> String {
+    if let Some(path) = &policy::get().install_path {
+        return path.clone();
+    }
+    let programs = known_folders::get(KnownFolder::UserProgramFiles).unwrap_or_else(|| PathBuf::from("C:\\"));
+    programs.join(&branding::get().install_dir_name).to_string_lossy().to_string()
+}
+
+/// The payload shipped in `resources/` next to the installer, preferring app.7z over
+/// app.zip. Returns the path and whether it is 7z.
+fn bundled_payload() -> Option<(PathBuf, bool)> {
+    let resources = std::env::current_exe().ok()?.parent()?.join("resources");
+    arch::find_payload(&resources)
+}
+
+/// Read the version recorded in an install's version.txt (created during build)
+fn read_installed_version(install_path: &str) -> Option<String> {
+    let version_txt_path = PathBuf::from(install_path).join(payload::VERSION_FILE);
+    std::fs::read_to_string(&version_txt_path)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Cache installer for silent/update installations (no Tauri runtime)
+fn cache_for_silent_install(install_path: &str, previous_version: Option<&str>) {
+    debug_log("cache_for_silent_install: Starting");
+
+    let blockmap = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("resources").join("installer.blockmap")));
+    if let Err(e) = cache_installer(install_path, blockmap.as_deref(), previous_version) {
+        debug_log(&format!("Caching failed: {}", e));
+    }
+
+    debug_log("cache_for_silent_install: Finished");
+}
+
+/// Store the running installer under the installed version, then trim the cache
+/// to its size limit while keeping the current and previous versions
+fn cache_installer(install_path: &str, blockmap: Option<&std::path::Path>, previous_version: Option<&str>) -> Result<(), String> {
+    let Some(version) = read_installed_version(install_path) else {
+        debug_log("version.txt not found!");
+        return Ok(());
+    };
+    debug_log(&format!("Caching installer for version: {}", version));
+
+    // The SFX module passes its own path; without it we can't cache ourselves and
+    // the Electron download caching handles the next update with a full download
+    let sfx_path = SFX_PATH.lock().ok().and_then(|p| p.clone());
+    match sfx_path {
+        Some(sfx_path) => {
+            let dir = update_cache::store(&version, &PathBuf::from(sfx_path), blockmap)?;
+            debug_log(&format!("Cached installer in: {:?}", dir));
+            update_cache::dedupe()?;
+        }
+        None => debug_log("No SFX path: Electron download caching will handle future updates"),
+    }
+
+    let limit = CACHE_SIZE_LIMIT.lock().ok().and_then(|l| *l).unwrap_or(update_cache::DEFAULT_SIZE_LIMIT);
+    let mut keep = vec![version.as_str()];
+    keep.extend(previous_version);
+    let freed = update_cache::enforce_size_limit(limit, &keep)?;
+    if freed > 0 {
+        debug_log(&format!("Update cache: freed {} bytes", freed));
+    }
+    Ok(())
+}
+
+/// The silent install or update to `path`, up to the point where only the exit code is
+/// left to report. Every failure comes back with the exit code it maps to.
+fn install_silently(path: &str, args: &[String]) -> Result<(), silent::Failure> {
+    use silent::{ExitCode, Failure};
+
+    requirements::check().map_err(|reason| Failure::new(ExitCode::UnsupportedSystem, "System requirements", reason.message()))?;
+
+    // Wait for the old app to fully close before extracting
+    // The app spawns us and then quits after 1 second, so we wait 3 seconds to be safe
+    debug_log("Waiting 3 seconds for old app to close...");
+    silent::progress("waiting", 0);
+    std::thread::sleep(std::time::Duration::from_secs(3));
+    // --kill-timeout: copies still running are asked to close, then terminated
+    if let Some(timeout) = processes::parse_kill_timeout(args) {
+        let remaining = processes::close_gracefully(path, timeout);
+        processes::force_close(path, &remaining);
+    }
+    let blocking = processes::running_in(path);
+    if !blocking.is_empty() {
+        debug_log(&format!("FAILED: App still running: {:?}", blocking));
+        silent::app_running(&blocking);
+    }
+    debug_log("Proceeding with extraction...");
+    let previous_version = read_installed_version(path);
+    silent::started(previous_version.as_deref());
+
+    // Create install directory
+    network_share::probe(path).map_err(|message| Failure::new(ExitCode::NetworkShare, "Network share", message))?;
+    controlled_folders::probe(path).map_err(|message| {
+        silent::controlled_folder_access(path);
+        Failure::new(ExitCode::ControlledFolderAccess, "Controlled Folder Access", message)
+    })?;
+    std::fs::create_dir_all(path)
+        .map_err(|e| Failure::new(ExitCode::Failed, "Create install directory", format!("Cannot create {}: {}", path, e)))?;
+    secure_install_dir(path).map_err(|e| Failure::new(ExitCode::Failed, "Secure install directory", e))?;
+
+    // Find the payload for this machine's architecture in resources (relative to current exe)
+    let current_exe =
+        std::env::current_exe().map_err(|e| Failure::new(ExitCode::PayloadMissing, "Locate installer", e.to_string()))?;
+    let exe_dir = current_exe
+        .parent()
+        .ok_or_else(|| Failure::new(ExitCode::PayloadMissing, "Locate installer", format!("No parent directory: {:?}", current_exe)))?;
+    let resources = exe_dir.join("resources");
+    let Some((payload_path, is_7z)) = arch::find_payload(&resources) else {
+        let message = format!("Payload not found in {}", resources.display());
+        return Err(Failure::new(ExitCode::PayloadMissing, "Find payload", message));
+    };
+
+    // Updates keep the acceptance given at install time; a fresh install needs its own
+    let license = license::read(&payload_path, is_7z);
+    if let Some(license) = &license {
+        if args.iter().any(|a| a == license::ACCEPT_ARG) {
+            license::accept(&license.version, "silent");
+        } else if previous_version.is_none() {
+            let message = format!("This build has a license agreement; pass {} to accept it", license::ACCEPT_ARG);
+            return Err(Failure::new(ExitCode::LicenseNotAccepted, "License", message));
+        }
+    }
+    let acceptance = license.as_ref().and_then(license::accepted);
+
+    debug_log(&format!("Extracting from: {:?}", payload_path));
+    let payload_version = payload::read_version(&payload_path, is_7z);
+    silent::installing(payload_version.as_deref());
+    debug_log(&format!(
+        "Updating {} -> {}",
+        previous_version.as_deref().unwrap_or("unknown"),
+        payload_version.as_deref().unwrap_or("unknown")
+    ));
+    let operation = if previous_version.is_some() { event_log::Operation::Update } else { event_log::Operation::Install };
+    event_log::started(operation, payload_version.as_deref());
+    telemetry::started(payload_version.as_deref());
+    backup_before_major_update(path, previous_version.as_deref(), payload_version.as_deref());
+    // Updates keep the choices made at install time; --components=<ids> replaces
+    // the component selection
+    let mut options = manifest::load(path).and_then(|m| m.options).or_else(|| {
+        // A lost manifest still leaves the options recorded for this install
+        let record = install_record::load();
+        record.options.filter(|_| record.install_path.as_deref() == Some(path))
+    });
+    if options.as_ref().is_some_and(|o| o.send_telemetry) {
+        telemetry::enable();
+    }
+    if let (Some(options), Some(url)) = (options.as_mut(), &policy::get().update_url) {
+        options.update_url = Some(url.clone());
+    }
+    // Updates started by the app bring the signed manifest they were downloaded against;
+    // re-runs, winget upgrades and admin deployments don't, and aren't held to one.
+    // A folder source is the organization's own copy, which isn't re-signed as it ages.
+    if let Some(release_manifest) = release_manifest::from_args(args) {
+        let sfx_path = SFX_PATH.lock().ok().and_then(|p| p.clone()).map(PathBuf::from);
+        let installer = sfx_path.unwrap_or_else(|| current_exe.clone());
+        let update_url = options.as_ref().and_then(|o| o.update_url.as_deref()).or(policy::get().update_url.as_deref());
+        let enforce_expiry = !update_url.is_some_and(policy::is_update_folder);
+        release_manifest::check(release_manifest, &installer, payload_version.as_deref(), enforce_expiry)
+            .map_err(|message| Failure::new(ExitCode::StaleRelease, "Release manifest", message))?;
+    }
+    // --start-menu-folder=<name> / --all-users-start-menu move the shortcuts
+    let previous_options = options.clone();
+    let start_menu_changed = options.as_mut().is_some_and(|o| shortcuts::parse_start_menu_args(args, o));
+    let requested_components = components::parse_arg(args);
+    let selection = requested_components
+        .clone()
+        .or_else(|| options.as_ref().map(|o| o.components.clone()))
+        .unwrap_or_default();
+    silent::progress("extracting", 10);
+    let mut files = extract_payload(&payload_path, is_7z, path, &selection, |_, _, _| {})
+        .map_err(|e| Failure::new(ExitCode::ExtractionFailed, "Extraction", e))?;
+    let quarantined = |files: Vec<String>| {
+        silent::quarantined(&files);
+        Failure::new(ExitCode::Quarantined, "Quarantine check", quarantine::message(path, &files))
+    };
+    let affected = quarantine::affected(path, &files, None);
+    if !affected.is_empty() {
+        return Err(quarantined(affected));
+    }
+    silent::progress("verifying", 60);
+    verify_extracted(&payload_path, is_7z, path, &files)
+        .map_err(|e| Failure::new(ExitCode::ExtractionFailed, "Verification", e))?;
+    let affected = quarantine::affected(path, &files, payload::read_manifest(&payload_path, is_7z).as_ref());
+    if !affected.is_empty() {
+        return Err(quarantined(affected));
+    }
+    if pending::deferred_count() > 0 {
+        pending::schedule(path).map_err(|e| Failure::new(ExitCode::ExtractionFailed, "Scheduling locked files", e))?;
+    }
+    debug_log("Silent installation complete!");
+    write_update_policy(path, options.as_ref().and_then(|o| o.update_url.as_deref()), &mut files);
+    let portable = options.as_ref().is_some_and(|o| o.portable);
+    if let Err(e) = uninstall::install_uninstaller(path) {
+        debug_log(&e);
+    }
+    if portable {
+        if let Err(e) = portable::mark(path) {
+            debug_log(&e);
+        }
+    }
+    let mut moved_shortcuts = None;
+    if start_menu_changed && !portable {
+        if let (Some(options), Some(previous)) = (&options, &previous_options) {
+            for lnk in manifest::load(path).map(|m| m.shortcuts).unwrap_or_default() {
+                uninstall::remove_file_if_exists(&lnk);
+            }
+            shortcuts::remove_start_menu_dir(previous);
+            match shortcuts::create_shortcuts(path, options) {
+                Ok(links) => moved_shortcuts = Some(links),
+                Err(e) => debug_log(&format!("Moving shortcuts failed: {}", e)),
+            }
+        }
+    }
+    let version = read_installed_version(path);
+
+    // Newly requested downloaded components are fetched during the update
+    silent::progress("components", 70);
+    // Logged once per percent, not for every chunk
+    let mut logged: Option<(String, u32)> = None;
+    let component_files = requested_components
+        .as_ref()
+        .map(|ids| install_components(path, &payload_path, is_7z, ids, |name, received, total| {
+            let percent = progress::percent(received, total);
+            if logged.as_ref().is_some_and(|(n, p)| n == name && *p == percent) {
+                return;
+            }
+            debug_log(&format!("Downloading {}: {}%", name, percent));
+            logged = Some((name.to_string(), percent));
+        }));
+    let removed = remove_deselected_components(path, &payload_path, is_7z, &selection);
+    if let (Some(options), Some(ids)) = (options.as_mut(), requested_components) {
+        options.components = ids;
+    }
+
+    let hashes = payload_hashes(&payload_path, is_7z, &files);
+    let recorded_options = options.clone();
+    if let Err(e) = manifest::update(path, |m| {
+        m.version = version.clone();
+        m.hashes = hashes;
+        m.files = files;
+        if let Some(shortcuts) = moved_shortcuts {
+            m.shortcuts = shortcuts;
+        }
+        m.licenses.extend(acceptance);
+        if options.is_some() {
+            m.options = options;
+        }
+        if let Some(component_files) = component_files {
+            m.component_files.extend(component_files);
+            m.component_files.sort();
+            m.component_files.dedup();
+        }
+        m.component_files.retain(|f| !removed.contains(f));
+    }) {
+        debug_log(&format!("Failed to update install manifest: {}", e));
+    }
+
+    // Cache the installer for differential updates
+    if !portable {
+        silent::progress("setting_up_updates", 90);
+        // A delta installer only applies over one version, so it's no base for later updates
+        if delta::read_manifest(&payload_path, is_7z).is_none() {
+            debug_log("Caching installer for differential updates...");
+            cache_for_silent_install(path, previous_version.as_deref());
+        }
+        if let Err(e) = arp::register(path, version.as_deref()) {
+            debug_log(&format!("Apps & Features entry failed: {}", e));
+        }
+        if let Err(e) = install_record::save(path, version.as_deref(), recorded_options.as_ref()) {
+            debug_log(&format!("Recording the install failed: {}", e));
+        }
+    }
+
+    // Let the user know the update happened instead of it finishing invisibly; only once
+    // everything is recorded, and not for a fresh install
+    let updated = version.as_deref().filter(|v| previous_version.is_some() && previous_version.as_deref() != Some(*v));
+    if let Some(new_version) = updated {
+        if let Err(e) = toast::show_update_complete(new_version) {
+            debug_log(&e);
+        }
+    }
+
+    // Launch the app after installation
+    if !policy::get().disable_auto_launch {
+        let app_exe = PathBuf::from(path).join(&branding::get().exe_name);
+        let app_args: Vec<String> = args.iter().filter(|a| *a == FIRST_RUN_ARG).cloned().collect();
+        if let Err(e) = spawn_app(&app_exe, &app_args) {
+            debug_log(&e);
+        }
+    }
+    Ok(())
+}
+
+/// Entry point of the `mangyomi-hashes` build tool
+pub fn generate_hashes() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match hash_manifest::run(&args) {
+        Ok(out) => println!("{}", out.display()),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(silent::ExitCode::Failed as i32);
+        }
+    }
+}
+
+pub fn run() {
+    crash::install_hook();
+
+    // Parse --sfx-path argument passed by SFX module
+    let args = silent::expand_nsis_switches(std::env::args().collect());
+    debug_log(&format!("Installer started with {} arguments: {:?}", args.len(), args));
+    policy::init(&args);
+    locale::init(&args);
+    arch::init(&args);
+    // Logs and the update cache live under these, so a guessed location is worth a warning
+    known_folders::get(KnownFolder::UserProgramFiles);
+    if args.iter().any(|a| a == "--silent") {
+        for fallback in known_folders::fallbacks() {
+            let message = format!("{:?} resolved from {:?}: {}", fallback.folder, fallback.source, fallback.path.display());
+            silent::warning("folder_fallback", &message);
+        }
+    }
+    
+    for i in 0..args.len() {
+        if args[i] == "--sfx-path" {
+            if let Some(path) = args.get(i + 1) {
+                if let Ok(mut sfx_path) = SFX_PATH.lock() {
+                    *sfx_path = Some(path.clone());
+                    debug_log(&format!("SFX path set to: {}", path));
+                }
+            }
+        } else if args[i] == "--cache-size-limit" {
+            if let Some(mb) = args.get(i + 1).and_then(|v| v.parse::<u64>().ok()) {
+                if let Ok(mut limit) = CACHE_SIZE_LIMIT.lock() {
+                    *limit = Some(mb * 1024 * 1024);
+                    debug_log(&format!("Update cache limit set to: {} MB", mb));
+                }
+            }
+        } else if args[i] == "--motw" {
+            if let Some(policy) = args.get(i + 1).and_then(|v| motw::MotwPolicy::parse(v)) {
+                if let Ok(mut motw_policy) = MOTW_POLICY.lock() {
+                    *motw_policy = Some(policy);
+                    debug_log(&format!("Mark-of-the-Web policy set to: {}", args[i + 1]));
+                }
+            }
+        } else if args[i] == "--event-log" {
+            event_log::enable();
+            debug_log("Event log reporting enabled");
+        } else if args[i] == "--delete-installer" {
+            cleanup::delete_installer();
+        } else if args[i] == "--telemetry" {
+            telemetry::enable();
+            debug_log("Install telemetry enabled");
+        }
+    }
+
+    // generate-blockmap <file> [--out <path>] [--block-size <bytes>]: release tooling
+    if args.get(1).is_some_and(|a| a == "generate-blockmap") {
+        match blockmap::run(&args[2..]) {
+            Ok(out) => {
+                println!("{}", out.display());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(silent::ExitCode::Failed as i32);
+            }
+        }
+    }
+
+    // doctor [--fix] [--json] [--install-path <dir>]: find and optionally repair common
+    // problems with an install. Exits 0 when nothing is left unfixed.
+    if args.get(1).is_some_and(|a| a == "doctor") {
+        let install_path = args
+            .iter()
+            .position(|a| a == "--install-path")
+            .and_then(|i| args.get(i + 1).cloned())
+            .or_else(recorded_install_path)
+            .unwrap_or_else(default_install_path);
+        let fix = args.iter().any(|a| a == "--fix");
+        let Some(report) = doctor::run(&install_path, fix) else {
+            eprintln!("No install found at {}", install_path);
+            std::process::exit(silent::ExitCode::NotInstalled as i32);
+        };
+        if args.iter().any(|a| a == "--json") {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+        } else {
+            println!("{}", doctor::summary(&report, fix));
+        }
+        let code = if report.healthy() { silent::ExitCode::Success } else { silent::ExitCode::Failed };
+        std::process::exit(code as i32);
+    }
+
+    // --elevated-task <task> [args]: one machine-wide step, run in an elevated copy of the
+    // installer (see elevate.rs)
+    if let Some(i) = args.iter().position(|a| a == "--elevated-task") {
+        let result = match (args.get(i + 1).map(String::as_str), args.get(i + 2)) {
+            (Some(firewall::ADD_TASK), Some(exe)) => firewall::add_rule(Path::new(exe)),
+            (Some(firewall::REMOVE_TASK), _) => firewall::remove_rule(),
+            (Some(defender::ADD_TASK), Some(path)) => {
+                if let Some(previous) = args.get(i + 3) {
+                    if let Err(e) = defender::remove_exclusion(Path::new(previous)) {
+                        debug_log(&e);
+                    }
+                }
+                defender::add_exclusion(Path::new(path))
+            }
+            (Some(defender::REMOVE_TASK), Some(path)) => defender::remove_exclusion(Path::new(path)),
+            (task, _) => Err(format!("Unknown elevated task: {:?}", task)),
+        };
+        if let Err(e) = result {
+            debug_log(&e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    // --cached-base <version>: print a verified cached installer to use as the delta base.
+    // Exits 1 when there is none (or it was corrupted) so the updater does a full download.
+    if let Some(i) = args.iter().position(|a| a == "--cached-base") {
+        let base = args.get(i + 1).and_then(|version| update_cache::verified_base(version));
+        match base {
+            Some(path) => {
+                println!("{}", path.display());
+                std::process::exit(0);
+            }
+            None => std::process::exit(1),
+        }
+    }
+
+    // --update-stub <installer> --version <app version> [--blockmap <file>]: adopt a newer,
+    // signed build of the installer as the cached base for the next differential update
+    if let Some(i) = args.iter().position(|a| a == "--update-stub") {
+        let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+        let result = match (args.get(i + 1), option("--version")) {
+            (Some(installer), Some(version)) => {
+                update_cache::replace_installer(version, Path::new(installer), option("--blockmap").map(Path::new))
+            }
+            _ => Err("Usage: --update-stub <installer> --version <app version> [--blockmap <file>]".to_string()),
+        };
+        match result {
+            Ok(dir) => {
+                println!("{}", dir.display());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                debug_log(&format!("Stub update failed: {}", e));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // --version [--json]: the installer's version, or with --json its capabilities. After
+    // --update-stub, which takes a --version of its own.
+    if args.iter().any(|a| a == "--version") {
+        let capabilities = capabilities::collect();
+        if args.iter().any(|a| a == "--json") {
+            println!("{}", serde_json::to_string_pretty(&capabilities).unwrap_or_default());
+        } else {
+            println!("{}", capabilities.installer_version);
+        }
+        std::process::exit(0);
+    }
+
+    // --list-payload [--json]: what the bundled archive contains, without extracting it.
+    // Exits 1 when files the hash manifest lists are missing or the wrong size.
+    if args.iter().any(|a| a == "--list-payload") {
+        let listing = bundled_payload()
+            .ok_or_else(|| "Installer payload not found (app.7z or app.zip)".to_string())
+            .and_then(|(path, is_7z)| payload::list(&path, is_7z));
+        let listing = match listing {
+            Ok(listing) => listing,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(silent::ExitCode::PayloadMissing as i32);
+            }
+        };
+        if args.iter().any(|a| a == "--json") {
+            println!("{}", serde_json::to_string_pretty(&listing).unwrap_or_default());
+        } else {
+            for entry in &listing.entries {
+                println!("{:>12}  {:<64}  {}", entry.size, entry.sha256.as_deref().unwrap_or("-"), entry.path);
+            }
+            let total: u64 = listing.entries.iter().map(|e| e.size).sum();
+            println!("{} files, {} bytes, version {}", listing.entries.len(), total, listing.version.as_deref().unwrap_or("unknown"));
+            for path in &listing.missing {
+                println!("MISSING  {}", path);
+            }
+            for path in &listing.size_mismatches {
+                println!("WRONG SIZE  {}", path);
+            }
+        }
+        std::process::exit(if listing.is_complete() { 0 } else { 1 });
+    }
+
+    // --dry-run [--install-path <dir>] [--components=<ids>]: print the planned install as
+    // JSON without changing anything
+    if args.iter().any(|a| a == "--dry-run") {
+        let install_path = args
+            .iter()
+            .position(|a| a == "--install-path")
+            .and_then(|i| args.get(i + 1).cloned())
+            .unwrap_or_else(default_install_path);
+        let report = bundled_payload()
+            .ok_or_else(|| "Installer payload not found (app.7z or app.zip)".to_string())
+            .and_then(|(path, is_7z)| dry_run::plan(&install_path, &path, is_7z, &args));
+        match report {
+            Ok(report) => {
+                println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(silent::ExitCode::Failed as i32);
+            }
+        }
+    }
+
+    // --verify (or --silent --verify-only) [--install-path <dir>]: check an install against
+    // its manifest and print a JSON report, on one line when silent. Exits 0 when intact,
+    // 1 when anything is missing or modified.
+    if args.iter().any(|a| a == "--verify" || a == "--verify-only") {
+        let install_path = args
+            .iter()
+            .position(|a| a == "--install-path")
+            .and_then(|i| args.get(i + 1).cloned())
+            .or_else(recorded_install_path)
+            .unwrap_or_else(default_install_path);
+        let Some(report) = verify::verify(&install_path) else {
+            eprintln!("No install found at {}", install_path);
+            std::process::exit(silent::ExitCode::NotInstalled as i32);
+        };
+        let json = if args.iter().any(|a| a == "--silent") {
+            serde_json::to_string(&report)
+        } else {
+            serde_json::to_string_pretty(&report)
+        };
+        println!("{}", json.unwrap_or_default());
+        let code = if report.ok { silent::ExitCode::Success } else { silent::ExitCode::Failed };
+        std::process::exit(code as i32);
+    }
+
+    // --uninstall-portable [<dir>]: remove a portable install without touching the registry
+    // or the data folder; run from the app, or by hand from the stick
+    if let Some(i) = args.iter().position(|a| a == "--uninstall-portable") {
+        let install_path = args
+            .get(i + 1)
+            .filter(|a| !a.starts_with("--"))
+            .map(PathBuf::from)
+            .or_else(uninstall::default_install_path);
+        match install_path {
+            Some(path) => portable::run(&path.to_string_lossy()),
+            None => silent::finish(silent::ExitCode::InvalidArguments, Some("No install path given")),
+        }
+    }
+
+    // --uninstall [--silent [--purge]] [--install-path <dir>]: run from the "Uninstall Mangyomi"
+    // shortcut, or by scripts and the app without any UI
+    if args.iter().any(|a| a == "--uninstall") {
+        let install_path = args
+            .iter()
+            .position(|a| a == "--install-path")
+            .and_then(|i| args.get(i + 1).map(PathBuf::from))
+            .or_else(uninstall::default_install_path);
+        let silent_mode = args.iter().any(|a| a == "--silent");
+        let code = match install_path {
+            Some(path) if silent_mode => uninstall::run_silent(&path.to_string_lossy(), args.iter().any(|a| a == "--purge")),
+            Some(path) => uninstall::run_interactive(&path.to_string_lossy()),
+            None if silent_mode => silent::finish(silent::ExitCode::InvalidArguments, Some("No install path given")),
+            None => 1,
+        };
+        std::process::exit(code);
+    }
+
+    // --finish-pending [--install-path <dir>]: move files that were locked during an
+    // update into place; run from RunOnce at the next sign-in
+    if args.iter().any(|a| a == "--finish-pending") {
+        let install_path = args
+            .iter()
+            .position(|a| a == "--install-path")
+            .and_then(|i| args.get(i + 1).map(PathBuf::from))
+            .or_else(uninstall::default_install_path);
+        let result = match install_path {
+            Some(path) => pending::finish(&path.to_string_lossy()),
+            None => Err("No install path given".to_string()),
+        };
+        if let Err(e) = &result {
+            debug_log(&format!("Finishing pending files failed: {}", e));
+        }
+        std::process::exit(if result.is_ok() { 0 } else { 1 });
+    }
+
+    // --relocate <dir> [--install-path <dir>]: move an install, by default the one the
+    // uninstaller copy runs from
+    if let Some(i) = args.iter().position(|a| a == "--relocate") {
+        let from = args
+            .iter()
+            .position(|a| a == "--install-path")
+            .and_then(|i| args.get(i + 1).map(PathBuf::from))
+            .or_else(uninstall::default_install_path);
+        let result = match (from, args.get(i + 1)) {
+            (Some(from), Some(to)) => relocate::relocate(&from.to_string_lossy(), to),
+            _ => Err("Usage: --relocate <new dir> [--install-path <current dir>]".to_string()),
+        };
+        if let Err(e) = result {
+            debug_log(&format!("Relocation failed: {}", e));
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    // Parse --silent and --install-path for silent updates
+    let mut silent_mode = false;
+    let mut install_path: Option<String> = None;
+    
+    for i in 0..args.len() {
+        if args[i] == "--silent" {
+            silent_mode = true;
+            debug_log("Silent mode enabled");
+        } else if args[i] == "--install-path" {
+            if let Some(path) = args.get(i + 1) {
+                install_path = Some(path.clone());
+                debug_log(&format!("Install path set to: {}", path));
+            }
+        }
+    }
+
+    // If silent mode, run installation directly and exit
+    if silent_mode {
+        let recorded = recorded_install_path();
+        if let (Some(given), Some(recorded)) = (&install_path, &recorded) {
+            if !same_path(given, recorded) {
+                let message = format!("--install-path {} differs from the recorded install at {}", given, recorded);
+                debug_log(&message);
+                silent::warning("install_path_mismatch", &message);
+            }
+        }
+        // Without --install-path, update the recorded install, or install fresh at the default
+        if let Some(path) = install_path.or(recorded).or_else(|| Some(default_install_path())) {
+            if let Some(warning) = cloud_sync::check(&path) {
+                let message = warning.message(&path);
+                debug_log(&message);
+                silent::warning("cloud_sync", &message);
+            }
+            debug_log(&format!("Running silent installation to: {}", path));
+            if let Err(failure) = install_silently(&path, &args) {
+                debug_log(&format!("FAILED: {}: {}", failure.stage, failure.message));
+                silent::finish(failure.code, Some(&failure.message));
+            }
+            cleanup::schedule();
+            if pending::deferred_count() > 0 {
+                silent::finish(silent::ExitCode::PendingRestart, Some(&locale::text("pending_restart", &[])));
+            }
+            silent::finish(silent::ExitCode::Success, None);
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    gui::run();
+    #[cfg(not(feature = "gui"))]
+    silent::finish(silent::ExitCode::InvalidArguments, Some("This build has no window; use --silent [--install-path <dir>]"));
+}
@@ -0,0 +1,56 @@
+//! The license agreement shipped with the payload (`LICENSE.txt`, sidecar or archive
+//! root). Builds without one install without asking. With one, the installer window
+//! won't install until it is accepted, and a silent fresh install needs
+//! `--accept-license`. Each acceptance is recorded in the install manifest with the time
+//! and the license version (a hash of its text), for organizations that have to show
+//! who agreed to what.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{debug_log, hash, payload};
+
+pub const LICENSE_FILE: &str = "LICENSE.txt";
+pub const ACCEPT_ARG: &str = "--accept-license";
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct License {
+    pub text: String,
+    /// Identifies the exact text, so a changed license can be told apart
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Acceptance {
+    pub version: String,
+    /// Seconds since UNIX epoch
+    pub accepted_at: u64,
+    /// `gui` or `silent`
+    pub method: String,
+}
+
+/// The acceptance given in this run, waiting for the manifest to be written
+static ACCEPTED: Mutex<Option<Acceptance>> = Mutex::new(None);
+
+pub fn read(payload_path: &Path, is_7z: bool) -> Option<License> {
+    let text = payload::read_text(payload_path, is_7z, LICENSE_FILE)?;
+    let version = hash::sha256_bytes(text.as_bytes())[..16].to_string();
+    Some(License { text, version })
+}
+
+/// Record that the license with `version` was accepted, by `method`
+pub fn accept(version: &str, method: &str) {
+    let accepted_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    debug_log(&format!("License {} accepted ({})", version, method));
+    if let Ok(mut accepted) = ACCEPTED.lock() {
+        *accepted = Some(Acceptance { version: version.to_string(), accepted_at, method: method.to_string() });
+    }
+}
+
+/// The acceptance given in this run, if it was for `license`
+pub fn accepted(license: &License) -> Option<Acceptance> {
+    ACCEPTED.lock().ok()?.clone().filter(|a| a.version == license.version)
+}
@@ -0,0 +1,95 @@
+//! The user's display language, used by the frontend to pick a default translation and
+//! by the few messages the backend shows itself (the update toast, silent run results).
+//! `--lang xx-YY` overrides it for both.
+
+use std::sync::OnceLock;
+
+static OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Maximum length of a locale name including the terminator (LOCALE_NAME_MAX_LENGTH)
+#[cfg(windows)]
+const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+/// The Windows display language, which is what the shell's own text is in; the regional
+/// format locale (dates, numbers) is often set differently
+#[cfg(windows)]
+fn query() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Globalization::{GetUserPreferredUILanguages, MUI_LANGUAGE_NAME};
+
+    // A double-null-terminated list of names, most preferred first
+    let mut count = 0;
+    let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH * 4];
+    let mut len = buffer.len() as u32;
+    let buffer_ptr = Some(PWSTR(buffer.as_mut_ptr()));
+    let listed = unsafe { GetUserPreferredUILanguages(MUI_LANGUAGE_NAME, &mut count, buffer_ptr, &mut len) };
+    let first = buffer.iter().position(|&c| c == 0).unwrap_or(0);
+    if listed.is_ok() && count > 0 && first > 0 {
+        return Some(String::from_utf16_lossy(&buffer[..first]));
+    }
+    regional_locale()
+}
+
+#[cfg(windows)]
+fn regional_locale() -> Option<String> {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH];
+    // Returns the length including the terminating null, or 0 on failure
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+#[cfg(not(windows))]
+fn query() -> Option<String> {
+    // POSIX locales look like `ja_JP.UTF-8`; the frontend expects a BCP 47 tag
+    let lang = std::env::var("LC_ALL").or_else(|_| std::env::var("LANG")).ok()?;
+    let tag = lang.split(['.', '@']).next()?.replace('_', "-");
+    (!tag.is_empty() && tag != "C" && tag != "POSIX").then_some(tag)
+}
+
+/// Take `--lang xx-YY` (or `--lang=xx-YY`) from the command line
+pub fn init(args: &[String]) {
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        arg.strip_prefix("--lang=")
+            .map(str::to_string)
+            .or_else(|| (arg == "--lang").then(|| args.get(i + 1).cloned()).flatten())
+    });
+    if let Some(tag) = value.map(|v| v.trim().replace('_', "-")).filter(|v| !v.is_empty()) {
+        crate::debug_log(&format!("Language set to: {}", tag));
+        let _ = OVERRIDE.set(tag);
+    }
+}
+
+/// BCP 47 tag of the user's language (e.g. `en-US`), `--lang` first, defaulting to `en-US`
+pub fn user_locale() -> String {
+    OVERRIDE.get().cloned().or_else(query).unwrap_or_else(|| "en-US".to_string())
+}
+
+/// The supported translation for the user's language: the same choice as the frontend's
+/// `resolveLanguage`
+fn language() -> &'static str {
+    let locale = user_locale().to_lowercase();
+    match locale.split('-').next() {
+        Some("ja") => "ja",
+        _ => "en",
+    }
+}
+
+/// The backend's own user-facing text in the user's language, with `{name}` placeholders
+/// filled from `params`. Unknown keys come back as the key.
+pub fn text(key: &str, params: &[(&str, &str)]) -> String {
+    let template = match (language(), key) {
+        ("ja", "update_complete") => "{app} が {version} に更新されました",
+        ("ja", "update_complete_hint") => "クリックして新機能を確認",
+        ("ja", "pending_restart") => "使用中のファイルがあったため、再起動後に更新が完了します",
+        (_, "update_complete") => "{app} was updated to {version}",
+        (_, "update_complete_hint") => "Click to see what's new",
+        (_, "pending_restart") => "Some files were in use; the update finishes after a restart",
+        _ => key,
+    };
+    params.iter().fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
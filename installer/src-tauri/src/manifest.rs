@@ -0,0 +1,146 @@
+//! The install manifest: `install-manifest.json` in the install directory, recording
+//! what the installer put on the system so updates, repair and uninstall can find it again.
+//! On macOS it lives in Application Support instead; see `state_dir`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::branding;
+use crate::durable;
+use crate::known_folders::{self, KnownFolder};
+use crate::license;
+use crate::payload::PayloadFile;
+use crate::shortcuts::ExtraShortcut;
+
+pub const MANIFEST_FILE: &str = "install-manifest.json";
+
+/// What the user chose in the installer; saved in the manifest so updates and
+/// reinstalls start from the same choices
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InstallOptions {
+    pub install_path: String,
+    pub desktop_shortcut: bool,
+    pub start_menu_shortcut: bool,
+    /// "Uninstall <app>" in the Start Menu folder
+    pub uninstall_shortcut: bool,
+    /// Start Menu folder for the shortcuts: `None` uses the app's own folder, an empty
+    /// name puts them at the top level
+    pub start_menu_folder: Option<String>,
+    /// Use the all-users Start Menu instead of the current user's; needs administrator rights
+    pub all_users_start_menu: bool,
+    /// Extra Start Menu shortcuts into sections of the app, saved in full so updates and
+    /// relocation can recreate them without the payload
+    pub extra_shortcuts: Vec<ExtraShortcut>,
+    pub register_file_associations: bool,
+    /// Firewall rule for local network sharing; needs administrator rights
+    pub firewall_rule: bool,
+    /// Microsoft Defender exclusion for the install directory; needs administrator rights
+    /// and is never on by default
+    pub defender_exclusion: bool,
+    /// Send one anonymous success/failure ping when the install finishes
+    pub send_telemetry: bool,
+    pub launch_on_finish: bool,
+    /// Self-contained install: no shortcuts, file associations, uninstaller or update cache
+    pub portable: bool,
+    /// Where library data, covers and downloads live; `None` keeps the app's default
+    pub data_dir: Option<String>,
+    /// Optional components to download, by id from components.json
+    pub components: Vec<String>,
+    /// Folder of extension packages to preinstall on a first install
+    pub extensions_dir: Option<String>,
+    /// Releases endpoint the app checks for updates instead of GitHub's, from the
+    /// deployment policy; kept so later updates go on using it without being told again
+    pub update_url: Option<String>,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            install_path: String::new(),
+            desktop_shortcut: true,
+            start_menu_shortcut: true,
+            uninstall_shortcut: true,
+            start_menu_folder: None,
+            all_users_start_menu: false,
+            extra_shortcuts: Vec::new(),
+            register_file_associations: true,
+            firewall_rule: false,
+            defender_exclusion: false,
+            send_telemetry: false,
+            launch_on_finish: true,
+            portable: false,
+            data_dir: None,
+            components: Vec::new(),
+            extensions_dir: None,
+            update_url: None,
+        }
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct InstallManifest {
+    pub version: Option<String>,
+    /// Seconds since UNIX epoch of the last install or update
+    pub installed_at: u64,
+    /// AppUserModelID stamped on the shortcuts; the app must use the same one
+    pub app_user_model_id: Option<String>,
+    /// Shortcut files created by the installer
+    pub shortcuts: Vec<PathBuf>,
+    /// Install-relative paths of the files extracted from the payload
+    pub files: Vec<String>,
+    /// Install-relative paths of the files written by optional components
+    pub component_files: Vec<String>,
+    /// Expected size and SHA-256 of the payload files, from the payload's hash manifest
+    pub hashes: BTreeMap<String, PayloadFile>,
+    /// Options of the last interactive install
+    pub options: Option<InstallOptions>,
+    /// Every acceptance of the bundled license, oldest first
+    pub licenses: Vec<license::Acceptance>,
+}
+
+impl InstallManifest {
+    /// Every file the installer put in the install directory
+    pub fn all_files(&self) -> Vec<String> {
+        self.files.iter().chain(&self.component_files).cloned().collect()
+    }
+}
+
+/// Where the installer keeps its own files for the install at `install_path`: the install
+/// directory, except on macOS. codesign seals every file in a bundle, Resources included,
+/// so there they go to `~/Library/Application Support/<data dir>` where the app finds them.
+pub fn state_dir(install_path: &str) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        if let Some(support) = known_folders::get(KnownFolder::RoamingAppData) {
+            return support.join(&branding::get().data_dir_name);
+        }
+    }
+    PathBuf::from(install_path)
+}
+
+/// Where the manifest of the install at `install_path` lives
+pub fn manifest_path(install_path: &str) -> PathBuf {
+    state_dir(install_path).join(MANIFEST_FILE)
+}
+
+pub fn load(install_path: &str) -> Option<InstallManifest> {
+    let data = std::fs::read_to_string(manifest_path(install_path)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save(install_path: &str, manifest: &InstallManifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    durable::write(&manifest_path(install_path), json.as_bytes()).map_err(|e| format!("Failed to write install manifest: {}", e))
+}
+
+/// Load the existing manifest (or start a new one), apply `f`, and write it back
+pub fn update(install_path: &str, f: impl FnOnce(&mut InstallManifest)) -> Result<(), String> {
+    let mut manifest = load(install_path).unwrap_or_default();
+    f(&mut manifest);
+    manifest.installed_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    save(install_path, &manifest)
+}
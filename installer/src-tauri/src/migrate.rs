@@ -0,0 +1,159 @@
+//! Importing user data from an older layout or a backup file during setup.
+//!
+//! Two kinds of source are supported:
+//! - a data directory from an older install (holding `mangyomi.db`), whose library,
+//!   extensions and cover cache are copied into the current data directory;
+//! - a Mangyomi (`.mgb`) or Tachiyomi-style (`.tachibk`, `.proto.gz`) backup file, which is
+//!   queued in `pending-import/` for the app to import on its next start, since parsing
+//!   it needs the app's database and extensions.
+//!
+//! Existing data is never overwritten. A dry run returns the same report without copying.
+
+use std::path::{Path, PathBuf};
+
+use crate::data_dir::DATABASE_FILE;
+use crate::debug_log;
+use crate::known_folders::{self, KnownFolder};
+
+/// Folder the app checks on startup for backups to import
+pub const PENDING_IMPORT_DIR: &str = "pending-import";
+
+/// Data directories used by earlier builds (published as `mangyomi-app`)
+const LEGACY_DATA_DIRS: &[&str] = &["mangyomi-app"];
+
+/// What a data directory migration carries over, relative to the data directory
+const DATA_ITEMS: &[&str] = &[DATABASE_FILE, "extensions", "cache/covers"];
+
+const BACKUP_EXTENSIONS: &[&str] = &[".mgb", ".tachibk", ".proto.gz", ".proto"];
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MigrationSource {
+    DataDir { path: PathBuf },
+    Backup { path: PathBuf },
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationAction {
+    Copy,
+    QueueImport,
+    /// Already present at the destination; left alone
+    SkipExisting,
+}
+
+#[derive(serde::Serialize)]
+pub struct MigrationItem {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub bytes: u64,
+    pub action: MigrationAction,
+}
+
+#[derive(serde::Serialize)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    pub items: Vec<MigrationItem>,
+    /// Bytes copied (or that would be copied in a dry run)
+    pub total_bytes: u64,
+}
+
+fn path_size(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(m) if m.is_dir() => std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| path_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(m) => m.len(),
+        Err(_) => 0,
+    }
+}
+
+fn is_backup_file(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    path.is_file() && BACKUP_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// Classify a user-chosen path as a migration source
+pub fn source_from_path(path: &Path) -> Result<MigrationSource, String> {
+    if path.join(DATABASE_FILE).is_file() {
+        Ok(MigrationSource::DataDir { path: path.to_path_buf() })
+    } else if is_backup_file(path) {
+        Ok(MigrationSource::Backup { path: path.to_path_buf() })
+    } else {
+        Err(format!("{} is neither a data folder nor a supported backup file", path.display()))
+    }
+}
+
+/// Older data directories that still hold a library
+pub fn detect_sources() -> Vec<MigrationSource> {
+    let Some(appdata) = known_folders::get(KnownFolder::RoamingAppData) else { return Vec::new() };
+    LEGACY_DATA_DIRS
+        .iter()
+        .map(|name| appdata.join(name))
+        .filter(|dir| dir.join(DATABASE_FILE).is_file())
+        .map(|path| MigrationSource::DataDir { path })
+        .collect()
+}
+
+fn plan(source: &MigrationSource, data_dir: &Path) -> Vec<MigrationItem> {
+    match source {
+        MigrationSource::DataDir { path } => DATA_ITEMS
+            .iter()
+            .map(|item| (path.join(item), data_dir.join(item)))
+            .filter(|(source, _)| source.exists())
+            .map(|(source, destination)| MigrationItem {
+                bytes: path_size(&source),
+                action: if destination.exists() { MigrationAction::SkipExisting } else { MigrationAction::Copy },
+                source,
+                destination,
+            })
+            .collect(),
+        MigrationSource::Backup { path } => {
+            let destination = data_dir.join(PENDING_IMPORT_DIR).join(path.file_name().unwrap_or_default());
+            vec![MigrationItem {
+                bytes: path_size(path),
+                action: if destination.exists() { MigrationAction::SkipExisting } else { MigrationAction::QueueImport },
+                source: path.clone(),
+                destination,
+            }]
+        }
+    }
+}
+
+pub fn copy_recursive(source: &Path, destination: &Path) -> Result<(), String> {
+    if source.is_dir() {
+        std::fs::create_dir_all(destination).map_err(|e| e.to_string())?;
+        for entry in std::fs::read_dir(source).map_err(|e| e.to_string())?.flatten() {
+            copy_recursive(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::copy(source, destination).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Migrate `source` into `data_dir`, calling `progress` with each item and the
+/// percentage done. With `dry_run` only the report is produced.
+pub fn migrate(source: &MigrationSource, data_dir: &Path, dry_run: bool, mut progress: impl FnMut(&Path, u32)) -> Result<MigrationReport, String> {
+    let items = plan(source, data_dir);
+    let total_bytes = items
+        .iter()
+        .filter(|i| !matches!(i.action, MigrationAction::SkipExisting))
+        .map(|i| i.bytes)
+        .sum();
+
+    if !dry_run {
+        let mut done = 0u64;
+        for item in items.iter().filter(|i| !matches!(i.action, MigrationAction::SkipExisting)) {
+            progress(&item.source, (done * 100).checked_div(total_bytes).unwrap_or(0) as u32);
+            copy_recursive(&item.source, &item.destination)?;
+            done += item.bytes;
+        }
+        debug_log(&format!("Migrated {} bytes from {} items", total_bytes, items.len()));
+    }
+
+    Ok(MigrationReport { dry_run, items, total_bytes })
+}
@@ -0,0 +1,88 @@
+//! Mark-of-the-Web handling for extracted files.
+//!
+//! Windows records where a download came from in a `Zone.Identifier` alternate data
+//! stream. Files written from a downloaded payload can end up carrying it, which makes
+//! SmartScreen prompt on the first launch of an app the user already chose to install.
+//!
+//! macOS does the same with the `com.apple.quarantine` attribute, which sends the
+//! bundle through Gatekeeper's "downloaded from the internet" prompt.
+
+use std::path::Path;
+
+use crate::debug_log;
+
+/// Extensions that SmartScreen and the loader check for a zone identifier
+const EXECUTABLE_EXTENSIONS: &[&str] = &["exe", "dll", "node"];
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum MotwPolicy {
+    /// Remove the zone identifier from extracted executables
+    #[default]
+    Strip,
+    /// Leave whatever the file system gave the files alone
+    Keep,
+}
+
+impl MotwPolicy {
+    /// Parse the value of `--motw <strip|keep>`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "strip" => Some(MotwPolicy::Strip),
+            "keep" => Some(MotwPolicy::Keep),
+            _ => None,
+        }
+    }
+}
+
+fn is_executable(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXECUTABLE_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+/// Apply `policy` to the executables among `files` (install-relative, `/` separators).
+/// Returns the number of zone identifiers removed.
+pub fn apply(install_path: &str, files: &[String], policy: MotwPolicy) -> usize {
+    if policy == MotwPolicy::Keep {
+        return 0;
+    }
+    if cfg!(target_os = "macos") {
+        return strip_quarantine(install_path);
+    }
+
+    let mut stripped = 0;
+    for file in files.iter().filter(|f| is_executable(f)) {
+        // The stream is addressed as `<file>:Zone.Identifier`; a missing one is the common case
+        let stream = format!("{}:Zone.Identifier", Path::new(install_path).join(file).display());
+        match std::fs::remove_file(&stream) {
+            Ok(()) => stripped += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => debug_log(&format!("Failed to remove zone identifier from {}: {}", file, e)),
+        }
+    }
+
+    if stripped > 0 {
+        debug_log(&format!("Removed Mark-of-the-Web from {} files", stripped));
+    }
+    stripped
+}
+
+/// Remove the quarantine attribute from the whole bundle; Gatekeeper checks the bundle,
+/// not individual executables. Returns 1 when it was cleared.
+fn strip_quarantine(install_path: &str) -> usize {
+    match std::process::Command::new("xattr").args(["-dr", "com.apple.quarantine", install_path]).status() {
+        Ok(status) if status.success() => {
+            debug_log(&format!("Removed quarantine from {}", install_path));
+            1
+        }
+        Ok(status) => {
+            debug_log(&format!("xattr exited with {}", status));
+            0
+        }
+        Err(e) => {
+            debug_log(&format!("Failed to run xattr: {}", e));
+            0
+        }
+    }
+}
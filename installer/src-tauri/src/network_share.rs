@@ -0,0 +1,133 @@
+//! Installs on network shares (`\\server\share\Mangyomi`, or a drive letter mapped to
+//! one), as school labs and shared family PCs use. The share is probed before anything
+//! is written so an unreachable server or missing credentials get their own message, not
+//! a generic I/O error halfway through. Local-volume niceties are skipped there: cluster
+//! preallocation, zone identifier streams and boot-time file moves either aren't supported
+//! by every SMB server or don't work on remote paths at all. Shortcuts point at the UNC
+//! path, since a mapped drive letter only exists in the session that mapped it.
+
+use std::path::{Component, Path, PathBuf, Prefix};
+
+use crate::debug_log;
+
+/// `\\server\share` for a UNC path
+fn share_of(path: &Path) -> Option<String> {
+    match path.components().next()? {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                Some(format!("\\\\{}\\{}", server.to_string_lossy(), share.to_string_lossy()))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn drive_letter(path: &Path) -> Option<char> {
+    match path.components().next()? {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => Some(char::from(letter).to_ascii_uppercase()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `path` is on a network share, by UNC path or mapped drive
+pub fn is_network(path: &Path) -> bool {
+    share_of(path).is_some() || drive_letter(path).is_some_and(is_remote_drive)
+}
+
+#[cfg(windows)]
+fn is_remote_drive(letter: char) -> bool {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    const DRIVE_REMOTE: u32 = 4;
+    unsafe { GetDriveTypeW(&HSTRING::from(format!("{}:\\", letter))) == DRIVE_REMOTE }
+}
+
+#[cfg(not(windows))]
+fn is_remote_drive(_letter: char) -> bool {
+    false
+}
+
+/// `path` with a mapped drive letter replaced by the share it maps, when Windows
+/// remembers the mapping; other paths come back unchanged
+pub fn to_unc(path: &Path) -> PathBuf {
+    let Some(letter) = drive_letter(path).filter(|&l| is_remote_drive(l)) else { return path.to_path_buf() };
+    let Some(remote) = remote_path(letter) else { return path.to_path_buf() };
+    let rest: PathBuf = path.components().skip_while(|c| matches!(c, Component::Prefix(_) | Component::RootDir)).collect();
+    PathBuf::from(format!("{}\\", remote.trim_end_matches('\\'))).join(rest)
+}
+
+/// Persistent drive mappings are kept under `HKCU\Network\<letter>`
+#[cfg(windows)]
+fn remote_path(letter: char) -> Option<String> {
+    crate::registry::get_string(&format!("Network\\{}", letter), "RemotePath").filter(|p| p.starts_with("\\\\"))
+}
+
+#[cfg(not(windows))]
+fn remote_path(_letter: char) -> Option<String> {
+    None
+}
+
+/// Why a share can't be used, told apart by the Windows error
+fn describe(e: &std::io::Error, share: &str) -> Option<String> {
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    const ERROR_REM_NOT_LIST: i32 = 51;
+    const ERROR_BAD_NETPATH: i32 = 53;
+    const ERROR_NETNAME_DELETED: i32 = 64;
+    const ERROR_NETWORK_ACCESS_DENIED: i32 = 65;
+    const ERROR_BAD_NET_NAME: i32 = 67;
+    const ERROR_INVALID_PASSWORD: i32 = 86;
+    const ERROR_SESSION_CREDENTIAL_CONFLICT: i32 = 1219;
+    const ERROR_NETWORK_UNREACHABLE: i32 = 1231;
+    const ERROR_NOT_AUTHENTICATED: i32 = 1244;
+    const ERROR_LOGON_FAILURE: i32 = 1326;
+
+    match e.raw_os_error()? {
+        ERROR_REM_NOT_LIST | ERROR_BAD_NETPATH | ERROR_NETNAME_DELETED | ERROR_BAD_NET_NAME
+        | ERROR_NETWORK_UNREACHABLE => Some(format!(
+            "Cannot reach the network share {}. Check that the server is on and the share name is right.",
+            share
+        )),
+        ERROR_INVALID_PASSWORD | ERROR_NOT_AUTHENTICATED | ERROR_LOGON_FAILURE => Some(format!(
+            "Windows has no valid credentials for {}. Open the share in File Explorer and sign in, then try again.",
+            share
+        )),
+        ERROR_SESSION_CREDENTIAL_CONFLICT => Some(format!(
+            "{} is already connected with a different user name. Disconnect it (net use {} /delete) and try again.",
+            share, share
+        )),
+        ERROR_ACCESS_DENIED | ERROR_NETWORK_ACCESS_DENIED => {
+            Some(format!("Your account can reach {} but isn't allowed to write to it.", share))
+        }
+        _ => None,
+    }
+}
+
+/// For network paths, create `install_path` and write a file into it, failing with a
+/// message naming the problem when the share is unreachable, needs credentials or is
+/// read-only. Local paths and other failures are left to the install steps.
+pub fn probe(install_path: &str) -> Result<(), String> {
+    let dir = Path::new(install_path);
+    if !is_network(dir) {
+        return Ok(());
+    }
+    let unc = to_unc(dir);
+    let share = share_of(&unc).unwrap_or_else(|| install_path.to_string());
+    let test_file = dir.join(".write-test");
+    let result = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&test_file, b""));
+    let _ = std::fs::remove_file(&test_file);
+    match result {
+        Ok(()) => {
+            debug_log(&format!("Installing to network share {}", share));
+            Ok(())
+        }
+        Err(e) => {
+            debug_log(&format!("Network share {} not usable: {}", share, e));
+            describe(&e, &share).map_or(Ok(()), Err)
+        }
+    }
+}
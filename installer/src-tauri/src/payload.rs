@@ -0,0 +1,193 @@
+//! The bundled app payload (app.7z or app.zip) and the per-file hash manifest shipped with it.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Name of the hash manifest, either next to the payload or at the archive root
+pub const MANIFEST_FILE: &str = "hashes.json";
+
+/// Written into the app directory during build; also read from the install afterwards
+pub const VERSION_FILE: &str = "version.txt";
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct PayloadFile {
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Per-file hashes of the payload, keyed by archive path with `/` separators
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct PayloadManifest {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub files: HashMap<String, PayloadFile>,
+}
+
+impl PayloadManifest {
+    pub fn get(&self, entry_name: &str) -> Option<&PayloadFile> {
+        self.files.get(&normalize_entry_name(entry_name))
+    }
+}
+
+/// Archive entries may use either separator depending on the tool that packed them
+pub fn normalize_entry_name(name: &str) -> String {
+    name.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+/// Load the hash manifest for a payload: a sidecar `hashes.json` next to the archive
+/// wins, otherwise the copy packed at the archive root is used.
+pub fn read_manifest(payload_path: &Path, is_7z: bool) -> Option<PayloadManifest> {
+    read_json(payload_path, is_7z, MANIFEST_FILE)
+}
+
+/// The version the payload installs: `version.txt` (sidecar or archive root), falling
+/// back to the version recorded in the hash manifest
+pub fn read_version(payload_path: &Path, is_7z: bool) -> Option<String> {
+    read_text(payload_path, is_7z, VERSION_FILE)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .or_else(|| read_manifest(payload_path, is_7z)?.version)
+}
+
+/// Read a JSON metadata file shipped with the payload, as a sidecar or at the archive root
+pub fn read_json<T: serde::de::DeserializeOwned>(payload_path: &Path, is_7z: bool, name: &str) -> Option<T> {
+    serde_json::from_str(&read_text(payload_path, is_7z, name)?).ok()
+}
+
+pub fn read_text(payload_path: &Path, is_7z: bool, name: &str) -> Option<String> {
+    let sidecar = payload_path.with_file_name(name);
+    if sidecar.exists() {
+        std::fs::read_to_string(&sidecar).ok()
+    } else if is_7z {
+        read_7z_entry(payload_path, name)
+    } else {
+        read_zip_entry(payload_path, name)
+    }
+}
+
+fn read_zip_entry(archive_path: &Path, name: &str) -> Option<String> {
+    let file = std::fs::File::open(archive_path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let mut entry = archive.by_name(name).ok()?;
+    let mut data = String::new();
+    entry.read_to_string(&mut data).ok()?;
+    Some(data)
+}
+
+fn read_7z_entry(archive_path: &Path, name: &str) -> Option<String> {
+    let mut found = None;
+    let mut reader = sevenz_rust::SevenZReader::open(archive_path, sevenz_rust::Password::empty()).ok()?;
+    reader
+        .for_each_entries(|entry, entry_reader| {
+            if normalize_entry_name(entry.name()) == name {
+                let mut data = String::new();
+                entry_reader.read_to_string(&mut data).map_err(sevenz_rust::Error::io)?;
+                found = Some(data);
+                return Ok(false);
+            }
+            // Entries in a solid block must be drained to reach the next one
+            std::io::copy(entry_reader, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+            Ok(true)
+        })
+        .ok()?;
+    found
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct PayloadSize {
+    /// Total uncompressed size of every file entry, in bytes
+    pub total_bytes: u64,
+    pub file_count: usize,
+}
+
+/// Sum the uncompressed file sizes recorded in the archive headers, without extracting anything
+pub fn uncompressed_size(payload_path: &Path, is_7z: bool) -> Result<PayloadSize, String> {
+    let mut file = std::fs::File::open(payload_path).map_err(|e| format!("Failed to open payload {:?}: {}", payload_path, e))?;
+    let mut size = PayloadSize::default();
+
+    if is_7z {
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        let archive = sevenz_rust::Archive::read(&mut file, len, &[]).map_err(|e| e.to_string())?;
+        for entry in archive.files.iter().filter(|e| !e.is_directory()) {
+            size.total_bytes += entry.size();
+            size.file_count += 1;
+        }
+    } else {
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        for i in 0..archive.len() {
+            // Raw access reads the central directory record without starting decompression
+            let entry = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+            if !entry.is_dir() {
+                size.total_bytes += entry.size();
+                size.file_count += 1;
+            }
+        }
+    }
+
+    Ok(size)
+}
+
+/// One file of the payload as recorded in the archive headers, with the hash the
+/// manifest expects for it
+#[derive(serde::Serialize)]
+pub struct PayloadEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct PayloadListing {
+    pub version: Option<String>,
+    pub entries: Vec<PayloadEntry>,
+    /// Files the hash manifest lists that the archive doesn't contain
+    pub missing: Vec<String>,
+    /// Files whose size in the archive differs from the manifest
+    pub size_mismatches: Vec<String>,
+}
+
+impl PayloadListing {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.size_mismatches.is_empty()
+    }
+}
+
+/// List the payload's files from the archive headers and compare them with the hash
+/// manifest, without extracting anything. A truncated archive fails to open here.
+pub fn list(payload_path: &Path, is_7z: bool) -> Result<PayloadListing, String> {
+    let mut file = std::fs::File::open(payload_path).map_err(|e| format!("Failed to open payload {:?}: {}", payload_path, e))?;
+    let mut files = Vec::new();
+    if is_7z {
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        let archive = sevenz_rust::Archive::read(&mut file, len, &[]).map_err(|e| e.to_string())?;
+        files.extend(archive.files.iter().filter(|e| !e.is_directory()).map(|e| (normalize_entry_name(e.name()), e.size())));
+    } else {
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+            if !entry.is_dir() {
+                files.push((normalize_entry_name(entry.name()), entry.size()));
+            }
+        }
+    }
+
+    let manifest = read_manifest(payload_path, is_7z).unwrap_or_default();
+    let mut size_mismatches = Vec::new();
+    let entries: Vec<PayloadEntry> = files
+        .into_iter()
+        .map(|(path, size)| {
+            let expected = manifest.get(&path);
+            if expected.is_some_and(|f| f.size != size) {
+                size_mismatches.push(path.clone());
+            }
+            PayloadEntry { sha256: expected.map(|f| f.sha256.clone()), path, size }
+        })
+        .collect();
+    let present: std::collections::HashSet<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    let mut missing: Vec<String> = manifest.files.keys().filter(|path| !present.contains(path.as_str())).cloned().collect();
+    missing.sort();
+
+    Ok(PayloadListing { version: read_version(payload_path, is_7z), entries, missing, size_mismatches })
+}
@@ -0,0 +1,134 @@
+//! Files that stay locked through an update. An install file another process holds open
+//! (an app that didn't quit, an antivirus scan) is retried for a few seconds; if it is
+//! still locked the new copy is written next to it as `<name>.pending-update` and moved
+//! into place at the next sign-in by a RunOnce entry, and at the next boot through
+//! MoveFileEx when we have the rights for that. The update then reports a pending
+//! restart instead of failing.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::debug_log;
+
+const PENDING_SUFFIX: &str = ".pending-update";
+const RETRIES: u32 = 5;
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Destinations written as pending copies during this run
+static DEFERRED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn pending_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(PENDING_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Sharing and lock violations; other errors aren't going to go away by waiting
+#[cfg(windows)]
+fn is_locked(e: &std::io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    matches!(e.raw_os_error(), Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION))
+}
+
+/// Open files can be replaced on Unix
+#[cfg(not(windows))]
+fn is_locked(_e: &std::io::Error) -> bool {
+    false
+}
+
+/// Where to write the new copy of `dest`: `dest` itself, unless it stays locked through
+/// the retries, in which case a pending copy next to it that is moved into place later
+pub fn writable_target(dest: &Path) -> PathBuf {
+    for attempt in 0..=RETRIES {
+        match std::fs::OpenOptions::new().write(true).open(dest) {
+            Err(e) if is_locked(&e) => {
+                if attempt < RETRIES {
+                    std::thread::sleep(RETRY_DELAY);
+                }
+            }
+            // Missing, writable, or failing for a reason extraction will report itself
+            _ => return dest.to_path_buf(),
+        }
+    }
+    debug_log(&format!("{:?} is still locked; deferring it until restart", dest));
+    if let Ok(mut deferred) = DEFERRED.lock() {
+        deferred.push(dest.to_path_buf());
+    }
+    pending_path(dest)
+}
+
+pub fn is_deferred(dest: &Path) -> bool {
+    DEFERRED.lock().is_ok_and(|deferred| deferred.iter().any(|d| d == dest))
+}
+
+pub fn deferred_count() -> usize {
+    DEFERRED.lock().map(|deferred| deferred.len()).unwrap_or(0)
+}
+
+/// Arrange for the pending copies to be moved into place: at the next boot when the
+/// system lets us (administrators only), and otherwise at the next sign-in through the
+/// uninstaller copy, which is the installer itself
+#[cfg(windows)]
+pub fn schedule(install_path: &str) -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{MoveFileExW, MOVEFILE_DELAY_UNTIL_REBOOT, MOVEFILE_REPLACE_EXISTING};
+
+    // Boot-time moves only work on local volumes
+    let deferred = DEFERRED.lock().map(|d| d.clone()).unwrap_or_default();
+    if !crate::network_share::is_network(Path::new(install_path)) {
+        for dest in &deferred {
+            let flags = MOVEFILE_DELAY_UNTIL_REBOOT | MOVEFILE_REPLACE_EXISTING;
+            if let Err(e) = unsafe { MoveFileExW(&HSTRING::from(pending_path(dest).as_path()), &HSTRING::from(dest.as_path()), flags) } {
+                debug_log(&format!("Boot-time move of {:?} not available: {}", dest, e));
+            }
+        }
+    }
+
+    let branding = crate::branding::get();
+    let uninstaller = Path::new(install_path).join(branding.uninstaller_exe());
+    let command = format!("\"{}\" --finish-pending --install-path \"{}\"", uninstaller.display(), install_path);
+    crate::registry::set_string(
+        "Software\\Microsoft\\Windows\\CurrentVersion\\RunOnce",
+        Some(&format!("{} update", branding.app_name)),
+        &command,
+    )
+}
+
+#[cfg(not(windows))]
+pub fn schedule(_install_path: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/// Move every pending copy under `install_path` into place (`--finish-pending`). Returns
+/// how many were moved; copies still locked are left for the next attempt.
+pub fn finish(install_path: &str) -> Result<usize, String> {
+    let mut pending = Vec::new();
+    collect_pending(Path::new(install_path), &mut pending);
+    let mut moved = 0;
+    let mut failed = Vec::new();
+    for path in pending {
+        let dest = path.with_file_name(path.file_name().unwrap_or_default().to_string_lossy().trim_end_matches(PENDING_SUFFIX));
+        match std::fs::rename(&path, &dest) {
+            Ok(()) => moved += 1,
+            Err(e) => failed.push(format!("{}: {}", dest.display(), e)),
+        }
+    }
+    debug_log(&format!("Finished {} pending files", moved));
+    if !failed.is_empty() {
+        return Err(format!("Still pending: {}", failed.join(", ")));
+    }
+    Ok(moved)
+}
+
+fn collect_pending(dir: &Path, pending: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            collect_pending(&path, pending);
+        } else if path.to_string_lossy().ends_with(PENDING_SUFFIX) {
+            pending.push(path);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Deployment policy for managed machines: `installer-policy.json` in
+//! `%ProgramData%\<data dir>`, overridable with command-line flags. Loaded once at
+//! startup, before any interactive defaults are computed.
+//!
+//! The update settings are for the app, which does its own updating: they are written
+//! to `update-policy.json` in the install directory (Application Support on macOS)
+//! whenever an install or update runs, and removed again once no update setting is left.
+//! The update server can also be set by Group Policy, as the `UpdateUrl` string under
+//! `Software\Policies\<app>` in HKLM or HKCU, which wins over the file.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::known_folders::{self, KnownFolder};
+use crate::{branding, cert_pins, debug_log, manifest, retry};
+
+pub const POLICY_FILE: &str = "installer-policy.json";
+
+/// The update settings handed to the app
+pub const UPDATE_POLICY_FILE: &str = "update-policy.json";
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Policy {
+    /// Install here, whatever the user picks (`--force-install-path`)
+    pub install_path: Option<String>,
+    /// Don't start the app after installing or updating (`--no-launch`)
+    pub disable_auto_launch: bool,
+    /// Never create a desktop shortcut (`--no-desktop-shortcut`)
+    pub disable_desktop_shortcut: bool,
+    /// `stable` or `beta`; the app's channel setting is locked to it (`--update-channel`)
+    pub update_channel: Option<String>,
+    /// Only offer releases at least this many days old (`--defer-updates`)
+    pub defer_updates_days: Option<u32>,
+    /// Where the app looks for updates instead of GitHub (`--update-url`): a releases
+    /// endpoint answering like `https://api.github.com/repos/<owner>/<repo>` does, or a
+    /// folder (usually a UNC path) holding one release's assets and signed manifest
+    pub update_url: Option<String>,
+    /// Replaces the build's certificate pins, for networks that inspect TLS with their own
+    /// CA; `{}` turns pinning off
+    pub certificate_pins: Option<cert_pins::Pins>,
+    /// How manifest fetches and downloads retry transient failures, here and in the app
+    pub retry: Option<retry::RetryPolicy>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdatePolicy<'a> {
+    channel: Option<&'a str>,
+    defer_days: Option<u32>,
+    update_url: Option<&'a str>,
+    retry: Option<retry::RetryPolicy>,
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+fn policy_path() -> Option<PathBuf> {
+    let program_data = known_folders::get(KnownFolder::ProgramData)?;
+    Some(program_data.join(&branding::get().data_dir_name).join(POLICY_FILE))
+}
+
+fn load_file() -> Policy {
+    let Some(path) = policy_path() else { return Policy::default() };
+    let Ok(json) = std::fs::read_to_string(&path) else { return Policy::default() };
+    match serde_json::from_str(&json) {
+        Ok(policy) => {
+            debug_log(&format!("Loaded deployment policy from {:?}", path));
+            policy
+        }
+        Err(e) => {
+            debug_log(&format!("Ignoring invalid {:?}: {}", path, e));
+            Policy::default()
+        }
+    }
+}
+
+/// `UpdateUrl` from the Group Policy key, machine before user
+#[cfg(windows)]
+fn registry_update_url() -> Option<String> {
+    use crate::registry;
+
+    let key = format!("Software\\Policies\\{}", branding::get().app_name);
+    registry::get_machine_string(&key, "UpdateUrl").or_else(|| registry::get_string(&key, "UpdateUrl"))
+}
+
+#[cfg(not(windows))]
+fn registry_update_url() -> Option<String> {
+    None
+}
+
+/// Whether the update source is a folder rather than an endpoint
+pub fn is_update_folder(source: &str) -> bool {
+    Path::new(source).is_absolute()
+}
+
+/// Only HTTPS endpoints and absolute folder paths are accepted; anything else is logged
+/// and ignored
+fn valid_update_url(value: String) -> Option<String> {
+    if is_update_folder(&value) {
+        return Some(value);
+    }
+    match url::Url::parse(&value) {
+        Ok(url) if url.scheme() == "https" && url.host().is_some() => Some(value.trim_end_matches('/').to_string()),
+        _ => {
+            debug_log(&format!("Ignoring update URL {:?}: neither an https URL nor a folder", value));
+            None
+        }
+    }
+}
+
+/// The policy file with the registry and command-line overrides from `args` applied
+fn load(args: &[String]) -> Policy {
+    let mut policy = load_file();
+    let value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned();
+    if let Some(url) = registry_update_url() {
+        policy.update_url = Some(url);
+    }
+    if let Some(url) = value("--update-url") {
+        policy.update_url = Some(url);
+    }
+    policy.update_url = policy.update_url.take().and_then(valid_update_url);
+    if let Some(path) = value("--force-install-path") {
+        policy.install_path = Some(path);
+    }
+    if args.iter().any(|a| a == "--no-launch") {
+        policy.disable_auto_launch = true;
+    }
+    if args.iter().any(|a| a == "--no-desktop-shortcut") {
+        policy.disable_desktop_shortcut = true;
+    }
+    if let Some(channel) = value("--update-channel").filter(|c| c == "stable" || c == "beta") {
+        policy.update_channel = Some(channel);
+    }
+    if let Some(days) = value("--defer-updates").and_then(|d| d.parse().ok()) {
+        policy.defer_updates_days = Some(days);
+    }
+    policy
+}
+
+/// Load the policy; call once at startup before anything reads it
+pub fn init(args: &[String]) {
+    let _ = POLICY.set(load(args));
+}
+
+pub fn get() -> &'static Policy {
+    POLICY.get_or_init(Policy::default)
+}
+
+impl Policy {
+    /// Override the choices the policy controls
+    pub fn apply(&self, options: &mut manifest::InstallOptions) {
+        if let Some(path) = &self.install_path {
+            options.install_path = path.clone();
+        }
+        if self.disable_auto_launch {
+            options.launch_on_finish = false;
+        }
+        if self.disable_desktop_shortcut {
+            options.desktop_shortcut = false;
+        }
+        if let Some(url) = &self.update_url {
+            options.update_url = Some(url.clone());
+        }
+    }
+
+    /// Write `update-policy.json` for the app when the policy sets any update setting, or
+    /// the install recorded an update URL (`recorded_url`) from an earlier one. Otherwise
+    /// remove one left by an earlier policy, so the app stops enforcing it. Returns
+    /// whether it was written.
+    pub fn write_update_policy(&self, install_path: &str, recorded_url: Option<&str>) -> Result<bool, String> {
+        let update_url = self.update_url.as_deref().or(recorded_url);
+        if self.update_channel.is_none() && self.defer_updates_days.is_none() && update_url.is_none() && self.retry.is_none() {
+            let stale = manifest::state_dir(install_path).join(UPDATE_POLICY_FILE);
+            match std::fs::remove_file(&stale) {
+                Ok(()) => debug_log(&format!("Removed {:?}: no update policy is set any more", stale)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Failed to remove {:?}: {}", stale, e)),
+            }
+            return Ok(false);
+        }
+        let update = UpdatePolicy {
+            channel: self.update_channel.as_deref(),
+            defer_days: self.defer_updates_days,
+            update_url,
+            retry: self.retry,
+        };
+        let json = serde_json::to_string_pretty(&update).map_err(|e| e.to_string())?;
+        let dir = manifest::state_dir(install_path);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        std::fs::write(dir.join(UPDATE_POLICY_FILE), json)
+            .map_err(|e| format!("Failed to write {}: {}", UPDATE_POLICY_FILE, e))?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,67 @@
+//! Portable installs: everything stays in the install directory, typically on a USB stick,
+//! with a `.portable` marker and a copy of the installer as the uninstaller beside the app.
+//! `--uninstall-portable <dir>`, run by hand or by the app's own hook, removes the files
+//! the install manifest lists, the manifest and the marker. The registry, shortcuts and
+//! data folder are never touched: another machine's entries aren't ours to remove, and the
+//! user's library on the stick is theirs.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::silent::{self, ExitCode};
+use crate::{data_dir, debug_log, manifest, processes, uninstall};
+
+pub const MARKER: &str = ".portable";
+
+/// How long the app gets to quit after starting the uninstall itself
+const APP_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Mark `install_path` as a portable install
+pub fn mark(install_path: &str) -> Result<(), String> {
+    std::fs::write(Path::new(install_path).join(MARKER), b"")
+        .map_err(|e| format!("Failed to write portable marker: {}", e))
+}
+
+pub fn is_portable(install_path: &str) -> bool {
+    Path::new(install_path).join(MARKER).is_file()
+        || manifest::load(install_path).and_then(|m| m.options).is_some_and(|o| o.portable)
+}
+
+/// Remove the portable install at `install_path`. Returns the number of files removed.
+pub fn uninstall(install_path: &str) -> Result<usize, String> {
+    if !is_portable(install_path) {
+        return Err(format!("{} is not a portable install; use --uninstall", install_path));
+    }
+    let manifest = manifest::load(install_path).ok_or("No install manifest found; nothing to uninstall")?;
+    let root = Path::new(install_path);
+    // A data folder kept next to the app stays, even if an install once wrote into it
+    let data = data_dir::resolve(Some(install_path)).ok();
+    let files: Vec<String> = manifest
+        .all_files()
+        .into_iter()
+        .filter(|f| !data.as_ref().is_some_and(|data| root.join(f).starts_with(data)))
+        .collect();
+    debug_log(&format!("Removing portable install at {} ({} files)", install_path, files.len()));
+    uninstall::remove_files(root, &files);
+    uninstall::remove_file_if_exists(&manifest::manifest_path(install_path));
+    uninstall::remove_file_if_exists(&root.join(MARKER));
+    Ok(files.len())
+}
+
+/// `--uninstall-portable`: JSON progress on stdout and a structured exit code
+pub fn run(install_path: &str) -> ! {
+    let remaining = processes::close_gracefully(install_path, APP_EXIT_TIMEOUT);
+    if !remaining.is_empty() {
+        silent::app_running(&remaining);
+    }
+    if let Err(e) = uninstall(install_path) {
+        debug_log(&format!("Portable uninstall failed: {}", e));
+        let code = if manifest::load(install_path).is_none() { ExitCode::NotInstalled } else { ExitCode::Failed };
+        silent::finish(code, Some(&e));
+    }
+    // Only the uninstaller copy inside the install removes itself
+    if std::env::current_exe().is_ok_and(|exe| exe.starts_with(install_path)) {
+        uninstall::schedule_self_delete(install_path);
+    }
+    silent::finish(ExitCode::Success, None)
+}
@@ -0,0 +1,203 @@
+//! Copies of the app (and its helpers) still running from the install directory, which
+//! would hold files open through an install. They are asked to close the way a user
+//! would close them, by posting WM_CLOSE to their windows (SIGTERM elsewhere), and
+//! whatever is still running after that is reported back so the user can deal with it,
+//! or terminated when they chose that (`--kill-timeout`, or the installer's prompt).
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::debug_log;
+
+/// How often we check whether the processes are gone
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long terminated processes get to disappear
+const FORCE_CLOSE_WAIT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningProcess {
+    pub pid: u32,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// True when `exe` is inside `dir`; paths are compared without regard to case on Windows
+fn is_inside(exe: &Path, dir: &Path) -> bool {
+    if cfg!(windows) {
+        let exe = exe.to_string_lossy().to_lowercase();
+        let dir = dir.to_string_lossy().trim_end_matches(['\\', '/']).to_lowercase();
+        exe.strip_prefix(&dir).is_some_and(|rest| rest.starts_with(['\\', '/']))
+    } else {
+        exe.starts_with(dir)
+    }
+}
+
+/// Processes other than this one whose executable lives under `install_path`
+pub fn running_in(install_path: &str) -> Vec<RunningProcess> {
+    let dir = Path::new(install_path);
+    let own = std::process::id();
+    list().into_iter().filter(|p| p.pid != own && is_inside(&p.path, dir)).collect()
+}
+
+#[cfg(windows)]
+fn list() -> Vec<RunningProcess> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let mut processes = Vec::new();
+    let Ok(snapshot) = (unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) }) else { return processes };
+    let mut entry = PROCESSENTRY32W { dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32, ..Default::default() };
+    let mut next = unsafe { Process32FirstW(snapshot, &mut entry) };
+    while next.is_ok() {
+        let pid = entry.th32ProcessID;
+        // The snapshot only has the file name; the full path needs the process itself
+        if let Ok(process) = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) } {
+            let mut buffer = [0u16; 1024];
+            let mut len = buffer.len() as u32;
+            if unsafe { QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut len) }.is_ok() {
+                let name_len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+                processes.push(RunningProcess {
+                    pid,
+                    name: String::from_utf16_lossy(&entry.szExeFile[..name_len]),
+                    path: PathBuf::from(String::from_utf16_lossy(&buffer[..len as usize])),
+                });
+            }
+            let _ = unsafe { CloseHandle(process) };
+        }
+        next = unsafe { Process32NextW(snapshot, &mut entry) };
+    }
+    let _ = unsafe { CloseHandle(snapshot) };
+    processes
+}
+
+#[cfg(target_os = "linux")]
+fn list() -> Vec<RunningProcess> {
+    let Ok(entries) = std::fs::read_dir("/proc") else { return Vec::new() };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse().ok()?;
+            // Unreadable for other users' processes, which can't be ours anyway
+            let path = std::fs::read_link(entry.path().join("exe")).ok()?;
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            Some(RunningProcess { pid, name, path })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn list() -> Vec<RunningProcess> {
+    Vec::new()
+}
+
+/// Ask every window belonging to `processes` to close
+#[cfg(windows)]
+fn request_close(processes: &[RunningProcess]) {
+    use windows::core::BOOL;
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{EnumWindows, GetWindowThreadProcessId, PostMessageW, WM_CLOSE};
+
+    unsafe extern "system" fn close_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let pids = unsafe { &*(lparam.0 as *const Vec<u32>) };
+        let mut pid = 0;
+        unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+        if pids.contains(&pid) {
+            let _ = unsafe { PostMessageW(Some(hwnd), WM_CLOSE, WPARAM(0), LPARAM(0)) };
+        }
+        true.into()
+    }
+
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    let _ = unsafe { EnumWindows(Some(close_window), LPARAM(&pids as *const Vec<u32> as isize)) };
+}
+
+#[cfg(not(windows))]
+fn request_close(processes: &[RunningProcess]) {
+    for process in processes {
+        let _ = std::process::Command::new("kill").arg("-TERM").arg(process.pid.to_string()).status();
+    }
+}
+
+/// End `processes` outright, for those that ignored `request_close`
+#[cfg(windows)]
+fn terminate(processes: &[RunningProcess]) {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    for process in processes {
+        match unsafe { OpenProcess(PROCESS_TERMINATE, false, process.pid) } {
+            Ok(handle) => {
+                if let Err(e) = unsafe { TerminateProcess(handle, 1) } {
+                    debug_log(&format!("Failed to terminate {} ({}): {}", process.name, process.pid, e));
+                }
+                let _ = unsafe { CloseHandle(handle) };
+            }
+            Err(e) => debug_log(&format!("Cannot open {} ({}) to terminate it: {}", process.name, process.pid, e)),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn terminate(processes: &[RunningProcess]) {
+    for process in processes {
+        let _ = std::process::Command::new("kill").arg("-KILL").arg(process.pid.to_string()).status();
+    }
+}
+
+/// Wait up to `timeout` for every process under `install_path` to exit. Returns the ones
+/// still running.
+fn wait_for_exit(install_path: &str, timeout: Duration) -> Vec<RunningProcess> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = running_in(install_path);
+        if remaining.is_empty() || Instant::now() >= deadline {
+            return remaining;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Ask the app's processes under `install_path` to close and wait up to `timeout` for them
+/// to exit. Returns the ones still running.
+pub fn close_gracefully(install_path: &str, timeout: Duration) -> Vec<RunningProcess> {
+    let running = running_in(install_path);
+    if running.is_empty() {
+        return running;
+    }
+    debug_log(&format!("Asking {} running process(es) to close: {:?}", running.len(), running));
+    request_close(&running);
+    let remaining = wait_for_exit(install_path, timeout);
+    if !remaining.is_empty() {
+        debug_log(&format!("Still running: {:?}", remaining));
+    }
+    remaining
+}
+
+/// Terminate the processes left by `close_gracefully`. Returns any that survive even that
+/// (another user's elevated copy, say).
+pub fn force_close(install_path: &str, processes: &[RunningProcess]) -> Vec<RunningProcess> {
+    if processes.is_empty() {
+        return Vec::new();
+    }
+    debug_log(&format!("Terminating {} process(es) that didn't close", processes.len()));
+    terminate(processes);
+    wait_for_exit(install_path, FORCE_CLOSE_WAIT)
+}
+
+/// Parse `--kill-timeout=<secs>` (or `--kill-timeout <secs>`): how long a silent update
+/// lets the app close before terminating it
+pub fn parse_kill_timeout(args: &[String]) -> Option<Duration> {
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        arg.strip_prefix("--kill-timeout=")
+            .map(str::to_string)
+            .or_else(|| (arg == "--kill-timeout").then(|| args.get(i + 1).cloned()).flatten())
+    })?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
@@ -0,0 +1,65 @@
+//! Throughput and time-left estimates for long byte-counted stages (extraction, component
+//! downloads), from a rolling window of recent samples so a slow start or a burst of
+//! small files doesn't skew the figure for long.
+
+#[cfg(feature = "gui")]
+use std::collections::VecDeque;
+#[cfg(feature = "gui")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "gui")]
+/// How far back samples count towards the rate
+const WINDOW: Duration = Duration::from_secs(5);
+#[cfg(feature = "gui")]
+/// Minimum time between two reported snapshots, so the UI isn't flooded with events
+const INTERVAL: Duration = Duration::from_millis(250);
+
+#[cfg(feature = "gui")]
+/// Where a byte-counted stage stands, as sent to the frontend
+#[derive(Clone, Copy, serde::Serialize)]
+pub struct Transfer {
+    pub bytes_processed: u64,
+    pub bytes_total: Option<u64>,
+    pub bytes_per_second: Option<u64>,
+    pub seconds_remaining: Option<u64>,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Default)]
+pub struct Throughput {
+    samples: VecDeque<(Instant, u64)>,
+    last_report: Option<Instant>,
+}
+
+#[cfg(feature = "gui")]
+impl Throughput {
+    /// Record that `done` bytes are processed so far. Returns a snapshot at most every
+    /// `INTERVAL`, and always once the stage is complete.
+    pub fn sample(&mut self, done: u64, total: Option<u64>) -> Option<Transfer> {
+        let now = Instant::now();
+        let finished = total.is_some_and(|t| done >= t);
+        if !finished && self.last_report.is_some_and(|t| now - t < INTERVAL) {
+            return None;
+        }
+        self.last_report = Some(now);
+        self.samples.push_back((now, done));
+        while self.samples.len() > 2 && self.samples.front().is_some_and(|(t, _)| now - *t > WINDOW) {
+            self.samples.pop_front();
+        }
+
+        let bytes_per_second = self.samples.front().and_then(|&(start, start_done)| {
+            let elapsed = (now - start).as_secs_f64();
+            (elapsed > 0.0).then(|| (done.saturating_sub(start_done) as f64 / elapsed) as u64)
+        });
+        let seconds_remaining = match (total, bytes_per_second) {
+            (Some(total), Some(rate)) if rate > 0 => Some(total.saturating_sub(done).div_ceil(rate)),
+            _ => None,
+        };
+        Some(Transfer { bytes_processed: done, bytes_total: total, bytes_per_second, seconds_remaining })
+    }
+}
+
+/// `done` out of `total` as a whole percentage; 0 while the total is unknown
+pub fn percent(done: u64, total: Option<u64>) -> u32 {
+    total.filter(|t| *t > 0).map(|t| (done.min(t) * 100 / t) as u32).unwrap_or(0)
+}
@@ -0,0 +1,43 @@
+//! Antivirus quarantine of freshly installed files. A scanner that flags the main executable
+//! or `app.asar` removes or rewrites it moments after it is written; the install used to
+//! report success anyway and leave a shortcut to a file that no longer exists. The files
+//! the app can't start without are checked right after extraction and again after the
+//! verification pass, and a failure names them and suggests an exclusion.
+
+use std::path::Path;
+
+use crate::payload::PayloadManifest;
+use crate::{debug_log, durable, extract, pending};
+
+/// The app's essential files among `files` that are gone from `install_path`, or, when
+/// `manifest` is given, no longer match its hashes
+pub fn affected(install_path: &str, files: &[String], manifest: Option<&PayloadManifest>) -> Vec<String> {
+    let root = Path::new(install_path);
+    let affected: Vec<String> = files
+        .iter()
+        .filter(|f| durable::is_critical(f))
+        // A locked file keeps the old copy until its pending one moves in
+        .filter(|f| !pending::is_deferred(&root.join(f)))
+        .filter(|f| {
+            let path = root.join(f);
+            match manifest.and_then(|m| m.get(f)) {
+                Some(expected) => !extract::is_unchanged(&path, expected),
+                None => !path.is_file(),
+            }
+        })
+        .cloned()
+        .collect();
+    if !affected.is_empty() {
+        debug_log(&format!("Essential files removed or changed after extraction: {:?}", affected));
+    }
+    affected
+}
+
+pub fn message(install_path: &str, files: &[String]) -> String {
+    format!(
+        "{} disappeared or changed right after installation, most likely quarantined by antivirus software. \
+         Restore them from quarantine or add an exclusion for {}, then install again.",
+        files.join(", "),
+        install_path
+    )
+}
@@ -0,0 +1,67 @@
+//! Minimal HKEY_CURRENT_USER access. The installer is per-user, so nothing here
+//! writes to HKLM or needs elevation; machine settings are only ever read.
+
+#[cfg(windows)]
+mod imp {
+    use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+    use winreg::types::FromRegValue;
+    use winreg::RegKey;
+
+    fn hkcu() -> RegKey {
+        RegKey::predef(HKEY_CURRENT_USER)
+    }
+
+    fn create(subkey: &str) -> Result<RegKey, String> {
+        hkcu()
+            .create_subkey(subkey)
+            .map(|(key, _)| key)
+            .map_err(|e| format!("Failed to open HKCU\\{}: {}", subkey, e))
+    }
+
+    pub fn set_string(subkey: &str, name: Option<&str>, value: &str) -> Result<(), String> {
+        // An empty name addresses the key's default value
+        create(subkey)?
+            .set_value(name.unwrap_or(""), &value)
+            .map_err(|e| format!("Failed to write HKCU\\{}: {}", subkey, e))
+    }
+
+    pub fn set_dword(subkey: &str, name: &str, value: u32) -> Result<(), String> {
+        create(subkey)?
+            .set_value(name, &value)
+            .map_err(|e| format!("Failed to write HKCU\\{}\\{}: {}", subkey, name, e))
+    }
+
+    fn get_in<T: FromRegValue>(root: &RegKey, subkey: &str, name: &str) -> Option<T> {
+        root.open_subkey(subkey).ok()?.get_value(name).ok()
+    }
+
+    /// A string value, or None when the key or value is missing or not a string
+    pub fn get_string(subkey: &str, name: &str) -> Option<String> {
+        get_in(&hkcu(), subkey, name)
+    }
+
+    /// A string from HKEY_LOCAL_MACHINE, or None when it is missing or not a string
+    pub fn get_machine_string(subkey: &str, name: &str) -> Option<String> {
+        get_in(&RegKey::predef(HKEY_LOCAL_MACHINE), subkey, name)
+    }
+
+    /// A DWORD from HKEY_LOCAL_MACHINE, or None when it is missing or unreadable
+    pub fn get_machine_dword(subkey: &str, name: &str) -> Option<u32> {
+        get_in(&RegKey::predef(HKEY_LOCAL_MACHINE), subkey, name)
+    }
+
+    pub fn key_exists(subkey: &str) -> bool {
+        hkcu().open_subkey(subkey).is_ok()
+    }
+
+    /// Delete a key and everything below it; a missing key is not an error
+    pub fn delete_tree(subkey: &str) -> Result<(), String> {
+        match hkcu().delete_subkey_all(subkey) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to delete HKCU\\{}: {}", subkey, e)),
+        }
+    }
+}
+
+pub use imp::*;
@@ -0,0 +1,124 @@
+//! The signed release manifest the app checked before downloading an update, passed on
+//! with `--release-manifest <path>`. The installer holds it to the same rules against its
+//! own record, which survives a reset of the app's data: a manifest past its expiry (a
+//! mirror freezing users on an old release) or with a lower sequence than one already
+//! installed for its channel (a replayed manifest pointing back at a vulnerable release)
+//! is refused, and so is an installer that isn't the one the manifest lists. Updates from
+//! a folder the organization keeps skip the expiry, since its copy isn't re-signed. An
+//! update without a manifest is refused outright.
+//!
+//! The signature itself is verified by the app; see docs/update-signing.md.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::known_folders::{self, KnownFolder};
+use crate::{branding, debug_log, extract, hash};
+
+pub const ARG: &str = "--release-manifest";
+
+const STATE_FILE: &str = "release-state.json";
+/// Appended to the app's data folder name for the folder holding the installer's record
+const STATE_DIR_SUFFIX: &str = "-installer";
+
+#[derive(serde::Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    pub channel: String,
+    pub sequence: u64,
+    pub expires: String,
+    pub files: HashMap<String, ReleaseFile>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReleaseFile {
+    pub size: u64,
+    /// Base64
+    pub sha512: String,
+}
+
+/// The highest sequence installed per channel
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct State {
+    #[serde(default)]
+    sequences: HashMap<String, u64>,
+}
+
+/// Beside the app's data folder rather than in it: the app keeps a record of its own in
+/// userData, and resetting the app's data mustn't reset the installer's
+fn state_path() -> Option<PathBuf> {
+    let appdata = known_folders::get(KnownFolder::RoamingAppData)?;
+    Some(appdata.join(format!("{}{}", branding::get().data_dir_name, STATE_DIR_SUFFIX)).join(STATE_FILE))
+}
+
+fn load_state() -> State {
+    state_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn record(manifest: &ReleaseManifest) -> Result<(), String> {
+    let path = state_path().ok_or("APPDATA not found")?;
+    let mut state = load_state();
+    let highest = state.sequences.entry(manifest.channel.clone()).or_default();
+    *highest = (*highest).max(manifest.sequence);
+    let json = serde_json::to_vec_pretty(&state).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    }
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// `2026-01-31T12:00:00.000Z`, as the signing script writes `expires`
+fn parse_timestamp(value: &str) -> Option<SystemTime> {
+    let (date, time) = value.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.split('.').next()?.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    extract::utc_time(year, month, day, hour * 3600 + minute * 60 + second)
+}
+
+/// The `--release-manifest` argument, if given
+pub fn from_args(args: &[String]) -> Option<&Path> {
+    args.iter().position(|a| a == ARG).and_then(|i| args.get(i + 1)).map(Path::new)
+}
+
+/// Check the manifest at `path` against `installer` (the file the app downloaded) and the
+/// version it carries, and record its sequence once it passes
+pub fn check(path: &Path, installer: &Path, payload_version: Option<&str>, enforce_expiry: bool) -> Result<(), String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let manifest: ReleaseManifest =
+        serde_json::from_str(&json).map_err(|e| format!("Release manifest is malformed: {}", e))?;
+
+    let expires = parse_timestamp(&manifest.expires)
+        .ok_or_else(|| format!("Release manifest has an unreadable expiry: {}", manifest.expires))?;
+    if enforce_expiry && expires <= SystemTime::now() {
+        return Err(format!("The release manifest for {} expired on {}", manifest.version, manifest.expires));
+    }
+    let highest = load_state().sequences.get(&manifest.channel).copied().unwrap_or(0);
+    if manifest.sequence < highest {
+        return Err(format!(
+            "The release manifest for {} is older than one already installed ({} < {})",
+            manifest.version, manifest.sequence, highest
+        ));
+    }
+    if let Some(version) = payload_version.filter(|v| *v != manifest.version) {
+        return Err(format!("This installer carries {} but the release manifest is for {}", version, manifest.version));
+    }
+
+    let name = installer.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let listed = manifest.files.get(&name).ok_or_else(|| format!("{} is not listed in the release manifest", name))?;
+    let size = std::fs::metadata(installer).map_err(|e| format!("Failed to read {:?}: {}", installer, e))?.len();
+    if size != listed.size || hash::sha512_file_base64(installer)? != listed.sha512 {
+        return Err(format!("{} does not match the release manifest", name));
+    }
+
+    debug_log(&format!(
+        "Release manifest {} ({} #{}) accepted, expires {}",
+        manifest.version, manifest.channel, manifest.sequence, manifest.expires
+    ));
+    record(&manifest)
+}
@@ -0,0 +1,100 @@
+//! Moving an existing install to another directory (`--relocate <dir>` or the
+//! `relocate_install` command), for users who need to free up their system drive.
+//!
+//! Files are copied first and the old copy is only removed once the new one is
+//! complete, so a failure part-way leaves the original install working.
+
+use std::path::Path;
+
+use crate::{
+    acl, arp, block_clone, branding, debug_log, defender, file_assoc, firewall, install_record, manifest, shortcuts,
+    uninstall,
+};
+
+fn copy_file(from_root: &Path, to_root: &Path, file: &str) -> Result<(), String> {
+    let source = from_root.join(file);
+    let target = to_root.join(file);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    block_clone::copy(&source, &target).map_err(|e| format!("Failed to copy {}: {}", file, e))?;
+    Ok(())
+}
+
+pub fn relocate(from: &str, to: &str) -> Result<(), String> {
+    let from_root = Path::new(from);
+    let to_root = Path::new(to);
+    if from_root == to_root {
+        return Err("The install is already in that folder".to_string());
+    }
+    if std::fs::read_dir(to_root).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!("{} is not empty", to));
+    }
+    let mut manifest = manifest::load(from).ok_or("No install manifest found; cannot move this install")?;
+    let mut to_copy = manifest.all_files();
+    debug_log(&format!("Relocating {} files from {} to {}", to_copy.len(), from, to));
+
+    // 1. Copy everything the install consists of, including the uninstaller
+    let uninstaller = branding::get().uninstaller_exe();
+    if from_root.join(&uninstaller).exists() {
+        to_copy.push(uninstaller.clone());
+    }
+    std::fs::create_dir_all(to_root).map_err(|e| e.to_string())?;
+    if acl::is_per_machine(to_root) {
+        acl::restrict(to_root)?;
+    }
+    if let Err(e) = to_copy.iter().try_for_each(|file| copy_file(from_root, to_root, file)) {
+        uninstall::remove_files(to_root, &to_copy);
+        let _ = std::fs::remove_dir(to_root);
+        return Err(e);
+    }
+
+    // 2. Point shortcuts and file associations at the new location
+    let mut options = manifest.options.clone().unwrap_or_default();
+    options.install_path = to.to_string();
+    for lnk in &manifest.shortcuts {
+        uninstall::remove_file_if_exists(lnk);
+    }
+    manifest.shortcuts = if options.portable { Vec::new() } else { shortcuts::create_shortcuts(to, &options)? };
+    if options.register_file_associations && !options.portable {
+        if let Err(e) = file_assoc::register(to, &options) {
+            debug_log(&format!("File association failed: {}", e));
+        }
+    }
+    if options.firewall_rule && !options.portable {
+        if let Err(e) = firewall::request_rule(to) {
+            debug_log(&format!("Firewall rule failed: {}", e));
+        }
+    }
+    if options.defender_exclusion && !options.portable {
+        if let Err(e) = defender::request_exclusion(to, Some(from)) {
+            debug_log(&format!("Defender exclusion failed: {}", e));
+        }
+    }
+
+    // 3. The manifest carries the install path future updates and uninstall use
+    let portable = options.portable;
+    manifest.options = Some(options);
+    manifest::save(to, &manifest)?;
+    if !portable {
+        if let Err(e) = arp::register(to, manifest.version.as_deref()) {
+            debug_log(&format!("Apps & Features entry failed: {}", e));
+        }
+        if let Err(e) = install_record::save(to, manifest.version.as_deref(), manifest.options.as_ref()) {
+            debug_log(&format!("Recording the install failed: {}", e));
+        }
+    }
+
+    // 4. Only now remove the old copy
+    uninstall::remove_files(from_root, &to_copy);
+    // On macOS both installs share one manifest, which was just written for the new one
+    if manifest::manifest_path(from) != manifest::manifest_path(to) {
+        uninstall::remove_file_if_exists(&manifest::manifest_path(from));
+    }
+    if let Err(e) = std::fs::remove_dir(from_root) {
+        debug_log(&format!("Left {} in place: {}", from, e));
+    }
+
+    debug_log("Relocation complete");
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! What the app needs from the machine. The app ships 64-bit builds only, and Electron no
+//! longer starts on Windows before 10; installing on such a system used to succeed and
+//! leave an app that crashes on launch. The check runs before anything is extracted, so
+//! the installer stops with a message saying why, and silent runs exit with their own code.
+
+use crate::{branding, debug_log, system_info};
+
+/// Windows 10 RTM, the oldest build Electron supports
+pub const MIN_WINDOWS_BUILD: u32 = 10240;
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Unsupported {
+    /// A 32-bit processor or a 32-bit edition of Windows
+    #[serde(rename = "not_64_bit")]
+    Not64Bit,
+    WindowsTooOld { build: u32, minimum: u32 },
+}
+
+impl Unsupported {
+    pub fn message(&self) -> String {
+        let app = &branding::get().app_name;
+        match self {
+            Unsupported::Not64Bit => format!(
+                "{} needs a 64-bit version of Windows. This PC runs a 32-bit version, so the app can't be installed.",
+                app
+            ),
+            Unsupported::WindowsTooOld { build, minimum } => format!(
+                "{} needs Windows 10 or later (build {} or newer). This PC runs build {}; update Windows to install the app.",
+                app, minimum, build
+            ),
+        }
+    }
+}
+
+/// Whether this machine can run the app
+pub fn check() -> Result<(), Unsupported> {
+    let result = check_system();
+    if let Err(reason) = &result {
+        debug_log(&format!("Unsupported system: {:?}", reason));
+    }
+    result
+}
+
+fn check_system() -> Result<(), Unsupported> {
+    // IsWow64Process2 is missing before Windows 10 1511; then the installer's own
+    // architecture is the best guess
+    let arch = system_info::native_arch().unwrap_or(std::env::consts::ARCH);
+    if arch == "x86" {
+        return Err(Unsupported::Not64Bit);
+    }
+    // Only Windows reports a build
+    let build = system_info::os_build().and_then(|b| b.split('.').next().and_then(|b| b.parse::<u32>().ok()));
+    match build {
+        Some(build) if build < MIN_WINDOWS_BUILD => Err(Unsupported::WindowsTooOld { build, minimum: MIN_WINDOWS_BUILD }),
+        _ => Ok(()),
+    }
+}
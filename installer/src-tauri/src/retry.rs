@@ -0,0 +1,172 @@
+//! Retries for network calls that fail transiently: manifest fetches and payload
+//! downloads. The wait before each retry grows exponentially up to a cap and is picked at
+//! random below it ("full jitter"), so installs that failed together don't all come back
+//! at once. A server asking for a longer wait with `Retry-After` gets it, unless it asks
+//! for more than the cap, in which case the call fails right away.
+//!
+//! The deployment policy can tune the attempts and delays with `"retry"` in
+//! `installer-policy.json`; the app gets the same settings in `update-policy.json`.
+
+use std::hash::BuildHasher;
+use std::time::{Duration, SystemTime};
+
+use crate::{debug_log, extract, policy};
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Tries in all, the first included
+    pub attempts: u32,
+    /// Cap on the wait before the first retry; doubled for each one after it
+    pub base_delay_ms: u64,
+    /// Cap on any single wait
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { attempts: 4, base_delay_ms: 1000, max_delay_ms: 30_000 }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before retry number `retry` (from 1)
+    fn delay(&self, retry: u32) -> Duration {
+        let cap = self.base_delay_ms.saturating_mul(1 << (retry - 1).min(20)).min(self.max_delay_ms);
+        let random = std::collections::hash_map::RandomState::new().hash_one(retry);
+        Duration::from_millis(random % (cap + 1))
+    }
+}
+
+/// Why an attempt failed, and whether trying again could help
+pub struct Failure {
+    message: String,
+    transient: bool,
+    retry_after: Option<Duration>,
+}
+
+impl Failure {
+    pub fn transient(message: String, retry_after: Option<Duration>) -> Self {
+        Failure { message, transient: true, retry_after }
+    }
+
+    pub fn permanent(message: String) -> Self {
+        Failure { message, transient: false, retry_after: None }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.transient
+    }
+}
+
+/// Errors are final unless marked transient: a bad URL, a pin mismatch, a hash that
+/// doesn't match or a full disk won't go away by asking again
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure::permanent(message)
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// HTTP statuses that say the server may answer differently in a moment
+pub fn is_transient_status(status: u32) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// A `Retry-After` header: a number of seconds or an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    // IMF-fixdate, e.g. "Wed, 21 Oct 2015 07:28:00 GMT"
+    let mut parts = value.split_whitespace().skip(1);
+    let day = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"]
+        .iter()
+        .position(|m| *m == month_name)? as i64
+        + 1;
+    let year = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    let at = extract::utc_time(year, month, day, hour * 3600 + minute * 60 + second)?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// Call `attempt` until it succeeds, fails for good or runs out of tries, logging each
+/// failure as `what`
+pub fn run<T>(what: &str, mut attempt: impl FnMut() -> Result<T, Failure>) -> Result<T, String> {
+    let retry = policy::get().retry.unwrap_or_default();
+    let attempts = retry.attempts.max(1);
+    let mut n = 0;
+    loop {
+        n += 1;
+        let failure = match attempt() {
+            Ok(value) => {
+                if n > 1 {
+                    debug_log(&format!("{} succeeded on attempt {}", what, n));
+                }
+                return Ok(value);
+            }
+            Err(failure) => failure,
+        };
+        if !failure.transient || n >= attempts {
+            debug_log(&format!("{} failed (attempt {}/{}), giving up: {}", what, n, attempts, failure));
+            return Err(failure.message);
+        }
+        let delay = match failure.retry_after {
+            Some(after) if after > Duration::from_millis(retry.max_delay_ms) => {
+                debug_log(&format!("{} failed and the server asked to wait {}s, giving up: {}", what, after.as_secs(), failure));
+                return Err(failure.message);
+            }
+            after => retry.delay(n).max(after.unwrap_or_default()),
+        };
+        debug_log(&format!("{} failed (attempt {}/{}), retrying in {}ms: {}", what, n, attempts, delay.as_millis(), failure));
+        std::thread::sleep(delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_in_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn retry_after_as_http_date() {
+        // Already passed: retry right away
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let wait = parse_retry_after("Fri, 01 Jan 2100 00:00:00 GMT").unwrap();
+        assert!(wait > Duration::from_secs(365 * 24 * 3600));
+    }
+
+    #[test]
+    fn retry_after_garbage() {
+        for value in ["", "soon", "-5", "1.5", "Wed, 21 Foo 2015 07:28:00 GMT", "Wed, 21 Oct 2015 07:28 GMT"] {
+            assert_eq!(parse_retry_after(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn delays_stay_within_the_growing_cap() {
+        let policy = RetryPolicy { attempts: 10, base_delay_ms: 100, max_delay_ms: 1000 };
+        for retry in 1..=10 {
+            let cap = (100u64 << (retry - 1)).min(1000);
+            for _ in 0..50 {
+                assert!(policy.delay(retry) <= Duration::from_millis(cap), "retry {}", retry);
+            }
+        }
+        let none = RetryPolicy { attempts: 3, base_delay_ms: 0, max_delay_ms: 0 };
+        assert_eq!(none.delay(1), Duration::ZERO);
+    }
+}
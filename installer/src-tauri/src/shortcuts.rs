@@ -0,0 +1,295 @@
+//! Desktop and Start Menu shortcuts.
+//!
+//! Links are written through IShellLinkW rather than WScript.Shell so they can carry
+//! the app's AppUserModelID; without it Windows groups the app's taskbar button, jump
+//! list and toast notifications under a generic Electron identity.
+//!
+//! On Linux the same links are XDG desktop entries, which also declare the URL scheme
+//! and backup file type the app opens. macOS has no Start Menu: the bundle in
+//! Applications is what Launchpad shows, and the desktop shortcut is a symlink to it.
+//!
+//! Besides the app itself, the payload can offer extra Start Menu shortcuts that open
+//! the app at a given section (`shortcuts.json`, sidecar or archive root).
+
+use std::path::{Path, PathBuf};
+
+use crate::branding;
+use crate::known_folders::{self, KnownFolder};
+use crate::manifest::InstallOptions;
+use crate::network_share;
+use crate::payload;
+
+pub const SHORTCUTS_FILE: &str = "shortcuts.json";
+
+/// A shortcut launching the app with deep-link arguments
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtraShortcut {
+    pub id: String,
+    /// Shortcut name, e.g. "Mangyomi Library"
+    pub name: String,
+    /// Passed to the app, e.g. `--route=library`
+    pub arguments: String,
+    /// Preselected in the installer UI
+    pub selected_by_default: bool,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct ShortcutsManifest {
+    #[serde(default)]
+    shortcuts: Vec<ExtraShortcut>,
+}
+
+/// The extra shortcuts the payload offers
+pub fn read_extra_shortcuts(payload_path: &Path, is_7z: bool) -> Vec<ExtraShortcut> {
+    payload::read_json::<ShortcutsManifest>(payload_path, is_7z, SHORTCUTS_FILE).unwrap_or_default().shortcuts
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+struct Link<'a> {
+    lnk_path: PathBuf,
+    /// Desktop entry name on Linux, the tooltip on Windows
+    name: String,
+    target: PathBuf,
+    arguments: Option<String>,
+    app_user_model_id: Option<&'a str>,
+}
+
+/// The Start Menu folder name the options ask for; empty for the top level
+fn start_menu_folder(options: &InstallOptions) -> &str {
+    options.start_menu_folder.as_deref().map(str::trim).unwrap_or(&branding::get().install_dir_name)
+}
+
+/// The Start Menu folder holding our shortcuts, in the current user's or the all-users
+/// Start Menu
+pub fn start_menu_dir(options: &InstallOptions) -> Result<PathBuf, String> {
+    let root = if options.all_users_start_menu { KnownFolder::CommonPrograms } else { KnownFolder::Programs };
+    let programs = known_folders::get(root).ok_or("Start Menu folder not found")?;
+    let folder = Path::new(start_menu_folder(options));
+    // A nested folder is fine; anything that would leave the Start Menu isn't
+    if !folder.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+        return Err(format!("Invalid Start Menu folder: {}", folder.display()));
+    }
+    Ok(programs.join(folder))
+}
+
+/// Remove the Start Menu folder the shortcuts went into once it's empty; the top level
+/// is left alone
+pub fn remove_start_menu_dir(options: &InstallOptions) {
+    if start_menu_folder(options).is_empty() {
+        return;
+    }
+    if let Ok(dir) = start_menu_dir(options) {
+        let _ = std::fs::remove_dir(dir); // Only succeeds once it's empty
+    }
+}
+
+/// Apply `--start-menu-folder=<name>` (empty for the top level) and
+/// `--all-users-start-menu`; returns whether either was given
+pub fn parse_start_menu_args(args: &[String], options: &mut InstallOptions) -> bool {
+    let folder = args.iter().find_map(|arg| arg.strip_prefix("--start-menu-folder="));
+    let all_users = args.iter().any(|arg| arg == "--all-users-start-menu");
+    if let Some(folder) = folder {
+        options.start_menu_folder = Some(folder.to_string());
+    }
+    if all_users {
+        options.all_users_start_menu = true;
+    }
+    folder.is_some() || all_users
+}
+
+/// The shortcuts `options` ask for. With `assume_installed` the app and uninstaller are
+/// taken to exist, for planning before anything is extracted.
+fn links(install_path: &str, options: &InstallOptions, assume_installed: bool) -> Result<Vec<Link<'static>>, String> {
+    let branding = branding::get();
+    // A mapped drive letter only exists in the session that mapped it
+    let root = network_share::to_unc(Path::new(install_path));
+    let exe_path = root.join(&branding.exe_name);
+    if !assume_installed && !exe_path.exists() {
+        return Ok(Vec::new()); // Should warn?
+    }
+    let uninstaller = root.join(branding.uninstaller_exe());
+
+    let mut links = Vec::new();
+    if options.desktop_shortcut {
+        let desktop_dir = known_folders::get(KnownFolder::Desktop).ok_or("Desktop folder not found")?;
+        links.push(Link {
+            lnk_path: desktop_dir.join(branding.shortcut_name()),
+            name: branding.app_name.clone(),
+            target: exe_path.clone(),
+            arguments: None,
+            app_user_model_id: Some(&branding.app_user_model_id),
+        });
+    }
+    if (options.start_menu_shortcut || options.uninstall_shortcut) && !cfg!(target_os = "macos") {
+        let start_menu_dir = start_menu_dir(options)?;
+        if options.start_menu_shortcut {
+            links.push(Link {
+                lnk_path: start_menu_dir.join(branding.shortcut_name()),
+                name: branding.app_name.clone(),
+                target: exe_path.clone(),
+                arguments: None,
+                app_user_model_id: Some(&branding.app_user_model_id),
+            });
+            for extra in &options.extra_shortcuts {
+                links.push(Link {
+                    lnk_path: start_menu_dir.join(branding.named_shortcut(&extra.name)),
+                    name: extra.name.clone(),
+                    target: exe_path.clone(),
+                    arguments: Some(extra.arguments.clone()),
+                    app_user_model_id: Some(&branding.app_user_model_id),
+                });
+            }
+        }
+        if options.uninstall_shortcut && (assume_installed || uninstaller.exists()) {
+            links.push(Link {
+                lnk_path: start_menu_dir.join(branding.uninstall_shortcut_name()),
+                name: format!("Uninstall {}", branding.app_name),
+                target: uninstaller,
+                arguments: Some("--uninstall".to_string()),
+                app_user_model_id: None,
+            });
+        }
+    }
+    Ok(links)
+}
+
+/// The shortcut files `create_shortcuts` would write for a complete install
+pub fn planned_shortcuts(install_path: &str, options: &InstallOptions) -> Result<Vec<PathBuf>, String> {
+    Ok(links(install_path, options, true)?.into_iter().map(|l| l.lnk_path).collect())
+}
+
+/// Create the requested shortcuts, returning the .lnk files written
+pub fn create_shortcuts(install_path: &str, options: &InstallOptions) -> Result<Vec<PathBuf>, String> {
+    let links = links(install_path, options, false)?;
+    for link in &links {
+        if let Some(dir) = link.lnk_path.parent() {
+            std::fs::create_dir_all(dir).ok();
+        }
+        create_link(link, &network_share::to_unc(Path::new(install_path)))?;
+    }
+
+    Ok(links.into_iter().map(|l| l.lnk_path).collect())
+}
+
+#[cfg(windows)]
+fn create_link(link: &Link, working_dir: &Path) -> Result<(), String> {
+    let lnk_path = link.lnk_path.clone();
+    let target = link.target.clone();
+    let arguments = link.arguments.clone();
+    let description = link.name.clone();
+    let app_user_model_id = link.app_user_model_id.map(str::to_string);
+    let working_dir = working_dir.to_path_buf();
+
+    // COM needs an apartment; use a dedicated STA thread since the async runtime's
+    // worker threads may already be initialized differently
+    std::thread::spawn(move || unsafe {
+        use windows::core::{Interface, HSTRING};
+        use windows::Win32::Storage::EnhancedStorage::PKEY_AppUserModel_ID;
+        use windows::Win32::System::Com::StructuredStorage::{PropVariantClear, PROPVARIANT};
+        use windows::Win32::System::Com::{
+            CoCreateInstance, CoInitializeEx, CoUninitialize, IPersistFile, CLSCTX_INPROC_SERVER,
+            COINIT_APARTMENTTHREADED,
+        };
+        use windows::Win32::System::Variant::VT_LPWSTR;
+        use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+        use windows::Win32::UI::Shell::{IShellLinkW, SHStrDupW, ShellLink};
+
+        let initialized = CoInitializeEx(None, COINIT_APARTMENTTHREADED).is_ok();
+
+        let result = (|| -> windows::core::Result<()> {
+            let shell_link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            shell_link.SetPath(&HSTRING::from(target.as_path()))?;
+            shell_link.SetWorkingDirectory(&HSTRING::from(working_dir.as_path()))?;
+            shell_link.SetDescription(&HSTRING::from(description.as_str()))?;
+            if let Some(arguments) = &arguments {
+                shell_link.SetArguments(&HSTRING::from(arguments.as_str()))?;
+            }
+            // Include IconLocation to ensure the shortcut icon appears correctly
+            shell_link.SetIconLocation(&HSTRING::from(target.as_path()), 0)?;
+
+            if let Some(app_user_model_id) = &app_user_model_id {
+                let store: IPropertyStore = shell_link.cast()?;
+                let mut value = PROPVARIANT::default();
+                (*value.Anonymous.Anonymous).vt = VT_LPWSTR;
+                (*value.Anonymous.Anonymous).Anonymous.pwszVal = SHStrDupW(&HSTRING::from(app_user_model_id.as_str()))?;
+                let set = store.SetValue(&PKEY_AppUserModel_ID, &value).and_then(|_| store.Commit());
+                let _ = PropVariantClear(&mut value);
+                set?;
+            }
+
+            let file: IPersistFile = shell_link.cast()?;
+            file.Save(&HSTRING::from(lnk_path.as_path()), true)
+        })();
+
+        if initialized {
+            CoUninitialize();
+        }
+        result.map_err(|e| format!("Failed to create {:?}: {}", lnk_path, e))
+    })
+    .join()
+    .map_err(|_| "Shortcut thread panicked".to_string())?
+}
+
+/// The desktop file ID of the Start Menu entry: its path under `applications/` with
+/// slashes turned into dashes, per the desktop entry spec
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn desktop_file_id(options: &InstallOptions) -> String {
+    let name = branding::get().shortcut_name();
+    match start_menu_folder(options).trim_matches('/') {
+        "" => name,
+        folder => format!("{}-{}", folder.replace('/', "-"), name),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn create_link(link: &Link, working_dir: &Path) -> Result<(), String> {
+    // Links point at the bundle; Finder launches it like the original
+    crate::uninstall::remove_file_if_exists(&link.lnk_path);
+    std::os::unix::fs::symlink(working_dir, &link.lnk_path).map_err(|e| format!("Failed to create {:?}: {}", link.lnk_path, e))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn create_link(link: &Link, working_dir: &Path) -> Result<(), String> {
+    let branding = branding::get();
+    let quote = |path: &Path| format!("\"{}\"", path.display().to_string().replace('\\', "\\\\").replace('"', "\\\""));
+    let is_app = link.app_user_model_id.is_some();
+    let exec = match (link.arguments.as_deref(), is_app) {
+        (Some(arguments), _) => format!("{} {}", quote(&link.target), arguments),
+        (None, true) => format!("{} %u", quote(&link.target)),
+        (None, false) => quote(&link.target),
+    };
+
+    let mut entry = format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nPath={}\nTerminal=false\n",
+        link.name,
+        exec,
+        working_dir.display()
+    );
+    let icon = working_dir.join("resources").join("icon.png");
+    if icon.is_file() {
+        entry.push_str(&format!("Icon={}\n", icon.display()));
+    }
+    if is_app {
+        entry.push_str(&format!("Categories=Graphics;Viewer;\nStartupWMClass={}\n", branding.app_name));
+        // Only the main entry handles links and backup files
+        if link.arguments.is_none() {
+            entry.push_str(&format!(
+                "MimeType=x-scheme-handler/{};{};\n",
+                branding.url_scheme,
+                crate::file_assoc::BACKUP_MIME_TYPE
+            ));
+        }
+    } else {
+        entry.push_str("Categories=Settings;\n");
+    }
+
+    std::fs::write(&link.lnk_path, entry).map_err(|e| format!("Failed to create {:?}: {}", link.lnk_path, e))?;
+    // Desktop environments only launch entries on the desktop that are executable
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(&link.lnk_path, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    if let Some(dir) = link.lnk_path.parent() {
+        let _ = std::process::Command::new("update-desktop-database").arg(dir).status();
+    }
+    Ok(())
+}
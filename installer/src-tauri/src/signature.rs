@@ -0,0 +1,60 @@
+//! Authenticode checks for installer binaries we are about to trust, such as a newer
+//! installer stub replacing the cached one.
+
+use std::path::Path;
+
+/// Verify the Authenticode signature of `path` against the machine's trusted roots,
+/// returning the signer's display name
+#[cfg(windows)]
+pub fn signer(path: &Path) -> Result<String, String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Security::Cryptography::{CertGetNameStringW, CERT_NAME_SIMPLE_DISPLAY_TYPE};
+    use windows::Win32::Security::WinTrust::*;
+
+    let file_path = HSTRING::from(path);
+    let mut file = WINTRUST_FILE_INFO {
+        cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(file_path.as_ptr()),
+        ..Default::default()
+    };
+    let mut data = WINTRUST_DATA {
+        cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_WHOLECHAIN,
+        dwUnionChoice: WTD_CHOICE_FILE,
+        Anonymous: WINTRUST_DATA_0 { pFile: &mut file },
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        ..Default::default()
+    };
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+
+    unsafe {
+        let status = WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+        // The state holds the signer chain; read it before releasing
+        let name = (status == 0).then(|| {
+            let provider = WTHelperProvDataFromStateData(data.hWVTStateData);
+            let signer = WTHelperGetProvSignerFromChain(provider, 0, false, 0);
+            if signer.is_null() || (*signer).csCertChain == 0 {
+                return String::new();
+            }
+            let cert = (*(*signer).pasCertChain).pCert;
+            let mut buffer = [0u16; 256];
+            let len = CertGetNameStringW(cert, CERT_NAME_SIMPLE_DISPLAY_TYPE, 0, None, Some(&mut buffer));
+            String::from_utf16_lossy(&buffer[..(len as usize).saturating_sub(1)])
+        });
+        data.dwStateAction = WTD_STATEACTION_CLOSE;
+        WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+
+        match name {
+            Some(name) if !name.is_empty() => Ok(name),
+            Some(_) => Err(format!("{} is signed, but its signer could not be read", path.display())),
+            None => Err(format!("{} has no valid signature (0x{:08x})", path.display(), status as u32)),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn signer(path: &Path) -> Result<String, String> {
+    Err(format!("Cannot verify the signature of {} on this platform", path.display()))
+}
@@ -0,0 +1,230 @@
+//! Machine-readable reporting for silent runs (`--silent`, `--uninstall --silent`), used
+//! by deployment scripts and the app itself: one JSON object per line on stdout, and an
+//! exit code that says what went wrong. Silent installs and updates also leave their
+//! outcome in `install-result.json` in the app's data folder, so the relaunched app can
+//! tell the user an update failed.
+
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::processes::RunningProcess;
+use crate::{debug_log, event_log, telemetry};
+
+pub const RESULT_FILE: &str = "install-result.json";
+
+/// The silent install in progress, recorded in the result file when it finishes
+struct Run {
+    started: Instant,
+    previous_version: Option<String>,
+    version: Option<String>,
+}
+
+static CURRENT: Mutex<Option<Run>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitCode {
+    Success = 0,
+    /// Anything not covered below; details are in the log
+    Failed = 1,
+    InvalidArguments = 2,
+    /// No install manifest at the given location
+    NotInstalled = 3,
+    PayloadMissing = 4,
+    ExtractionFailed = 5,
+    /// Installed, but files in use get replaced at the next sign-in or restart
+    PendingRestart = 6,
+    /// The app is still running from the install directory and wouldn't close
+    AppRunning = 7,
+    /// A license is bundled and `--accept-license` wasn't given for a fresh install
+    LicenseNotAccepted = 8,
+    /// Controlled Folder Access blocks writing to the install directory; retrying won't help
+    ControlledFolderAccess = 9,
+    /// Essential files vanished or changed right after extraction, most likely antivirus
+    Quarantined = 10,
+    /// The install path is on a network share that can't be reached or written to
+    NetworkShare = 11,
+    /// A 32-bit or too old Windows that the app can't run on
+    UnsupportedSystem = 12,
+    /// The release manifest the app passed expired, is older than one already installed,
+    /// or doesn't list this installer
+    StaleRelease = 13,
+    /// The installer itself crashed (a panic); the backtrace is in the log
+    InternalError = 70,
+}
+
+impl ExitCode {
+    /// Every code, for `--version --json`
+    pub const ALL: &'static [ExitCode] = &[
+        ExitCode::Success,
+        ExitCode::Failed,
+        ExitCode::InvalidArguments,
+        ExitCode::NotInstalled,
+        ExitCode::PayloadMissing,
+        ExitCode::ExtractionFailed,
+        ExitCode::PendingRestart,
+        ExitCode::AppRunning,
+        ExitCode::LicenseNotAccepted,
+        ExitCode::ControlledFolderAccess,
+        ExitCode::Quarantined,
+        ExitCode::NetworkShare,
+        ExitCode::UnsupportedSystem,
+        ExitCode::StaleRelease,
+        ExitCode::InternalError,
+    ];
+}
+
+/// Why a silent run stopped: the exit code to report, the stage that failed (for the log)
+/// and the message passed on to the caller
+#[derive(Debug)]
+pub struct Failure {
+    pub code: ExitCode,
+    pub stage: &'static str,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: ExitCode, stage: &'static str, message: impl Into<String>) -> Self {
+        Self { code, stage, message: message.into() }
+    }
+}
+
+/// Rewrite the NSIS-style `/S` and `/D=<dir>` switches as `--silent` and
+/// `--install-path <dir>`, for deployment scripts and manifests written for NSIS
+/// installers. As with NSIS, `/D=` comes last and takes the rest of the command line
+/// as the path, spaces included and unquoted.
+pub fn expand_nsis_switches(args: Vec<String>) -> Vec<String> {
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut rest = args.into_iter();
+    while let Some(arg) = rest.next() {
+        if arg == "/S" {
+            expanded.push("--silent".to_string());
+        } else if let Some(first) = arg.strip_prefix("/D=") {
+            let path = std::iter::once(first.to_string()).chain(rest.by_ref()).collect::<Vec<_>>().join(" ");
+            expanded.push("--install-path".to_string());
+            expanded.push(path);
+        } else {
+            expanded.push(arg);
+        }
+    }
+    expanded
+}
+
+fn print(value: serde_json::Value) {
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    // Nobody may be reading stdout; that isn't an error
+    let _ = writeln!(stdout, "{}", value);
+    let _ = stdout.flush();
+}
+
+/// Start recording a silent install over `previous_version` (None for a fresh install)
+pub fn started(previous_version: Option<&str>) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(Run { started: Instant::now(), previous_version: previous_version.map(str::to_string), version: None });
+    }
+}
+
+/// The version being installed, once the payload has been read
+pub fn installing(version: Option<&str>) {
+    if let Ok(mut current) = CURRENT.lock() {
+        if let Some(run) = current.as_mut() {
+            run.version = version.map(str::to_string);
+        }
+    }
+}
+
+fn write_result(status: &str, code: ExitCode, message: Option<&str>) {
+    let Some(run) = CURRENT.lock().ok().and_then(|mut current| current.take()) else { return };
+    let Some(dir) = crate::log_dir() else { return };
+    let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let result = json!({
+        "status": status,
+        "code": code as i32,
+        "message": message,
+        "previousVersion": run.previous_version,
+        "version": run.version,
+        "durationMs": run.started.elapsed().as_millis() as u64,
+        "finishedAt": finished_at,
+        "logPath": crate::log_path(),
+    });
+    let written = std::fs::create_dir_all(&dir)
+        .and_then(|_| serde_json::to_vec_pretty(&result).map_err(std::io::Error::other))
+        .and_then(|json| std::fs::write(dir.join(RESULT_FILE), json));
+    if let Err(e) = written {
+        debug_log(&format!("Failed to write {}: {}", RESULT_FILE, e));
+    }
+}
+
+/// Report that `stage` started, with the overall percentage
+pub fn progress(stage: &str, percent: u32) {
+    print(json!({ "event": "progress", "stage": stage, "percent": percent }));
+}
+
+/// Report something the run worked around but the caller may want to fix
+pub fn warning(code: &str, message: &str) {
+    print(json!({ "event": "warning", "code": code, "message": message }));
+}
+
+/// Report that Controlled Folder Access blocks `path`, naming the program to allow
+pub fn controlled_folder_access(path: &str) {
+    let installer = std::env::current_exe().ok();
+    print(json!({ "event": "controlled_folder_access", "path": path, "installer": installer }));
+}
+
+/// Report essential files that antivirus software most likely quarantined
+pub fn quarantined(files: &[String]) {
+    print(json!({ "event": "quarantined", "files": files }));
+}
+
+/// Report the processes blocking the install and exit with `AppRunning`
+pub fn app_running(processes: &[RunningProcess]) -> ! {
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+    print(json!({ "event": "app_running", "pids": pids, "processes": names }));
+    let message = format!("Still running: {}", names.join(", "));
+    finish(ExitCode::AppRunning, Some(&message));
+}
+
+/// Report the outcome and exit with `code`
+pub fn finish(code: ExitCode, message: Option<&str>) -> ! {
+    let event = match code {
+        ExitCode::Success => "done",
+        ExitCode::PendingRestart => "pending_restart",
+        _ => "error",
+    };
+    print(json!({ "event": event, "code": code as i32, "message": message }));
+    write_result(event, code, message);
+    event_log::finish_current(code as i32, message);
+    telemetry::finish_current(code as i32);
+    std::process::exit(code as i32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(args: &[&str]) -> Vec<String> {
+        expand_nsis_switches(args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn nsis_silent_switch() {
+        assert_eq!(expand(&["/S"]), ["--silent"]);
+        assert_eq!(expand(&["--no-launch", "/S"]), ["--no-launch", "--silent"]);
+    }
+
+    #[test]
+    fn nsis_install_dir_takes_the_rest_of_the_line() {
+        assert_eq!(expand(&["/S", "/D=C:\\Program Files\\Mangyomi"]), ["--silent", "--install-path", "C:\\Program Files\\Mangyomi"]);
+        // Unquoted, the shell splits the path at its spaces
+        assert_eq!(expand(&["/S", "/D=C:\\My", "Apps\\Mangyomi"]), ["--silent", "--install-path", "C:\\My Apps\\Mangyomi"]);
+    }
+
+    #[test]
+    fn other_arguments_pass_through() {
+        assert_eq!(expand(&["--silent", "/s", "/Dir=x", "--install-path", "D:\\x"]), ["--silent", "/s", "/Dir=x", "--install-path", "D:\\x"]);
+        assert!(expand(&[]).is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+//! A description of the machine the installer runs on, for the frontend to tailor its
+//! warnings (an x64 build emulated on ARM64, too little memory) and for diagnostics.
+
+#[cfg(feature = "gui")]
+use crate::known_folders::{self, Fallback};
+#[cfg(feature = "gui")]
+use crate::locale;
+
+#[cfg(feature = "gui")]
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemInfo {
+    /// e.g. `Windows 11 Pro`, or the distribution name on Linux
+    pub os_name: Option<String>,
+    /// Windows build with its update revision (`22631.3880`)
+    pub os_build: Option<String>,
+    /// Architecture the installer was built for
+    pub arch: &'static str,
+    /// Architecture of the machine itself, which differs when the installer is emulated
+    pub native_arch: Option<&'static str>,
+    pub cpu: Option<String>,
+    pub cpu_count: usize,
+    pub memory_bytes: Option<u64>,
+    pub locale: String,
+    pub dark_mode: Option<bool>,
+    /// Scale factor of the installer window (1.0 at 96 DPI), filled in by the caller
+    pub display_scale: Option<f64>,
+    /// Folders the shell couldn't report, with where they were found instead
+    pub folder_fallbacks: Vec<Fallback>,
+}
+
+#[cfg(feature = "gui")]
+pub fn collect() -> SystemInfo {
+    SystemInfo {
+        os_name: os_name(),
+        os_build: os_build(),
+        arch: std::env::consts::ARCH,
+        native_arch: native_arch(),
+        cpu: cpu(),
+        cpu_count: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        memory_bytes: memory_bytes(),
+        locale: locale::user_locale(),
+        dark_mode: dark_mode(),
+        display_scale: None,
+        folder_fallbacks: known_folders::fallbacks(),
+    }
+}
+
+#[cfg(windows)]
+fn hklm(subkey: &str) -> Option<winreg::RegKey> {
+    winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE).open_subkey(subkey).ok()
+}
+
+#[cfg(feature = "gui")]
+#[cfg(windows)]
+fn os_name() -> Option<String> {
+    let name: String = hklm("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion")?.get_value("ProductName").ok()?;
+    // ProductName still says Windows 10 on Windows 11, which starts at build 22000
+    let is_11 = os_build().and_then(|b| b.split('.').next()?.parse::<u32>().ok()).is_some_and(|b| b >= 22000);
+    Some(if is_11 { name.replacen("Windows 10", "Windows 11", 1) } else { name })
+}
+
+#[cfg(feature = "gui")]
+#[cfg(target_os = "linux")]
+fn os_name() -> Option<String> {
+    let release = std::fs::read_to_string("/etc/os-release").ok()?;
+    let name = release.lines().find_map(|line| line.strip_prefix("PRETTY_NAME="))?;
+    Some(name.trim_matches('"').to_string())
+}
+
+#[cfg(feature = "gui")]
+#[cfg(target_os = "macos")]
+fn os_name() -> Option<String> {
+    Some("macOS".to_string())
+}
+
+#[cfg(windows)]
+pub fn os_build() -> Option<String> {
+    let key = hklm("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion")?;
+    let build: String = key.get_value("CurrentBuildNumber").ok()?;
+    match key.get_value::<u32, _>("UBR") {
+        Ok(revision) => Some(format!("{}.{}", build, revision)),
+        Err(_) => Some(build),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn os_build() -> Option<String> {
+    None
+}
+
+#[cfg(windows)]
+pub fn native_arch() -> Option<&'static str> {
+    use windows::Win32::System::SystemInformation::{
+        IMAGE_FILE_MACHINE, IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_I386,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    let mut process = IMAGE_FILE_MACHINE::default();
+    let mut native = IMAGE_FILE_MACHINE::default();
+    unsafe { IsWow64Process2(GetCurrentProcess(), &mut process, Some(&mut native)) }.ok()?;
+    match native {
+        IMAGE_FILE_MACHINE_AMD64 => Some("x86_64"),
+        IMAGE_FILE_MACHINE_ARM64 => Some("aarch64"),
+        IMAGE_FILE_MACHINE_I386 => Some("x86"),
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn native_arch() -> Option<&'static str> {
+    Some(std::env::consts::ARCH)
+}
+
+#[cfg(feature = "gui")]
+#[cfg(windows)]
+fn cpu() -> Option<String> {
+    let name: String = hklm("HARDWARE\\DESCRIPTION\\System\\CentralProcessor\\0")?.get_value("ProcessorNameString").ok()?;
+    Some(name.trim().to_string())
+}
+
+#[cfg(feature = "gui")]
+#[cfg(target_os = "linux")]
+fn cpu() -> Option<String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    let line = cpuinfo.lines().find(|line| line.starts_with("model name"))?;
+    Some(line.split_once(':')?.1.trim().to_string())
+}
+
+#[cfg(feature = "gui")]
+#[cfg(target_os = "macos")]
+fn cpu() -> Option<String> {
+    None
+}
+
+#[cfg(feature = "gui")]
+#[cfg(windows)]
+fn memory_bytes() -> Option<u64> {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status = MEMORYSTATUSEX { dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32, ..Default::default() };
+    unsafe { GlobalMemoryStatusEx(&mut status) }.ok()?;
+    Some(status.ullTotalPhys)
+}
+
+#[cfg(feature = "gui")]
+#[cfg(target_os = "linux")]
+fn memory_bytes() -> Option<u64> {
+    // MemTotal:       16318412 kB
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find_map(|line| line.strip_prefix("MemTotal:"))?;
+    let kib: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(feature = "gui")]
+#[cfg(target_os = "macos")]
+fn memory_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(feature = "gui")]
+/// Whether apps are set to the dark theme
+#[cfg(windows)]
+fn dark_mode() -> Option<bool> {
+    let key = winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER)
+        .open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize")
+        .ok()?;
+    let light: u32 = key.get_value("AppsUseLightTheme").ok()?;
+    Some(light == 0)
+}
+
+#[cfg(feature = "gui")]
+#[cfg(not(windows))]
+fn dark_mode() -> Option<bool> {
+    None
+}
+
+/// Most memory this process has held at once, for logging after heavy stages
+#[cfg(windows)]
+pub fn peak_memory_bytes() -> Option<u64> {
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters = PROCESS_MEMORY_COUNTERS::default();
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) }.ok()?;
+    Some(counters.PeakWorkingSetSize as u64)
+}
+
+#[cfg(target_os = "linux")]
+pub fn peak_memory_bytes() -> Option<u64> {
+    // VmHWM:     123456 kB
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+    let kib: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(target_os = "macos")]
+pub fn peak_memory_bytes() -> Option<u64> {
+    None
+}
@@ -0,0 +1,58 @@
+//! Native progress on the installer's taskbar button (ITaskbarList3), so a minimized
+//! install stays visible at a glance.
+
+#[derive(Clone, Copy)]
+pub enum TaskbarState {
+    Normal,
+    Paused,
+    Error,
+    /// Clear the progress overlay
+    Off,
+}
+
+#[cfg(windows)]
+fn with_taskbar<F>(app_handle: &tauri::AppHandle, f: F)
+where
+    F: FnOnce(&windows::Win32::UI::Shell::ITaskbarList3, windows::Win32::Foundation::HWND) -> windows::core::Result<()>
+        + Send
+        + 'static,
+{
+    use tauri::Manager;
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+    use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList};
+
+    let Some(window) = app_handle.get_webview_window("main") else { return };
+    // The taskbar object is apartment-threaded; the UI thread already has COM set up
+    let _ = app_handle.run_on_main_thread(move || unsafe {
+        let Ok(hwnd) = window.hwnd() else { return };
+        let result = CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER)
+            .and_then(|taskbar| taskbar.HrInit().map(|_| taskbar))
+            .and_then(|taskbar| f(&taskbar, hwnd));
+        if let Err(e) = result {
+            crate::debug_log(&format!("Taskbar progress failed: {}", e));
+        }
+    });
+}
+
+#[cfg(windows)]
+pub fn set_progress(app_handle: &tauri::AppHandle, percent: u32) {
+    with_taskbar(app_handle, move |taskbar, hwnd| unsafe { taskbar.SetProgressValue(hwnd, percent.min(100) as u64, 100) });
+}
+
+#[cfg(windows)]
+pub fn set_state(app_handle: &tauri::AppHandle, state: TaskbarState) {
+    use windows::Win32::UI::Shell::{TBPF_ERROR, TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED};
+    let flag = match state {
+        TaskbarState::Normal => TBPF_NORMAL,
+        TaskbarState::Paused => TBPF_PAUSED,
+        TaskbarState::Error => TBPF_ERROR,
+        TaskbarState::Off => TBPF_NOPROGRESS,
+    };
+    with_taskbar(app_handle, move |taskbar, hwnd| unsafe { taskbar.SetProgressState(hwnd, flag) });
+}
+
+#[cfg(not(windows))]
+pub fn set_progress(_app_handle: &tauri::AppHandle, _percent: u32) {}
+
+#[cfg(not(windows))]
+pub fn set_state(_app_handle: &tauri::AppHandle, _state: TaskbarState) {}
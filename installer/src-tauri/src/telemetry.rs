@@ -0,0 +1,54 @@
+//! Opt-in install telemetry: one anonymous ping after an install or update finishes, so
+//! maintainers can see how often installs fail in the wild.
+//!
+//! Nothing is sent unless the user opted in (the installer checkbox, or `--telemetry`
+//! for silent installs) and the build has an endpoint (`telemetryUrl` in branding). The
+//! ping carries only the version, the Windows build, the exit code and the duration.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{branding, debug_log, download, system_info};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// When the install being reported started, and the version it installs
+static CURRENT: Mutex<Option<(Instant, Option<String>)>> = Mutex::new(None);
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Ping<'a> {
+    version: Option<&'a str>,
+    os_build: Option<String>,
+    /// Process exit code: 0 for success, otherwise an `ExitCode`
+    code: i32,
+    duration_ms: u64,
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn started(version: Option<&str>) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some((Instant::now(), version.map(str::to_string)));
+    }
+}
+
+/// Send the ping for the install last started, if the user opted in
+pub fn finish_current(code: i32) {
+    let Some((start, version)) = CURRENT.lock().ok().and_then(|mut current| current.take()) else { return };
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(url) = &branding::get().telemetry_url else { return };
+
+    let ping = Ping { version: version.as_deref(), os_build: system_info::os_build(), code, duration_ms: start.elapsed().as_millis() as u64 };
+    let Ok(body) = serde_json::to_string(&ping) else { return };
+    // Never hold up the installer for long on a slow or blocked network
+    match download::post_json(url, &body, Duration::from_secs(5)) {
+        Ok(()) => debug_log("Sent install telemetry"),
+        Err(e) => debug_log(&format!("Telemetry not sent: {}", e)),
+    }
+}
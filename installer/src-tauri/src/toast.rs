@@ -0,0 +1,54 @@
+//! "<app> was updated" toast shown after a silent update.
+//!
+//! The toast is sent under the app's AppUserModelID (stamped on its shortcuts), so
+//! clicking it launches the app with the toast's `launch` string as its arguments.
+
+#[cfg(windows)]
+use crate::{branding, locale};
+
+#[cfg(windows)]
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Arguments the app receives when the toast is clicked
+#[cfg(windows)]
+fn activation_arguments(version: &str) -> String {
+    format!("--whats-new {}", version)
+}
+
+#[cfg(windows)]
+pub fn show_update_complete(version: &str) -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Data::Xml::Dom::XmlDocument;
+    use windows::UI::Notifications::{ToastNotification, ToastNotificationManager};
+
+    let branding = branding::get();
+    let xml = format!(
+        "<toast launch=\"{}\" activationType=\"foreground\">\
+            <visual><binding template=\"ToastGeneric\">\
+                <text>{}</text>\
+                <text>{}</text>\
+            </binding></visual>\
+        </toast>",
+        escape_xml(&activation_arguments(version)),
+        escape_xml(&locale::text("update_complete", &[("app", &branding.app_name), ("version", version)])),
+        escape_xml(&locale::text("update_complete_hint", &[]))
+    );
+
+    let show = || -> windows::core::Result<()> {
+        let document = XmlDocument::new()?;
+        document.LoadXml(&HSTRING::from(xml))?;
+        let toast = ToastNotification::CreateToastNotification(&document)?;
+        ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(&branding.app_user_model_id))?.Show(&toast)
+    };
+    show().map_err(|e| format!("Failed to show update toast: {}", e))
+}
+
+#[cfg(not(windows))]
+pub fn show_update_complete(_version: &str) -> Result<(), String> {
+    Ok(())
+}
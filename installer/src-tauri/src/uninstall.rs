@@ -0,0 +1,248 @@
+//! Uninstall mode (`--uninstall`), launched from the "Uninstall <app>" shortcut, or
+//! `--uninstall --silent [--purge]` for scripts and the app's own "Uninstall" button.
+//!
+//! Only what the installer recorded in the install manifest is removed, so anything
+//! else the user keeps in the install directory survives. `--purge` also removes the
+//! library, settings, backups and update cache.
+
+use std::path::{Path, PathBuf};
+
+use crate::silent::{self, ExitCode};
+use crate::event_log::{self, Operation};
+use crate::{
+    arp, branding, data_dir, debug_log, defender, extract, file_assoc, firewall, install_record, manifest, policy, shortcuts,
+};
+
+/// Copy the running installer into the install directory as the uninstaller. macOS apps
+/// are removed by moving them to the Trash, and a copy inside the bundle would break
+/// its signature, so there is none there.
+pub fn install_uninstaller(install_path: &str) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        return Ok(());
+    }
+    let current = std::env::current_exe().map_err(|e| e.to_string())?;
+    let target = Path::new(install_path).join(branding::get().uninstaller_exe());
+    if current == target {
+        return Ok(());
+    }
+    std::fs::copy(&current, &target).map_err(|e| format!("Failed to copy uninstaller: {}", e))?;
+    Ok(())
+}
+
+pub fn remove_file_if_exists(path: &Path) {
+    let mut result = std::fs::remove_file(path);
+    // Windows refuses to delete read-only files, which payloads may contain
+    if result.as_ref().is_err_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied) {
+        extract::make_writable(path);
+        result = std::fs::remove_file(path);
+    }
+    match result {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => debug_log(&format!("Failed to remove {:?}: {}", path, e)),
+    }
+}
+
+/// Remove now-empty parent directories of `path`, stopping at `root`
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if d == root || !d.starts_with(root) || std::fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+/// Remove install-relative `files` under `root`, along with directories they leave empty
+pub fn remove_files(root: &Path, files: &[String]) {
+    for file in files {
+        let path = root.join(file);
+        remove_file_if_exists(&path);
+        remove_empty_parents(&path, root);
+    }
+}
+
+/// Remove the shortcuts and files recorded in the install manifest, calling `progress`
+/// with each stage and the percentage done. A `silent` run never shows a UAC prompt:
+/// machine-wide settings it lacks the rights to undo are left in place and logged.
+pub fn uninstall(install_path: &str, silent: bool, mut progress: impl FnMut(&str, u32)) -> Result<(), String> {
+    let manifest = manifest::load(install_path).ok_or("No install manifest found; nothing to uninstall")?;
+    let files = manifest.all_files();
+    debug_log(&format!("Uninstalling from {} ({} files)", install_path, files.len()));
+
+    progress("shortcuts", 10);
+    for lnk in &manifest.shortcuts {
+        remove_file_if_exists(lnk);
+    }
+    shortcuts::remove_start_menu_dir(&manifest.options.clone().unwrap_or_default());
+    progress("file_associations", 30);
+    if manifest.options.as_ref().is_some_and(|o| o.register_file_associations && !o.portable) {
+        if let Err(e) = file_assoc::unregister(install_path) {
+            debug_log(&e);
+        }
+    }
+    if manifest.options.as_ref().is_some_and(|o| !o.portable) {
+        if let Err(e) = arp::unregister() {
+            debug_log(&e);
+        }
+        if let Err(e) = install_record::remove() {
+            debug_log(&e);
+        }
+    }
+    if manifest.options.as_ref().is_some_and(|o| o.firewall_rule && !o.portable) {
+        if let Err(e) = firewall::request_removal(silent) {
+            debug_log(&e);
+        }
+    }
+    if manifest.options.as_ref().is_some_and(|o| o.defender_exclusion && !o.portable) {
+        if let Err(e) = defender::request_removal(install_path, silent) {
+            debug_log(&e);
+        }
+    }
+
+    progress("files", 50);
+    let root = Path::new(install_path);
+    remove_files(root, &files);
+    let manifest_path = manifest::manifest_path(install_path);
+    remove_file_if_exists(&manifest_path);
+    remove_empty_parents(&manifest_path, root);
+    // Outside the install directory on macOS, so not among the files removed above
+    let state_dir = manifest::state_dir(install_path);
+    remove_file_if_exists(&state_dir.join(policy::UPDATE_POLICY_FILE));
+    remove_file_if_exists(&state_dir.join(data_dir::DATA_LOCATION_FILE));
+
+    debug_log("Uninstall complete");
+    Ok(())
+}
+
+/// A running exe can't delete itself: leave it to a detached cmd once we've exited.
+/// `rmdir` without /S only removes the install directory if nothing else is left in it.
+#[cfg(windows)]
+pub fn schedule_self_delete(install_path: &str) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let Ok(exe) = std::env::current_exe() else { return };
+    let script = format!(
+        "/C \"ping 127.0.0.1 -n 3 > nul & del /F /Q \"{}\" & rmdir \"{}\"\"",
+        exe.display(),
+        install_path
+    );
+    if let Err(e) = std::process::Command::new("cmd").raw_arg(script).creation_flags(CREATE_NO_WINDOW).spawn() {
+        debug_log(&format!("Failed to schedule uninstaller removal: {}", e));
+    }
+}
+
+/// Unix lets a running binary unlink itself
+#[cfg(not(windows))]
+pub fn schedule_self_delete(install_path: &str) {
+    // An installer run from elsewhere (the macOS disk image) isn't ours to remove
+    if let Some(exe) = std::env::current_exe().ok().filter(|exe| exe.starts_with(install_path)) {
+        remove_file_if_exists(&exe);
+    }
+    let _ = std::fs::remove_dir(install_path);
+}
+
+#[cfg(windows)]
+fn dialog_title() -> String {
+    format!("Uninstall {}", branding::get().app_name)
+}
+
+#[cfg(windows)]
+fn confirm(text: &str) -> bool {
+    use windows::core::HSTRING;
+    use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, IDYES, MB_ICONQUESTION, MB_YESNO};
+    unsafe { MessageBoxW(None, &HSTRING::from(text), &HSTRING::from(dialog_title()), MB_YESNO | MB_ICONQUESTION) == IDYES }
+}
+
+#[cfg(windows)]
+fn notify(text: &str) {
+    use windows::core::HSTRING;
+    use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_OK};
+    unsafe {
+        MessageBoxW(None, &HSTRING::from(text), &HSTRING::from(dialog_title()), MB_OK);
+    }
+}
+
+#[cfg(not(windows))]
+fn confirm(_text: &str) -> bool {
+    true
+}
+
+#[cfg(not(windows))]
+fn notify(text: &str) {
+    println!("{}", text);
+}
+
+/// The install the uninstaller belongs to: the directory it runs from
+pub fn default_install_path() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+/// Interactive uninstall: confirm, remove, report. Returns the process exit code.
+pub fn run_interactive(install_path: &str) -> i32 {
+    let app_name = &branding::get().app_name;
+    if !confirm(&format!("Remove {} from this computer?\n\nYour library and settings are kept.", app_name)) {
+        return 0;
+    }
+    event_log::started(Operation::Uninstall, None);
+    let result = uninstall(install_path, false, |_, _| {});
+    event_log::finish_current(if result.is_ok() { 0 } else { 1 }, result.as_ref().err().map(String::as_str));
+    match result {
+        Ok(()) => {
+            schedule_self_delete(install_path);
+            notify(&format!("{} was removed from this computer.", app_name));
+            0
+        }
+        Err(e) => {
+            debug_log(&format!("Uninstall failed: {}", e));
+            notify(&format!("Uninstall failed: {}", e));
+            1
+        }
+    }
+}
+
+/// The app's data folders: the default one (settings, backups, update cache) and a
+/// custom one chosen at install time. Resolved before uninstalling, which removes the
+/// file pointing at the custom one.
+fn data_dirs(install_path: &str) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = data_dir::default_dir().into_iter().collect();
+    if let Ok(custom) = data_dir::resolve(Some(install_path)) {
+        // A user-chosen folder is only removed when it really holds our library
+        if !dirs.contains(&custom) && custom.parent().is_some() && custom.join(data_dir::DATABASE_FILE).is_file() {
+            dirs.push(custom);
+        }
+    }
+    dirs
+}
+
+fn purge(dirs: &[PathBuf]) -> Result<(), String> {
+    for dir in dirs.iter().filter(|d| d.exists()) {
+        debug_log(&format!("Purging {:?}", dir));
+        std::fs::remove_dir_all(dir).map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Silent uninstall: no dialogs, JSON progress on stdout and a structured exit code
+pub fn run_silent(install_path: &str, purge_data: bool) -> ! {
+    event_log::started(Operation::Uninstall, None);
+    if manifest::load(install_path).is_none() {
+        silent::finish(ExitCode::NotInstalled, Some(&format!("No install found at {}", install_path)));
+    }
+    let dirs = if purge_data { data_dirs(install_path) } else { Vec::new() };
+    if let Err(e) = uninstall(install_path, true, silent::progress) {
+        debug_log(&format!("Uninstall failed: {}", e));
+        silent::finish(ExitCode::Failed, Some(&e));
+    }
+    if purge_data {
+        silent::progress("purge", 80);
+        if let Err(e) = purge(&dirs) {
+            debug_log(&e);
+            silent::finish(ExitCode::Failed, Some(&e));
+        }
+    }
+    schedule_self_delete(install_path);
+    silent::finish(ExitCode::Success, None)
+}
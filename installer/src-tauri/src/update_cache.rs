@@ -0,0 +1,304 @@
+//! Versioned installer cache used as the base for differential updates.
+//!
+//! Layout: `%APPDATA%\mangyomi\update-cache\<version>\` holding the installer,
+//! its blockmap and an `entry.json` with bookkeeping used for LRU eviction.
+
+use std::path::{Path, PathBuf};
+
+use crate::block_clone;
+use crate::branding;
+use crate::debug_log;
+use crate::hash;
+use crate::known_folders::{self, KnownFolder};
+use crate::signature;
+
+/// Default cap for the whole cache directory (1 GiB)
+pub const DEFAULT_SIZE_LIMIT: u64 = 1024 * 1024 * 1024;
+
+pub const INSTALLER_FILE: &str = "installer.exe";
+pub const BLOCKMAP_FILE: &str = "installer.blockmap";
+const ENTRY_FILE: &str = "entry.json";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CacheEntry {
+    pub version: String,
+    /// Seconds since UNIX epoch of the last store or reuse
+    pub last_used: u64,
+    /// SHA-256 of the cached files, recorded at store time and checked before reuse
+    #[serde(default)]
+    pub installer_sha256: Option<String>,
+    #[serde(default)]
+    pub blockmap_sha256: Option<String>,
+}
+
+pub fn cache_dir() -> Result<PathBuf, String> {
+    let appdata = known_folders::get(KnownFolder::RoamingAppData).ok_or("APPDATA not found")?;
+    Ok(appdata.join(&branding::get().data_dir_name).join("update-cache"))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Replace the cached installer for `version` with a newer build of the installer (a
+/// stub fix), once its signature checks out. The replacement must be signed by the
+/// subject pinned in branding; without one nothing is trusted.
+pub fn replace_installer(version: &str, installer: &Path, blockmap: Option<&Path>) -> Result<PathBuf, String> {
+    let trusted = branding::get()
+        .code_signing_subject
+        .as_deref()
+        .ok_or("No code signing subject is configured, so installer stubs can't be verified")?;
+    let signer = signature::signer(installer)?;
+    if signer != trusted {
+        return Err(format!("{} is signed by {}, not {}", installer.display(), signer, trusted));
+    }
+    debug_log(&format!("Replacing cached installer for {} with one signed by {}", version, signer));
+    store(version, installer, blockmap)
+}
+
+/// Copy the installer (and its blockmap when present) into the cache under `version`
+pub fn store(version: &str, installer: &Path, blockmap: Option<&Path>) -> Result<PathBuf, String> {
+    let dir = cache_dir()?.join(version);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    // Files may be hard links shared with other versions (see `dedupe`), so replace
+    // them instead of writing through the link
+    let cached_installer = dir.join(INSTALLER_FILE);
+    let _ = std::fs::remove_file(&cached_installer);
+    block_clone::copy(installer, &cached_installer)
+        .map_err(|e| format!("Failed to cache installer {:?}: {}", installer, e))?;
+
+    let cached_blockmap = dir.join(BLOCKMAP_FILE);
+    let blockmap_sha256 = match blockmap.filter(|p| p.exists()) {
+        Some(blockmap) => {
+            let _ = std::fs::remove_file(&cached_blockmap);
+            block_clone::copy(blockmap, &cached_blockmap)
+                .map_err(|e| format!("Failed to cache blockmap {:?}: {}", blockmap, e))?;
+            Some(hash::sha256_file(&cached_blockmap)?)
+        }
+        None => {
+            // Don't leave a blockmap from an earlier store of this version behind
+            let _ = std::fs::remove_file(&cached_blockmap);
+            None
+        }
+    };
+
+    let entry = CacheEntry {
+        version: version.to_string(),
+        last_used: now(),
+        installer_sha256: Some(hash::sha256_file(&cached_installer)?),
+        blockmap_sha256,
+    };
+    write_entry(&dir, &entry)?;
+    Ok(dir)
+}
+
+/// Mark a cached version as just used so eviction keeps it around longer
+pub fn touch(version: &str) -> Result<(), String> {
+    let dir = cache_dir()?.join(version);
+    let mut entry = read_entry(&dir).ok_or_else(|| format!("No cache entry for {}", version))?;
+    entry.last_used = now();
+    write_entry(&dir, &entry)
+}
+
+fn write_entry(dir: &Path, entry: &CacheEntry) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(entry).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(ENTRY_FILE), json).map_err(|e| e.to_string())
+}
+
+fn read_entry(dir: &Path) -> Option<CacheEntry> {
+    let data = std::fs::read_to_string(dir.join(ENTRY_FILE)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Check a cached file against the hash recorded when it was stored
+fn verify_file(path: &Path, expected: Option<&str>) -> Result<(), String> {
+    let expected = expected.ok_or_else(|| format!("No stored hash for {:?}", path))?;
+    let actual = hash::sha256_file(path)?;
+    if actual != expected {
+        return Err(format!("Hash mismatch for {:?}: expected {}, got {}", path, expected, actual));
+    }
+    Ok(())
+}
+
+/// The cached installer for `version`, without verifying it
+#[cfg(windows)]
+pub fn installer_path(version: &str) -> Option<PathBuf> {
+    Some(cache_dir().ok()?.join(version).join(INSTALLER_FILE)).filter(|path| path.is_file())
+}
+
+/// Return the cached installer for `version` if it is safe to use as a delta base.
+///
+/// The installer and blockmap are re-hashed against the values stored with the entry;
+/// a corrupted or unverifiable entry is discarded and `None` is returned so the
+/// caller falls back to a full download instead of assembling a broken payload.
+pub fn verified_base(version: &str) -> Option<PathBuf> {
+    let dir = cache_dir().ok()?.join(version);
+    read_entry(&dir)?;
+    if let Err(e) = check(version) {
+        debug_log(&format!("Update cache: discarding {}: {}", version, e));
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            debug_log(&format!("Update cache: failed to remove {:?}: {}", dir, e));
+        }
+        return None;
+    }
+
+    let _ = touch(version);
+    Some(dir.join(INSTALLER_FILE))
+}
+
+/// Re-hash the cached files for `version` against its entry, without touching anything
+pub fn check(version: &str) -> Result<(), String> {
+    let dir = cache_dir()?.join(version);
+    let entry = read_entry(&dir).ok_or_else(|| format!("No readable {} in {:?}", ENTRY_FILE, dir))?;
+    verify_file(&dir.join(INSTALLER_FILE), entry.installer_sha256.as_deref())?;
+    let blockmap = dir.join(BLOCKMAP_FILE);
+    if blockmap.exists() {
+        verify_file(&blockmap, entry.blockmap_sha256.as_deref())?;
+    }
+    Ok(())
+}
+
+/// The versions with a directory in the cache
+pub fn versions() -> Vec<String> {
+    let Ok(root) = cache_dir() else { return Vec::new() };
+    let Ok(read_dir) = std::fs::read_dir(root) else { return Vec::new() };
+    read_dir
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else { return 0 };
+    entries
+        .flatten()
+        .map(|e| match e.metadata() {
+            Ok(m) if m.is_dir() => dir_size(&e.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Evict least-recently-used versions until the cache fits in `limit` bytes.
+/// Versions listed in `keep` (the current and previous install) are never evicted.
+/// Returns the number of bytes freed.
+pub fn enforce_size_limit(limit: u64, keep: &[&str]) -> Result<u64, String> {
+    let root = cache_dir()?;
+    let Ok(read_dir) = std::fs::read_dir(&root) else { return Ok(0) };
+
+    // (version, last_used, size); directories without entry.json fall back to their mtime
+    let mut versions: Vec<(String, u64, u64)> = read_dir
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let path = e.path();
+            let name = e.file_name().to_string_lossy().to_string();
+            let last_used = read_entry(&path).map(|entry| entry.last_used).unwrap_or_else(|| {
+                e.metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            });
+            (name, last_used, dir_size(&path))
+        })
+        .collect();
+
+    let mut total: u64 = versions.iter().map(|(_, _, size)| size).sum();
+    debug_log(&format!("Update cache: {} bytes in {} versions (limit {})", total, versions.len(), limit));
+
+    versions.sort_by_key(|(_, last_used, _)| *last_used);
+
+    let mut freed = 0;
+    for (version, _, size) in versions {
+        if total <= limit {
+            break;
+        }
+        if keep.contains(&version.as_str()) {
+            continue;
+        }
+        match std::fs::remove_dir_all(root.join(&version)) {
+            Ok(()) => {
+                debug_log(&format!("Update cache: evicted {} ({} bytes)", version, size));
+                total -= size;
+                freed += size;
+            }
+            Err(e) => debug_log(&format!("Update cache: failed to evict {}: {}", version, e)),
+        }
+    }
+
+    Ok(freed)
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct DedupeReport {
+    /// Files replaced by (or already) a hard link to identical content elsewhere in the cache
+    pub linked_files: usize,
+    /// Bytes that would be stored twice without the hard links
+    pub bytes_saved: u64,
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<(PathBuf, u64)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(m) if m.is_dir() => collect_files(&path, out),
+            Ok(m) if entry.file_name() != ENTRY_FILE && m.len() > 0 => out.push((path, m.len())),
+            _ => {}
+        }
+    }
+}
+
+/// Hard-link files with identical content across cached versions instead of storing them twice.
+/// Safe to run repeatedly; already-linked files are simply counted again in the report.
+pub fn dedupe() -> Result<DedupeReport, String> {
+    let mut files = Vec::new();
+    collect_files(&cache_dir()?, &mut files);
+
+    // Only files sharing a size can be identical, so only those get hashed
+    let mut by_size: std::collections::HashMap<u64, Vec<PathBuf>> = std::collections::HashMap::new();
+    for (path, size) in files {
+        by_size.entry(size).or_default().push(path);
+    }
+
+    let mut report = DedupeReport::default();
+    for (size, paths) in by_size.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        let mut by_hash: std::collections::HashMap<String, Vec<PathBuf>> = std::collections::HashMap::new();
+        for path in paths {
+            if let Ok(digest) = hash::sha256_file(&path) {
+                by_hash.entry(digest).or_default().push(path);
+            }
+        }
+
+        for (_, mut group) in by_hash.into_iter().filter(|(_, group)| group.len() > 1) {
+            let original = group.remove(0);
+            for duplicate in group {
+                // Link under a temporary name first so the duplicate is never missing
+                let tmp = duplicate.with_extension("link-tmp");
+                let _ = std::fs::remove_file(&tmp);
+                let linked = std::fs::hard_link(&original, &tmp).and_then(|_| std::fs::rename(&tmp, &duplicate));
+                match linked {
+                    Ok(()) => {
+                        report.linked_files += 1;
+                        report.bytes_saved += size;
+                    }
+                    Err(e) => {
+                        let _ = std::fs::remove_file(&tmp);
+                        debug_log(&format!("Update cache: failed to link {:?}: {}", duplicate, e));
+                    }
+                }
+            }
+        }
+    }
+
+    debug_log(&format!("Update cache: {} files hard-linked, {} bytes saved", report.linked_files, report.bytes_saved));
+    Ok(report)
+}
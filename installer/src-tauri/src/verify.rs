@@ -0,0 +1,91 @@
+//! `--verify` (or `--silent --verify-only`): check an install against its install
+//! manifest, so admins can validate machines after imaging or a mass deployment. Files
+//! are checked for presence, then size and SHA-256 where the manifest recorded them;
+//! shortcuts and file associations for presence; the Apps & Features entry and the
+//! install record for pointing at this install. Nothing is extracted or repaired.
+
+use std::path::{Path, PathBuf};
+
+use crate::{arp, file_assoc, hash, install_record, manifest};
+
+#[derive(Default, serde::Serialize)]
+pub struct VerifyReport {
+    pub install_path: String,
+    pub version: Option<String>,
+    pub ok: bool,
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub size_mismatches: Vec<String>,
+    pub hash_mismatches: Vec<String>,
+    /// Files installed without a recorded hash (older installs, some delta updates)
+    pub unverified: usize,
+    pub missing_shortcuts: Vec<PathBuf>,
+    pub missing_file_associations: Vec<String>,
+    /// Registry entries that are missing or point somewhere else
+    pub registry_problems: Vec<String>,
+}
+
+/// Verify the install at `install_path`; `None` when there is no install manifest there
+pub fn verify(install_path: &str) -> Option<VerifyReport> {
+    let manifest = manifest::load(install_path)?;
+    let root = Path::new(install_path);
+    let mut report = VerifyReport {
+        install_path: install_path.to_string(),
+        version: manifest.version.clone(),
+        ..Default::default()
+    };
+
+    for file in manifest.all_files() {
+        report.checked += 1;
+        let path = root.join(&file);
+        let Ok(meta) = std::fs::metadata(&path) else {
+            report.missing.push(file);
+            continue;
+        };
+        let Some(expected) = manifest.hashes.get(&file) else {
+            report.unverified += 1;
+            continue;
+        };
+        if meta.len() != expected.size {
+            report.size_mismatches.push(file);
+        } else if !hash::sha256_file(&path).is_ok_and(|actual| actual.eq_ignore_ascii_case(&expected.sha256)) {
+            report.hash_mismatches.push(file);
+        }
+    }
+
+    report.missing_shortcuts = manifest.shortcuts.iter().filter(|lnk| !lnk.exists()).cloned().collect();
+    if manifest.options.as_ref().is_some_and(|o| o.register_file_associations && !o.portable) {
+        report.missing_file_associations = file_assoc::missing_changes(install_path);
+    }
+    // Portable installs leave nothing in the registry
+    if cfg!(windows) && !manifest.options.as_ref().is_some_and(|o| o.portable) {
+        report.registry_problems = registry_problems(install_path);
+    }
+
+    report.ok = report.missing.is_empty()
+        && report.size_mismatches.is_empty()
+        && report.hash_mismatches.is_empty()
+        && report.missing_shortcuts.is_empty()
+        && report.missing_file_associations.is_empty()
+        && report.registry_problems.is_empty();
+    Some(report)
+}
+
+fn registry_problems(install_path: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    match arp::install_location() {
+        None => problems.push("Apps & Features entry missing".to_string()),
+        Some(location) if !crate::same_path(&location, install_path) => {
+            problems.push(format!("Apps & Features entry points at {}", location))
+        }
+        Some(_) => {}
+    }
+    match install_record::load().install_path {
+        None => problems.push("Install record missing".to_string()),
+        Some(location) if !crate::same_path(&location, install_path) => {
+            problems.push(format!("Install record points at {}", location))
+        }
+        Some(_) => {}
+    }
+    problems
+}
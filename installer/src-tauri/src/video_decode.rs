@@ -0,0 +1,233 @@
+//! Hardware video decoding, as the anime player needs it. Media Foundation is asked which
+//! codecs have a hardware decoder, and the display adapters' driver dates are read from
+//! the registry; a machine without an H.264 decoder (no GPU driver, a remote session) or
+//! with years-old drivers plays on the CPU, which stutters and drains batteries. The
+//! installer only warns, and the report goes into the diagnostics bundle so playback bug
+//! reports say what the machine could do.
+//!
+//! HEVC and AV1 aren't decodable on a stock Windows install at all. When their free Store
+//! extensions are missing, the installer links to them and preselects the bundled
+//! software-decode component, since missing codecs are behind many playback complaints.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::debug_log;
+
+/// Drivers older than this miss decoder fixes and newer codec support
+const OUTDATED_DRIVER_AGE: Duration = Duration::from_secs(2 * 365 * 24 * 60 * 60);
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gpu {
+    pub name: String,
+    pub driver_version: Option<String>,
+    /// Seconds since the Unix epoch
+    pub driver_date: Option<u64>,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "code", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum DecodeWarning {
+    /// No hardware H.264 decoder, so every episode is decoded in software
+    SoftwareDecode,
+    OutdatedDriver { gpu: String, age_days: u64 },
+}
+
+impl DecodeWarning {
+    pub fn message(&self) -> String {
+        match self {
+            DecodeWarning::SoftwareDecode => {
+                "No hardware video decoder was found, so videos will be decoded by the CPU. Playback may stutter \
+                 and use more battery; installing your graphics driver usually fixes this."
+                    .to_string()
+            }
+            DecodeWarning::OutdatedDriver { gpu, age_days } => format!(
+                "The {} driver is {} years old. Updating it can fix video playback problems.",
+                gpu,
+                age_days / 365
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeReport {
+    /// Codecs with a hardware decoder (`h264`, `hevc`, `vp9`, `av1`); None where the
+    /// platform can't be asked
+    pub hardware_codecs: Option<Vec<&'static str>>,
+    pub gpus: Vec<Gpu>,
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// Query the decoders and drivers. Takes a moment, so it runs on request only.
+pub fn check() -> DecodeReport {
+    let hardware_codecs = hardware_codecs();
+    let gpus = gpus();
+    let mut warnings = Vec::new();
+    if hardware_codecs.as_ref().is_some_and(|codecs| !codecs.contains(&"h264")) {
+        warnings.push(DecodeWarning::SoftwareDecode);
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    // The basic display driver ships with Windows; without a real one there's no decoder anyway
+    for gpu in gpus.iter().filter(|gpu| !gpu.name.starts_with("Microsoft Basic")) {
+        let Some(age) = gpu.driver_date.map(|date| now.saturating_sub(date)) else { continue };
+        if age > OUTDATED_DRIVER_AGE.as_secs() {
+            warnings.push(DecodeWarning::OutdatedDriver { gpu: gpu.name.clone(), age_days: age / 86400 });
+        }
+    }
+    debug_log(&format!("Hardware decoders: {:?}, GPUs: {:?}", hardware_codecs, gpus));
+    for warning in &warnings {
+        debug_log(&format!("Video decode warning: {}", warning.message()));
+    }
+    DecodeReport { hardware_codecs, gpus, warnings }
+}
+
+/// Codecs with a hardware decoder
+fn hardware_codecs() -> Option<Vec<&'static str>> {
+    decoders(true)
+}
+
+/// Codecs Windows can decode at all: in hardware, in software or through an extension
+/// from the Store
+fn installed_codecs() -> Option<Vec<&'static str>> {
+    decoders(false)
+}
+
+#[cfg(windows)]
+fn decoders(hardware_only: bool) -> Option<Vec<&'static str>> {
+    use windows::core::GUID;
+    use windows::Win32::Media::MediaFoundation::{
+        IMFActivate, MFMediaType_Video, MFShutdown, MFStartup, MFTEnumEx, MFVideoFormat_AV1, MFVideoFormat_H264,
+        MFVideoFormat_HEVC, MFVideoFormat_VP90, MFSTARTUP_LITE, MFT_CATEGORY_VIDEO_DECODER, MFT_ENUM_FLAG_ALL,
+        MFT_ENUM_FLAG_HARDWARE, MFT_ENUM_FLAG_SORTANDFILTER, MFT_ENUM_FLAG_UNTRUSTED_STOREMFT, MFT_REGISTER_TYPE_INFO,
+        MF_VERSION,
+    };
+    use windows::Win32::System::Com::{CoInitializeEx, CoTaskMemFree, CoUninitialize, COINIT_MULTITHREADED};
+
+    const CODECS: &[(&str, GUID)] = &[
+        ("h264", MFVideoFormat_H264),
+        ("hevc", MFVideoFormat_HEVC),
+        ("vp9", MFVideoFormat_VP90),
+        ("av1", MFVideoFormat_AV1),
+    ];
+
+    // The HEVC and AV1 extensions are Store packages, left out unless asked for
+    let flags = if hardware_only {
+        MFT_ENUM_FLAG_HARDWARE | MFT_ENUM_FLAG_SORTANDFILTER
+    } else {
+        MFT_ENUM_FLAG_ALL | MFT_ENUM_FLAG_UNTRUSTED_STOREMFT | MFT_ENUM_FLAG_SORTANDFILTER
+    };
+    unsafe {
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+        if let Err(e) = MFStartup(MF_VERSION, MFSTARTUP_LITE) {
+            debug_log(&format!("Media Foundation unavailable: {}", e));
+            if initialized {
+                CoUninitialize();
+            }
+            return None;
+        }
+        let codecs = CODECS
+            .iter()
+            .filter(|(_, subtype)| {
+                let input = MFT_REGISTER_TYPE_INFO { guidMajorType: MFMediaType_Video, guidSubtype: *subtype };
+                let mut activates: *mut Option<IMFActivate> = std::ptr::null_mut();
+                let mut count = 0u32;
+                let found = MFTEnumEx(MFT_CATEGORY_VIDEO_DECODER, flags, Some(&input), None, &mut activates, &mut count)
+                    .is_ok()
+                    && count > 0;
+                // The caller releases each activation object and frees the array
+                if !activates.is_null() {
+                    for i in 0..count as usize {
+                        std::ptr::drop_in_place(activates.add(i));
+                    }
+                    CoTaskMemFree(Some(activates as *const _));
+                }
+                found
+            })
+            .map(|(name, _)| *name)
+            .collect();
+        let _ = MFShutdown();
+        if initialized {
+            CoUninitialize();
+        }
+        Some(codecs)
+    }
+}
+
+#[cfg(not(windows))]
+fn decoders(_hardware_only: bool) -> Option<Vec<&'static str>> {
+    None
+}
+
+/// A free codec extension from the Microsoft Store
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodecExtension {
+    pub codec: &'static str,
+    pub name: &'static str,
+    pub store_id: &'static str,
+}
+
+const EXTENSIONS: &[CodecExtension] = &[
+    CodecExtension { codec: "hevc", name: "HEVC Video Extensions from Device Manufacturer", store_id: "9N4WGH0Z6VHQ" },
+    CodecExtension { codec: "av1", name: "AV1 Video Extension", store_id: "9MVZQVXJBQ9V" },
+];
+
+/// The extensions for codecs Windows can't decode, which many anime releases use. Empty
+/// where the decoders can't be queried.
+pub fn missing_extensions() -> Vec<CodecExtension> {
+    let Some(installed) = installed_codecs() else { return Vec::new() };
+    let missing: Vec<CodecExtension> = EXTENSIONS.iter().filter(|ext| !installed.contains(&ext.codec)).cloned().collect();
+    if !missing.is_empty() {
+        debug_log(&format!("No decoder for: {:?}", missing.iter().map(|ext| ext.codec).collect::<Vec<_>>()));
+    }
+    missing
+}
+
+/// Open the Store page of the extension for `codec`
+pub fn open_store(codec: &str) -> Result<(), String> {
+    let ext = EXTENSIONS.iter().find(|ext| ext.codec == codec).ok_or_else(|| format!("No extension for {}", codec))?;
+    let url = format!("ms-windows-store://pdp/?ProductId={}", ext.store_id);
+    debug_log(&format!("Opening {}", url));
+    // explorer.exe exits with 1 even on success, so only a failed spawn is an error
+    std::process::Command::new("explorer.exe")
+        .arg(&url)
+        .spawn()
+        .map_err(|e| format!("Could not open the Microsoft Store: {}", e))?;
+    Ok(())
+}
+
+/// Display adapters with their drivers, from the display device class in the registry
+#[cfg(windows)]
+fn gpus() -> Vec<Gpu> {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    const DISPLAY_CLASS: &str = "SYSTEM\\CurrentControlSet\\Control\\Class\\{4d36e968-e325-11ce-bfc1-08002be10318}";
+    // FILETIME counts 100 ns intervals from 1601
+    const FILETIME_UNIX_EPOCH: u64 = 11_644_473_600;
+
+    let Ok(class) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(DISPLAY_CLASS) else { return Vec::new() };
+    class
+        .enum_keys()
+        .flatten()
+        // Adapters are 0000, 0001...; "Properties" isn't one, and isn't readable anyway
+        .filter(|name| name.bytes().all(|b| b.is_ascii_digit()))
+        .filter_map(|name| class.open_subkey(name).ok())
+        .filter_map(|key| {
+            let name: String = key.get_value("DriverDesc").ok()?;
+            let driver_version = key.get_value("DriverVersion").ok();
+            let driver_date = key.get_raw_value("DriverDateData").ok().and_then(|value| {
+                let filetime = u64::from_le_bytes(value.bytes.get(..8)?.try_into().ok()?);
+                (filetime / 10_000_000).checked_sub(FILETIME_UNIX_EPOCH)
+            });
+            Some(Gpu { name, driver_version, driver_date })
+        })
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn gpus() -> Vec<Gpu> {
+    Vec::new()
+}
@@ -0,0 +1,121 @@
+//! The yt-dlp executable some streaming sources need, kept in `<install>\tools`.
+//!
+//! The version is pinned by `yt-dlp.json` shipped with the payload (sidecar or archive
+//! root): a download URL and SHA-256. Only that pin is trusted, since the payload comes
+//! with an installer verified against the signed release manifest; a pin fetched at run
+//! time would let whoever can tamper with it choose both the binary and its hash, so the
+//! tool moves to a new version with the next app release. The pin that was installed is
+//! written next to the executable, which is how the installed version is known later.
+
+use std::path::{Path, PathBuf};
+
+use crate::{debug_log, download, hash, manifest, payload};
+
+pub const PIN_FILE: &str = "yt-dlp.json";
+
+/// Install-relative directory holding the executable and its pin
+pub const TOOLS_DIR: &str = "tools";
+
+#[cfg(windows)]
+const EXE_NAME: &str = "yt-dlp.exe";
+#[cfg(not(windows))]
+const EXE_NAME: &str = "yt-dlp";
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YtDlpPin {
+    pub version: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YtDlpStatus {
+    pub path: PathBuf,
+    pub installed: bool,
+    pub installed_version: Option<String>,
+    pub pinned_version: Option<String>,
+    /// The executable on disk matches the hash it was installed with
+    pub verified: bool,
+}
+
+pub fn read_pin(payload_path: &Path, is_7z: bool) -> Option<YtDlpPin> {
+    payload::read_json(payload_path, is_7z, PIN_FILE)
+}
+
+fn tools_dir(install_path: &str) -> PathBuf {
+    Path::new(install_path).join(TOOLS_DIR)
+}
+
+pub fn exe_path(install_path: &str) -> PathBuf {
+    tools_dir(install_path).join(EXE_NAME)
+}
+
+fn installed_pin(install_path: &str) -> Option<YtDlpPin> {
+    let json = std::fs::read_to_string(tools_dir(install_path).join(PIN_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+pub fn status(install_path: &str, pin: Option<&YtDlpPin>) -> YtDlpStatus {
+    let path = exe_path(install_path);
+    let installed = installed_pin(install_path);
+    let verified = installed
+        .as_ref()
+        .is_some_and(|i| hash::sha256_file(&path).is_ok_and(|actual| actual.eq_ignore_ascii_case(&i.sha256)));
+    YtDlpStatus {
+        installed: path.is_file(),
+        installed_version: installed.map(|i| i.version),
+        pinned_version: pin.map(|p| p.version.clone()),
+        verified,
+        path,
+    }
+}
+
+/// Download the pinned executable, verify it and put it in place of any existing one.
+/// Both files are recorded in the install manifest so uninstall removes them.
+pub fn install(install_path: &str, pin: &YtDlpPin) -> Result<YtDlpStatus, String> {
+    let dir = tools_dir(install_path);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.download", EXE_NAME));
+    debug_log(&format!("Downloading yt-dlp {} from {}", pin.version, pin.url));
+    download::download_file(&pin.url, &temp, |_, _| {})?;
+
+    let actual = hash::sha256_file(&temp)?;
+    if !actual.eq_ignore_ascii_case(&pin.sha256) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!("yt-dlp {} failed verification: expected {}, got {}", pin.version, pin.sha256, actual));
+    }
+    let exe = exe_path(install_path);
+    std::fs::rename(&temp, &exe).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        format!("Failed to replace {:?}: {}", exe, e)
+    })?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+
+    let json = serde_json::to_string_pretty(pin).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(PIN_FILE), json).map_err(|e| e.to_string())?;
+
+    manifest::update(install_path, |m| {
+        for file in [EXE_NAME, PIN_FILE].map(|name| format!("{}/{}", TOOLS_DIR, name)) {
+            if !m.component_files.contains(&file) {
+                m.component_files.push(file);
+            }
+        }
+    })?;
+    debug_log(&format!("Installed yt-dlp {}", pin.version));
+    Ok(status(install_path, Some(pin)))
+}
+
+/// Install `pin` unless that version is already installed intact
+pub fn update(install_path: &str, pin: &YtDlpPin) -> Result<YtDlpStatus, String> {
+    let current = status(install_path, Some(pin));
+    if current.verified && current.installed_version.as_deref() == Some(pin.version.as_str()) {
+        return Ok(current);
+    }
+    install(install_path, pin)
+}
@@ -1,28 +1,240 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
-import { open } from '@tauri-apps/plugin-dialog';
+import { ask, message, open } from '@tauri-apps/plugin-dialog';
 import { listen } from '@tauri-apps/api/event';
+import { resolveLanguage, translate } from './i18n';
 import './App.css';
 
 type Screen = 'install' | 'progress' | 'complete';
 
+interface Branding {
+    appName: string;
+    exeName: string;
+    installDirName: string;
+    telemetryUrl: string | null;
+    supportUrl: string | null;
+}
+
+interface PayloadSize {
+    total_bytes: number;
+    file_count: number;
+}
+
+interface License {
+    text: string;
+    version: string;
+}
+
+interface PathSuggestion {
+    path: string;
+    freeBytes: number | null;
+    isDefault: boolean;
+    recommended: boolean;
+}
+
+interface InstallOptions {
+    installPath: string;
+    desktopShortcut: boolean;
+    startMenuShortcut: boolean;
+    uninstallShortcut: boolean;
+    // null for the app's own folder, '' for the top level
+    startMenuFolder: string | null;
+    allUsersStartMenu: boolean;
+    extraShortcuts: ExtraShortcut[];
+    registerFileAssociations: boolean;
+    firewallRule: boolean;
+    defenderExclusion: boolean;
+    sendTelemetry: boolean;
+    launchOnFinish: boolean;
+    portable: boolean;
+    dataDir: string | null;
+    components: string[];
+    extensionsDir: string | null;
+    // Set by the deployment policy, never in the UI
+    updateUrl: string | null;
+}
+
+interface ExtraShortcut {
+    id: string;
+    name: string;
+    arguments: string;
+    selectedByDefault: boolean;
+}
+
+interface Component {
+    id: string;
+    name: string;
+    kind: 'core' | 'player' | 'codecs' | 'locales' | 'themes' | 'other';
+    required: boolean;
+    dependsOn: string[];
+    size: number;
+    url: string | null;
+}
+
+interface SystemInfo {
+    osName: string | null;
+    osBuild: string | null;
+    arch: string;
+    nativeArch: string | null;
+    cpu: string | null;
+    cpuCount: number;
+    memoryBytes: number | null;
+    locale: string;
+    darkMode: boolean | null;
+    displayScale: number | null;
+    folderFallbacks: FolderFallback[];
+}
+
+interface FolderFallback {
+    folder: string;
+    source: 'environment' | 'profile' | 'temp';
+    path: string;
+}
+
+interface RunningProcess {
+    pid: number;
+    name: string;
+    path: string;
+}
+
+interface Policy {
+    installPath: string | null;
+    disableAutoLaunch: boolean;
+    disableDesktopShortcut: boolean;
+}
+
+type InstallToggle = 'desktopShortcut' | 'startMenuShortcut' | 'registerFileAssociations' | 'firewallRule' | 'defenderExclusion' | 'portable';
+
+interface SyncWarning {
+    provider: 'one_drive' | 'dropbox' | 'google_drive';
+    syncRoot: string;
+    risks: string[];
+}
+
+// From check_video_decode: what the anime player can decode on the GPU
+interface DecodeReport {
+    hardwareCodecs: string[] | null;
+    warnings: (
+        | { code: 'software_decode' }
+        | { code: 'outdated_driver'; gpu: string; ageDays: number }
+    )[];
+}
+
+interface CodecExtension {
+    codec: string;
+    name: string;
+    storeId: string;
+}
+
+// Why this PC can't run the app
+type Unsupported =
+    | { reason: 'not_64_bit' }
+    | { reason: 'windows_too_old'; build: number; minimum: number };
+
+const SYNC_PROVIDERS: Record<SyncWarning['provider'], string> = {
+    one_drive: 'OneDrive',
+    dropbox: 'Dropbox',
+    google_drive: 'Google Drive',
+};
+
+type InstallDirCheck =
+    | { kind: 'empty' }
+    | { kind: 'existing_install'; version: string | null }
+    | { kind: 'not_empty'; entries: number };
+
 interface ProgressPayload {
-    status: string;
+    key: string;
+    params: Record<string, string>;
     percent: number;
+    // Only on byte-counted stages (extraction, downloads)
+    bytes_processed?: number;
+    bytes_total?: number | null;
+    bytes_per_second?: number | null;
+    seconds_remaining?: number | null;
+}
+
+/** "120 MB/s — about 15 s left", or null before there is a rate to show */
+function transferText(lang: string, progress: ProgressPayload): string | null {
+    if (!progress.bytes_per_second) return null;
+    const rate = `${Math.max(1, Math.round(progress.bytes_per_second / (1024 * 1024)))} MB/s`;
+    if (progress.seconds_remaining == null) return rate;
+    return translate(lang, 'time_left', { rate, seconds: String(progress.seconds_remaining) });
 }
 
 function App() {
     const [screen, setScreen] = useState<Screen>('install');
     const [installPath, setInstallPath] = useState('');
-    const [progress, setProgress] = useState({ status: '', percent: 0 });
+    const [progress, setProgress] = useState<ProgressPayload>({ key: 'preparing', params: {}, percent: 0 });
+    const [language, setLanguage] = useState('en');
+    const [branding, setBranding] = useState<Branding>({ appName: 'Mangyomi', exeName: 'Mangyomi.exe', installDirName: 'Mangyomi', telemetryUrl: null, supportUrl: null });
     const [exePath, setExePath] = useState('');
     const [launchOnClose, setLaunchOnClose] = useState(true);
+    // Passed to the app as --first-run so it opens on onboarding and import
+    const [openOnboarding, setOpenOnboarding] = useState(true);
+    const [policy, setPolicy] = useState<Policy>({ installPath: null, disableAutoLaunch: false, disableDesktopShortcut: false });
+    const [options, setOptions] = useState<Omit<InstallOptions, 'installPath'>>({
+        desktopShortcut: true,
+        startMenuShortcut: true,
+        uninstallShortcut: true,
+        startMenuFolder: null,
+        allUsersStartMenu: false,
+        extraShortcuts: [],
+        registerFileAssociations: true,
+        firewallRule: false,
+        defenderExclusion: false,
+        sendTelemetry: false,
+        launchOnFinish: true,
+        portable: false,
+        dataDir: null,
+        components: [],
+        extensionsDir: null,
+        updateUrl: null,
+    });
+    const [availableComponents, setAvailableComponents] = useState<Component[]>([]);
+    const [availableShortcuts, setAvailableShortcuts] = useState<ExtraShortcut[]>([]);
     const [error, setError] = useState('');
+    const [requiredBytes, setRequiredBytes] = useState<number | null>(null);
+    const [pathSuggestions, setPathSuggestions] = useState<PathSuggestion[]>([]);
+    const [license, setLicense] = useState<License | null>(null);
+    const [licenseAccepted, setLicenseAccepted] = useState(false);
+    const [payloadVersion, setPayloadVersion] = useState<string | null>(null);
+    const [paused, setPaused] = useState(false);
+    const [systemInfo, setSystemInfo] = useState<SystemInfo | null>(null);
+    const [unsupported, setUnsupported] = useState<Unsupported | null>(null);
+    const [decodeReport, setDecodeReport] = useState<DecodeReport | null>(null);
+    const [missingCodecs, setMissingCodecs] = useState<CodecExtension[]>([]);
+    // Started from "Modify" in Apps & Features: change the existing install in place
+    const [maintenance, setMaintenance] = useState(false);
 
     useEffect(() => {
-        // Get default install path
-        invoke<string>('get_default_path').then(setInstallPath).catch(console.error);
+        // The install being modified, or the default path for a new one
+        invoke<string | null>('get_maintenance_path')
+            .then(path => {
+                if (path) {
+                    setMaintenance(true);
+                    setOpenOnboarding(false);
+                    setInstallPath(path);
+                    return;
+                }
+                return invoke<PathSuggestion[]>('suggest_install_paths').then(suggestions => {
+                    setPathSuggestions(suggestions);
+                    const recommended = suggestions.find(s => s.recommended) ?? suggestions[0];
+                    if (recommended) setInstallPath(recommended.path);
+                });
+            })
+            .catch(console.error);
+        invoke<string | null>('get_payload_version').then(setPayloadVersion).catch(console.error);
+        invoke<License | null>('get_license').then(setLicense).catch(console.error);
+        invoke<PayloadSize>('estimate_install_size').then(size => setRequiredBytes(size.total_bytes)).catch(console.error);
+        invoke<Component[]>('get_components').then(setAvailableComponents).catch(console.error);
+        invoke<ExtraShortcut[]>('get_extra_shortcuts').then(setAvailableShortcuts).catch(console.error);
+        invoke<Policy>('get_policy').then(setPolicy).catch(console.error);
+        invoke<Branding>('get_branding').then(setBranding).catch(console.error);
+        invoke<SystemInfo>('get_system_info').then(setSystemInfo).catch(console.error);
+        invoke<Unsupported | null>('check_requirements').then(setUnsupported).catch(console.error);
+        invoke<CodecExtension[]>('get_missing_codecs').then(setMissingCodecs).catch(console.error);
+        invoke<string>('get_locale').then(locale => setLanguage(resolveLanguage(locale))).catch(console.error);
 
         // Listen for progress updates
         const unlisten = listen<ProgressPayload>('install-progress', (event) => {
@@ -32,37 +244,194 @@ function App() {
             }
         });
 
+        // Sent by the installer's panic handler just before it exits
+        const unlistenError = listen<{ message: string }>('install-error', (event) => {
+            setError(event.payload.message);
+            setScreen('install');
+        });
+
         return () => {
             unlisten.then(f => f());
+            unlistenError.then(f => f());
         };
     }, []);
 
+    // Only worth asking the GPU once the player is part of the install
+    const playerSelected = availableComponents.some(c => c.kind === 'player' && options.components.includes(c.id));
+    useEffect(() => {
+        if (!playerSelected || decodeReport) return;
+        invoke<DecodeReport>('check_video_decode').then(setDecodeReport).catch(console.error);
+    }, [playerSelected, decodeReport]);
+
+    // Start from the choices saved by an existing install at this location
+    useEffect(() => {
+        if (!installPath) return;
+        invoke<InstallOptions>('get_install_options', { installPath })
+            .then(({ installPath: _, ...saved }) => {
+                setOptions(saved);
+                setLaunchOnClose(saved.launchOnFinish);
+            })
+            .catch(console.error);
+    }, [installPath]);
+
+    const toggleOption = async (key: InstallToggle) => {
+        // Turning off antivirus scanning must be a deliberate choice
+        if (key === 'defenderExclusion' && !options.defenderExclusion) {
+            const confirmed = await ask(
+                `Microsoft Defender will stop scanning the ${branding.appName} folder. Only do this if Defender keeps quarantining ${branding.appName}'s files. Continue?`,
+                { title: `${branding.appName} Setup`, kind: 'warning' },
+            );
+            if (!confirmed) return;
+        }
+        setOptions(o => ({ ...o, [key]: !o[key] }));
+    };
+
+    // Selecting a component selects what it depends on; deselecting one deselects its dependents
+    const toggleShortcut = (shortcut: ExtraShortcut) => setOptions(o => ({
+        ...o,
+        extraShortcuts: o.extraShortcuts.some(s => s.id === shortcut.id)
+            ? o.extraShortcuts.filter(s => s.id !== shortcut.id)
+            : [...o.extraShortcuts, shortcut],
+    }));
+
+    const toggleComponent = (id: string) => setOptions(o => {
+        const selected = new Set(o.components);
+        const visit = (current: string, select: boolean) => {
+            if (selected.has(current) === select) return;
+            if (select) selected.add(current); else selected.delete(current);
+            availableComponents
+                .filter(c => select ? c.id === current : c.dependsOn.includes(current))
+                .forEach(c => select ? c.dependsOn.forEach(dep => visit(dep, true)) : visit(c.id, false));
+        };
+        visit(id, !selected.has(id));
+        return { ...o, components: [...selected] };
+    });
+
     const handleBrowse = async () => {
         const selected = await open({
             directory: true,
             multiple: false,
         });
         if (selected) {
-            setInstallPath(selected as string + '\\Mangyomi');
+            setInstallPath(`${selected as string}\\${branding.installDirName}`);
+        }
+    };
+
+    const handleBrowseDataDir = async () => {
+        const selected = await open({
+            directory: true,
+            multiple: false,
+        });
+        if (selected) {
+            setOptions(o => ({ ...o, dataDir: selected as string }));
+        }
+    };
+
+    const handleBrowseExtensionsDir = async () => {
+        const selected = await open({
+            directory: true,
+            multiple: false,
+        });
+        if (selected) {
+            setOptions(o => ({ ...o, extensionsDir: selected as string }));
         }
     };
 
+    // Confirm before mixing our files into a folder that holds something else
+    const confirmFolder = async (command: string, args: Record<string, string>, path: string) => {
+        const check = await invoke<InstallDirCheck>(command, args);
+        if (check.kind !== 'not_empty') return true;
+        return ask(
+            `${path} already contains ${check.entries} item(s) that don't belong to ${branding.appName}. Use it anyway?`,
+            { title: `${branding.appName} Setup`, kind: 'warning' },
+        );
+    };
+
     const handleInstall = async () => {
+        try {
+            if (!await confirmFolder('check_install_dir', { installPath }, installPath)) return;
+            const sync = await invoke<SyncWarning | null>('check_cloud_sync', { installPath });
+            if (sync && !await ask(
+                `${installPath} is inside your ${SYNC_PROVIDERS[sync.provider]} folder. Every update would be uploaded, `
+                + `the sync client can lock files while ${branding.appName} runs, and the app counts against your `
+                + `storage quota. Install here anyway?`,
+                { title: `${branding.appName} Setup`, kind: 'warning' },
+            )) return;
+            if (options.dataDir && !await confirmFolder('check_data_dir', { dataDir: options.dataDir }, options.dataDir)) return;
+            // Running copies would hold their files open through the install
+            let blocking = await invoke<RunningProcess[]>('close_running_apps', { installPath });
+            if (blocking.length > 0) {
+                const list = blocking.map(p => `${p.name} (PID ${p.pid})`).join(', ');
+                const force = await ask(
+                    `These programs are still running: ${list}. Force them to close? Unsaved work in them will be lost.`,
+                    { title: `${branding.appName} Setup`, kind: 'warning' },
+                );
+                if (force) {
+                    blocking = await invoke<RunningProcess[]>('close_running_apps', { installPath, force: true });
+                }
+            }
+            if (blocking.length > 0) {
+                const list = blocking.map(p => `${p.name} (PID ${p.pid})`).join(', ');
+                setError(`Close these programs and try again: ${list}`);
+                return;
+            }
+        } catch (err) {
+            setError(String(err));
+            return;
+        }
+
         setScreen('progress');
         setError('');
         try {
-            await invoke('install_app', { installPath });
-            setExePath(`${installPath}\\Mangyomi.exe`); // Assuming standard path
+            if (license) {
+                await invoke('accept_license', { version: license.version });
+            }
+            await invoke('install_app', { options: { ...options, installPath } });
+            setExePath(`${installPath}\\${branding.exeName}`);
         } catch (err) {
             setError(String(err) || 'Installation failed');
             setScreen('install');
         }
     };
 
+    const togglePause = async () => {
+        try {
+            await invoke(paused ? 'resume_install' : 'pause_install');
+            setPaused(!paused);
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
+    // install_app rejects with the cancel message, which takes us back to the install screen
+    const cancelInstall = async () => {
+        try {
+            await invoke('cancel_install');
+            setPaused(false);
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
+    // Opt-in: nothing leaves the machine unless the user confirms
+    const handleUploadLogs = async () => {
+        const confirmed = await ask(
+            'Send the installer log and a description of this PC to the developers? The log includes folder paths.',
+            { title: `${branding.appName} Setup` },
+        );
+        if (!confirmed) return;
+        try {
+            const id = await invoke<string>('upload_logs');
+            await message(`Logs sent. Mention reference ${id} in your bug report.`, { title: `${branding.appName} Setup` });
+        } catch (err) {
+            await message(`Could not send logs: ${err}`, { title: `${branding.appName} Setup`, kind: 'error' });
+        }
+    };
+
     const handleFinish = async () => {
-        if (launchOnClose && exePath) {
+        if (launchOnClose && !policy.disableAutoLaunch && exePath) {
             try {
-                await invoke('launch_app', { exePath });
+                await invoke('launch_app', { exePath, args: openOnboarding ? ['--first-run'] : [] });
             } catch (e) {
                 console.error(e);
             }
@@ -75,7 +444,7 @@ function App() {
             {/* Title Bar */}
             <div className="title-bar" data-tauri-drag-region>
                 <div className="title-bar-drag" data-tauri-drag-region>
-                    <span className="title">Mangyomi Setup</span>
+                    <span className="title">{branding.appName} Setup</span>
                 </div>
                 <div className="title-bar-buttons">
                     <button className="title-btn minimize" onClick={() => getCurrentWindow().minimize()}>
@@ -93,10 +462,30 @@ function App() {
                     <div className="screen install-screen">
                         <div className="logo-section">
                             <div className="logo">
-                                <img src="/icon.png" alt="Mangyomi" width="64" height="64" />
+                                <img src="/icon.png" alt={branding.appName} width="64" height="64" />
                             </div>
-                            <h1>Welcome to Mangyomi</h1>
-                            <p>A beautiful manga reader for your desktop</p>
+                            {maintenance ? (
+                                <>
+                                    <h1>Modify {branding.appName}</h1>
+                                    <p>Add or remove optional components</p>
+                                </>
+                            ) : (
+                                <>
+                                    <h1>Welcome to {branding.appName}</h1>
+                                    <p>A beautiful manga reader for your desktop</p>
+                                </>
+                            )}
+                            {payloadVersion && <p className="version-text">Version {payloadVersion}</p>}
+                            {systemInfo?.nativeArch && systemInfo.nativeArch !== systemInfo.arch && (
+                                <p className="size-hint">
+                                    This {systemInfo.arch} build runs under emulation on your {systemInfo.nativeArch} PC and may be slower.
+                                </p>
+                            )}
+                            {systemInfo?.folderFallbacks.some(f => f.source === 'temp') && (
+                                <p className="size-hint">
+                                    Windows didn't report your AppData folder, so logs and the update cache go to a temporary folder this time.
+                                </p>
+                            )}
                         </div>
 
                         <div className="install-options">
@@ -108,14 +497,183 @@ function App() {
                                     onChange={(e) => setInstallPath(e.target.value)}
                                     readOnly
                                 />
-                                <button className="browse-btn" onClick={handleBrowse}>Browse</button>
+                                <button className="browse-btn" onClick={handleBrowse} disabled={maintenance || policy.installPath !== null}>Browse</button>
+                            </div>
+                            {requiredBytes !== null && (
+                                <p className="size-hint">Requires {Math.ceil(requiredBytes / (1024 * 1024))} MB</p>
+                            )}
+                            {/* Only worth showing when the default drive is the problem */}
+                            {!maintenance && pathSuggestions.some(s => s.recommended && !s.isDefault) && (
+                                <div className="path-suggestions">
+                                    <p className="size-hint">Your system drive is low on space. Other drives:</p>
+                                    {pathSuggestions.filter(s => s.path !== installPath).map(s => (
+                                        <button key={s.path} className="link-btn" onClick={() => setInstallPath(s.path)}>
+                                            {s.path}
+                                            {s.freeBytes !== null && ` (${Math.floor(s.freeBytes / (1024 * 1024 * 1024))} GB free)`}
+                                        </button>
+                                    ))}
+                                </div>
+                            )}
+                            <label className="input-label data-dir-label">Library Data Location</label>
+                            <div className="path-input">
+                                <input
+                                    type="text"
+                                    value={options.dataDir ?? ''}
+                                    placeholder="Default (AppData)"
+                                    readOnly
+                                />
+                                <button className="browse-btn" onClick={handleBrowseDataDir}>Browse</button>
+                            </div>
+                            <label className="input-label data-dir-label">Extensions to Preinstall</label>
+                            <div className="path-input">
+                                <input
+                                    type="text"
+                                    value={options.extensionsDir ?? ''}
+                                    placeholder="Bundled extensions only"
+                                    readOnly
+                                />
+                                <button className="browse-btn" onClick={handleBrowseExtensionsDir}>Browse</button>
                             </div>
+                            {([
+                                ['desktopShortcut', 'Create a desktop shortcut'],
+                                ['startMenuShortcut', 'Add to the Start Menu'],
+                                ['registerFileAssociations', `Open backup files with ${branding.appName}`],
+                                ['firewallRule', 'Allow local network sharing through the firewall (asks for administrator rights)'],
+                                ['defenderExclusion', 'Exclude the install folder from Microsoft Defender scans (not recommended; asks for administrator rights)'],
+                                ['portable', 'Portable install (no shortcuts or system changes)'],
+                            ] as [InstallToggle, string][]).map(([key, label]) => (
+                                <label key={key} className="launch-checkbox option-checkbox">
+                                    <input
+                                        type="checkbox"
+                                        checked={options[key]}
+                                        onChange={() => toggleOption(key)}
+                                        disabled={key === 'desktopShortcut' && policy.disableDesktopShortcut}
+                                    />
+                                    <span className="checkmark-box"></span>
+                                    <span>{label}</span>
+                                </label>
+                            ))}
+                            {options.startMenuShortcut && !options.portable && (
+                                <>
+                                    <label className="input-label data-dir-label">Start Menu Folder</label>
+                                    <div className="path-input">
+                                        <input
+                                            type="text"
+                                            value={options.startMenuFolder ?? branding.installDirName}
+                                            onChange={e => setOptions(o => ({ ...o, startMenuFolder: e.target.value }))}
+                                            disabled={options.startMenuFolder === ''}
+                                        />
+                                    </div>
+                                    <label className="launch-checkbox option-checkbox">
+                                        <input
+                                            type="checkbox"
+                                            checked={options.startMenuFolder === ''}
+                                            onChange={() => setOptions(o => ({ ...o, startMenuFolder: o.startMenuFolder === '' ? null : '' }))}
+                                        />
+                                        <span className="checkmark-box"></span>
+                                        <span>Put shortcuts at the top level of the Start Menu</span>
+                                    </label>
+                                    <label className="launch-checkbox option-checkbox">
+                                        <input
+                                            type="checkbox"
+                                            checked={options.allUsersStartMenu}
+                                            onChange={() => setOptions(o => ({ ...o, allUsersStartMenu: !o.allUsersStartMenu }))}
+                                        />
+                                        <span className="checkmark-box"></span>
+                                        <span>Add shortcuts for all users (needs administrator rights)</span>
+                                    </label>
+                                    {availableShortcuts.map(shortcut => (
+                                        <label key={shortcut.id} className="launch-checkbox option-checkbox">
+                                            <input
+                                                type="checkbox"
+                                                checked={options.extraShortcuts.some(s => s.id === shortcut.id)}
+                                                onChange={() => toggleShortcut(shortcut)}
+                                            />
+                                            <span className="checkmark-box"></span>
+                                            <span>Add a "{shortcut.name}" shortcut</span>
+                                        </label>
+                                    ))}
+                                </>
+                            )}
+                            {branding.telemetryUrl && (
+                                <label className="launch-checkbox option-checkbox">
+                                    <input
+                                        type="checkbox"
+                                        checked={options.sendTelemetry}
+                                        onChange={() => setOptions(o => ({ ...o, sendTelemetry: !o.sendTelemetry }))}
+                                    />
+                                    <span className="checkmark-box"></span>
+                                    <span>Send an anonymous report of whether setup succeeded (version, Windows build, duration)</span>
+                                </label>
+                            )}
+                            {availableComponents.filter(component => !component.required).map(component => (
+                                <label key={component.id} className="launch-checkbox option-checkbox">
+                                    <input
+                                        type="checkbox"
+                                        checked={options.components.includes(component.id)}
+                                        onChange={() => toggleComponent(component.id)}
+                                    />
+                                    <span className="checkmark-box"></span>
+                                    <span>
+                                        {component.name} ({Math.ceil(component.size / (1024 * 1024))} MB{component.url ? ' download' : ''})
+                                    </span>
+                                </label>
+                            ))}
+                            {playerSelected && decodeReport?.warnings.map((warning, i) => (
+                                <p key={i} className="size-hint">
+                                    {warning.code === 'software_decode'
+                                        ? 'No hardware video decoder was found, so videos will play on the CPU and may stutter. Installing your graphics driver usually fixes this.'
+                                        : `Your ${warning.gpu} driver is ${Math.floor(warning.ageDays / 365)} years old. Updating it can fix video playback problems.`}
+                                </p>
+                            ))}
+                            {missingCodecs.length > 0 && (
+                                <p className="size-hint">
+                                    Windows can't play {missingCodecs.map(ext => ext.codec.toUpperCase()).join(' or ')} videos yet. Get the free
+                                    {missingCodecs.map(ext => (
+                                        <button key={ext.codec} className="link-btn" onClick={() => invoke('open_codec_store', { codec: ext.codec }).catch(console.error)}>
+                                            {ext.name}
+                                        </button>
+                                    ))}
+                                    {availableComponents.some(c => c.kind === 'codecs') && ' or keep the bundled codecs selected'}.
+                                </p>
+                            )}
+                            {license && (
+                                <>
+                                    <label className="input-label data-dir-label">License Agreement</label>
+                                    <pre className="license-text">{license.text}</pre>
+                                    <label className="launch-checkbox option-checkbox">
+                                        <input
+                                            type="checkbox"
+                                            checked={licenseAccepted}
+                                            onChange={(e) => setLicenseAccepted(e.target.checked)}
+                                        />
+                                        <span className="checkmark-box"></span>
+                                        <span>I accept the license agreement</span>
+                                    </label>
+                                </>
+                            )}
                         </div>
 
-                        {error && <div className="error-message">{error}</div>}
+                        {unsupported && (
+                            <div className="error-message">
+                                {unsupported.reason === 'not_64_bit'
+                                    ? `${branding.appName} needs a 64-bit version of Windows, and this PC runs a 32-bit one.`
+                                    : `${branding.appName} needs Windows 10 or later (build ${unsupported.minimum}), and this PC runs build ${unsupported.build}. Update Windows to install it.`}
+                            </div>
+                        )}
+
+                        {error && (
+                            <div className="error-message">
+                                {error}
+                                <button className="link-btn" onClick={() => invoke('open_logs_dir').catch(console.error)}>Show logs</button>
+                                {branding.supportUrl && (
+                                    <button className="link-btn" onClick={handleUploadLogs}>Send logs</button>
+                                )}
+                            </div>
+                        )}
 
-                        <button className="install-btn" onClick={handleInstall}>
-                            <span>Install</span>
+                        <button className="install-btn" onClick={handleInstall} disabled={unsupported !== null || (license !== null && !licenseAccepted)}>
+                            <span>{maintenance ? 'Apply changes' : 'Install'}</span>
                             <svg width="20" height="20" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2">
                                 <path d="M5 12h14M12 5l7 7-7 7" />
                             </svg>
@@ -129,8 +687,8 @@ function App() {
                             <div className="spinner">
                                 <div className="spinner-ring"></div>
                             </div>
-                            <h2>Installing Mangyomi</h2>
-                            <p className="status-text">{progress.status || 'Preparing installation...'}</p>
+                            <h2>Installing {branding.appName}</h2>
+                            <p className="status-text">{translate(language, progress.key, progress.params)}</p>
                             <div className="progress-bar">
                                 <div
                                     className="progress-fill"
@@ -138,6 +696,21 @@ function App() {
                                 />
                             </div>
                             <span className="progress-percent">{progress.percent}%</span>
+                            {paused ? (
+                                <p className="transfer-text">{translate(language, 'paused')}</p>
+                            ) : transferText(language, progress) && (
+                                <p className="transfer-text">{transferText(language, progress)}</p>
+                            )}
+                            {progress.key === 'extracting_file' && (
+                                <div className="extract-actions">
+                                    <button className="link-btn" onClick={togglePause}>
+                                        {translate(language, paused ? 'resume' : 'pause')}
+                                    </button>
+                                    <button className="link-btn" onClick={cancelInstall}>
+                                        {translate(language, 'cancel')}
+                                    </button>
+                                </div>
+                            )}
                         </div>
                     </div>
                 )}
@@ -157,21 +730,40 @@ function App() {
                             </svg>
                         </div>
                         <h2>Installation Complete!</h2>
-                        <p>Mangyomi has been installed successfully.</p>
-
-                        <label className="launch-checkbox">
-                            <input
-                                type="checkbox"
-                                checked={launchOnClose}
-                                onChange={(e) => setLaunchOnClose(e.target.checked)}
-                            />
-                            <span className="checkmark-box"></span>
-                            <span>Launch Mangyomi</span>
-                        </label>
+                        <p>{branding.appName} has been installed successfully.</p>
+                        {progress.key === 'done_pending_restart' && (
+                            <p>{translate(language, 'done_pending_restart')}</p>
+                        )}
+
+                        {!policy.disableAutoLaunch && (
+                            <label className="launch-checkbox">
+                                <input
+                                    type="checkbox"
+                                    checked={launchOnClose}
+                                    onChange={(e) => setLaunchOnClose(e.target.checked)}
+                                />
+                                <span className="checkmark-box"></span>
+                                <span>Launch {branding.appName}</span>
+                            </label>
+                        )}
+                        {!policy.disableAutoLaunch && launchOnClose && (
+                            <label className="launch-checkbox">
+                                <input
+                                    type="checkbox"
+                                    checked={openOnboarding}
+                                    onChange={(e) => setOpenOnboarding(e.target.checked)}
+                                />
+                                <span className="checkmark-box"></span>
+                                <span>Start with setup and library import</span>
+                            </label>
+                        )}
 
                         <button className="finish-btn" onClick={handleFinish}>
                             Finish
                         </button>
+                        <button className="link-btn" onClick={() => invoke('open_install_dir', { installPath }).catch(console.error)}>
+                            Open folder
+                        </button>
                     </div>
                 )}
             </div>
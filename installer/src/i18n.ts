@@ -0,0 +1,53 @@
+// Translations for the message keys the installer backend emits in progress events.
+// Missing keys fall back to English, then to the key itself.
+
+type Messages = Record<string, string>;
+
+const messages: Record<string, Messages> = {
+    en: {
+        preparing: 'Preparing installation...',
+        extracting: 'Extracting files...',
+        extracting_file: 'Extracting {file}...',
+        verifying: 'Verifying...',
+        creating_shortcuts: 'Creating shortcuts...',
+        setting_up_updates: 'Setting up updates...',
+        done: 'Done!',
+        done_pending_restart: 'Some files were in use; they will be replaced after you sign in again.',
+        migrating: 'Importing {item}...',
+        downloading_component: 'Downloading {name}...',
+        time_left: '{rate} — about {seconds} s left',
+        paused: 'Paused',
+        pause: 'Pause',
+        resume: 'Resume',
+        cancel: 'Cancel',
+    },
+    ja: {
+        preparing: 'インストールの準備中...',
+        extracting: 'ファイルを展開中...',
+        extracting_file: '{file} を展開中...',
+        verifying: 'ファイルを検証中...',
+        creating_shortcuts: 'ショートカットを作成中...',
+        setting_up_updates: 'アップデートを設定中...',
+        done: '完了しました',
+        done_pending_restart: '使用中のファイルがあったため、次回サインイン時に置き換えられます。',
+        migrating: '{item} をインポート中...',
+        downloading_component: '{name} をダウンロード中...',
+        time_left: '{rate} — 残り約 {seconds} 秒',
+        paused: '一時停止中',
+        pause: '一時停止',
+        resume: '再開',
+        cancel: 'キャンセル',
+    },
+};
+
+/** Pick the best supported language for a BCP 47 tag such as `ja-JP` */
+export function resolveLanguage(locale: string): string {
+    const lang = locale.toLowerCase().split('-')[0];
+    return lang in messages ? lang : 'en';
+}
+
+/** Look up `key` and substitute `{name}` placeholders from `params` */
+export function translate(lang: string, key: string, params: Record<string, string> = {}): string {
+    const template = messages[lang]?.[key] ?? messages.en[key] ?? key;
+    return template.replace(/\{(\w+)\}/g, (match, name) => params[name] ?? match);
+}
@@ -0,0 +1,94 @@
+/**
+ * Writes and signs the release manifest the app's updater verifies
+ * Usage: node scripts/sign-release-manifest.js <artifacts dir> <version> <stable|nightly>
+ *        node scripts/sign-release-manifest.js --refresh <dir>
+ *
+ * Every file in <artifacts dir> is listed with its size and SHA-512 in
+ * release-manifest.json, and release-manifest.json.sig holds one Ed25519 signature per
+ * key in UPDATE_SIGNING_KEYS, a JSON object of key ID to PEM private key. Signing with
+ * two keys is how a key rotation starts; see docs/update-signing.md.
+ *
+ * Installers named Mangyomi-Installer-from-<version>.exe carry a delta payload against
+ * that version and are listed under `deltas`, so the updater can pick the smallest
+ * download that applies to the installed version.
+ *
+ * Each signing gets a new sequence number (the signing time in seconds, so it only grows)
+ * and an expiry EXPIRY_DAYS out. --refresh re-signs the manifest already in <dir> with a
+ * new sequence and expiry, for the scheduled job that keeps the latest releases current.
+ */
+
+const fs = require('fs');
+const path = require('path');
+const crypto = require('crypto');
+
+const MANIFEST_FILE = 'release-manifest.json';
+const EXPIRY_DAYS = 21;
+const CHANNELS = ['stable', 'nightly'];
+const DELTA_PATTERN = /^Mangyomi-Installer-from-(.+)\.exe$/;
+
+const refresh = process.argv[2] === '--refresh';
+const [dir, version, channel] = process.argv.slice(refresh ? 3 : 2);
+if (!dir || (!refresh && (!version || !CHANNELS.includes(channel)))) {
+    console.error('Usage: node scripts/sign-release-manifest.js <artifacts dir> <version> <stable|nightly>');
+    console.error('       node scripts/sign-release-manifest.js --refresh <dir>');
+    process.exit(1);
+}
+
+let keys;
+try {
+    keys = JSON.parse(process.env.UPDATE_SIGNING_KEYS || '');
+} catch {
+    console.error('ERROR: UPDATE_SIGNING_KEYS must be a JSON object of key ID to PEM private key');
+    process.exit(1);
+}
+if (Object.keys(keys).length === 0) {
+    console.error('ERROR: UPDATE_SIGNING_KEYS has no keys');
+    process.exit(1);
+}
+
+function listFiles() {
+    const files = {};
+    for (const name of fs.readdirSync(dir).sort()) {
+        const filePath = path.join(dir, name);
+        if (!fs.statSync(filePath).isFile() || name.startsWith(MANIFEST_FILE)) continue;
+        const data = fs.readFileSync(filePath);
+        files[name] = { size: data.length, sha512: crypto.createHash('sha512').update(data).digest('base64') };
+    }
+    return files;
+}
+
+const now = new Date();
+const base = refresh
+    ? JSON.parse(fs.readFileSync(path.join(dir, MANIFEST_FILE), 'utf-8'))
+    : { version, channel, publishedAt: now.toISOString(), files: listFiles() };
+const deltas = Object.keys(base.files)
+    .map(file => ({ from: file.match(DELTA_PATTERN)?.[1], file }))
+    .filter(delta => delta.from);
+if (!CHANNELS.includes(base.channel)) {
+    console.error(`ERROR: ${MANIFEST_FILE} has no channel; it predates sequence numbers and can't be refreshed`);
+    process.exit(1);
+}
+const content = {
+    version: base.version,
+    channel: base.channel,
+    sequence: Math.floor(now.getTime() / 1000),
+    publishedAt: base.publishedAt,
+    expires: new Date(now.getTime() + EXPIRY_DAYS * 24 * 60 * 60 * 1000).toISOString(),
+    files: base.files,
+    deltas,
+};
+const manifest = Buffer.from(JSON.stringify(content, null, 2) + '\n');
+const signatures = Object.entries(keys).map(([keyId, pem]) => ({
+    keyId,
+    signature: crypto.sign(null, manifest, crypto.createPrivateKey(pem)).toString('base64'),
+}));
+
+fs.writeFileSync(path.join(dir, MANIFEST_FILE), manifest);
+fs.writeFileSync(path.join(dir, `${MANIFEST_FILE}.sig`), JSON.stringify({ signatures }, null, 2) + '\n');
+
+console.log(`=== RELEASE MANIFEST ===`);
+console.log(`Version: ${content.version} (${content.channel})`);
+console.log(`Sequence: ${content.sequence}, expires ${content.expires}`);
+console.log(`Files: ${Object.keys(content.files).join(', ')}`);
+if (deltas.length > 0) console.log(`Deltas from: ${deltas.map(d => d.from).join(', ')}`);
+console.log(`Signed with: ${signatures.map(s => s.keyId).join(', ')}`);
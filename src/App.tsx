@@ -78,6 +78,16 @@ function App() {
         return unsubscribe;
     }, []);
 
+    // Open the section a shortcut or the installer launched the app into
+    useEffect(() => {
+        window.electronAPI.app.takeLaunchRoute().then(route => {
+            if (route) window.location.hash = `#${route}`;
+        });
+        return window.electronAPI.app.onNavigate((_, route) => {
+            window.location.hash = `#${route}`;
+        });
+    }, []);
+
     const handleCaptchaSolved = () => {
         const callback = captchaCallback;
         hideCaptcha();
@@ -1,4 +1,5 @@
 import { useState, useEffect, useMemo } from 'react';
+import { useSearchParams } from 'react-router-dom';
 import { useSettingsStore, Theme, ReaderMode, ProxyConfig, ProxyType } from '../stores/settingsStore';
 import { useAniListStore } from '../../../stores/anilistStore';
 import { useUpdateStore } from '../../../stores/updateStore';
@@ -163,11 +164,18 @@ const SETTING_DEFINITIONS: SettingDef[] = [
     { id: 'about-github', category: 'about', label: 'GitHub', description: 'View source code on GitHub', keywords: ['github', 'source', 'code', 'repository'] },
     { id: 'about-update', category: 'about', label: 'Check for Updates', description: 'Check if a newer version is available', keywords: ['update', 'upgrade', 'new', 'version'] },
     { id: 'about-beta', category: 'about', label: 'Beta Updates', description: 'Receive nightly/beta releases', keywords: ['beta', 'nightly', 'preview', 'experimental'] },
+    { id: 'about-lan-sharing', category: 'about', label: 'Share Updates on Local Network', description: 'Get and share update downloads with other computers on your network', keywords: ['lan', 'network', 'peer', 'share', 'download', 'bandwidth'] },
 ];
 
 function Settings() {
     const [cacheSize, setCacheSize] = useState<number>(0);
     const [activeCategory, setActiveCategory] = useState<CategoryId>('general');
+    // `#/settings?category=backup` opens a category directly, e.g. on first run
+    const [searchParams] = useSearchParams();
+    useEffect(() => {
+        const requested = CATEGORIES.find(c => c.id === searchParams.get('category'));
+        if (requested) setActiveCategory(requested.id);
+    }, [searchParams]);
     const [searchQuery, setSearchQuery] = useState('');
     const [memoryMonitorEnabled, setMemoryMonitorEnabled] = useState(false);
     const { stats: memoryStats } = useMemoryMonitor(memoryMonitorEnabled);
@@ -285,11 +293,11 @@ function Settings() {
 
     const {
         theme, defaultReaderMode, prefetchChapters, maxCacheSize, ignoreCacheLimitForPrefetch, logLevel,
-        hideNsfwInLibrary, hideNsfwInHistory, hideNsfwInTags, hideNsfwCompletely, developerMode, betaUpdates,
+        hideNsfwInLibrary, hideNsfwInHistory, hideNsfwInTags, hideNsfwCompletely, developerMode, betaUpdates, lanUpdateSharing,
         discordRpcEnabled, discordRpcHideNsfw, discordRpcStrictNsfw, adaptivePrefetchEnabled,
         proxies, addProxy, removeProxy,
         setTheme, setDefaultReaderMode, setPrefetchChapters, setMaxCacheSize, setIgnoreCacheLimitForPrefetch, setLogLevel,
-        setHideNsfwInLibrary, setHideNsfwInHistory, setHideNsfwInTags, setHideNsfwCompletely, setDeveloperMode, setBetaUpdates,
+        setHideNsfwInLibrary, setHideNsfwInHistory, setHideNsfwInTags, setHideNsfwCompletely, setDeveloperMode, setBetaUpdates, setLanUpdateSharing,
         setDiscordRpcEnabled, setDiscordRpcHideNsfw, setDiscordRpcStrictNsfw, setAdaptivePrefetchEnabled,
     } = useSettingsStore();
 
@@ -1299,6 +1307,28 @@ function Settings() {
                     </div>
                 </div>
             )}
+
+            {shouldShow('about-lan-sharing') && (
+                <div className="setting-item">
+                    <div className="setting-info">
+                        <label className="setting-label">Share Updates on Local Network</label>
+                        <span className="setting-description">
+                            Download updates from other computers on your network that already have them, and offer yours to them.
+                            Every file is checked against the signed release before it is used.
+                        </span>
+                    </div>
+                    <div className="setting-control">
+                        <label className="checkbox-switch">
+                            <input
+                                type="checkbox"
+                                checked={lanUpdateSharing}
+                                onChange={(e) => setLanUpdateSharing(e.target.checked)}
+                            />
+                            <span className="checkbox-slider"></span>
+                        </label>
+                    </div>
+                </div>
+            )}
         </section>
     );
 
@@ -36,6 +36,7 @@ interface SettingsState {
     discordRpcStrictNsfw: boolean;
     developerMode: boolean;
     betaUpdates: boolean;
+    lanUpdateSharing: boolean;
     browseViewMode: BrowseViewMode;
     proxies: ProxyConfig[];
     adaptivePrefetchEnabled: boolean;  // Adaptive prefetch (Beta)
@@ -57,6 +58,7 @@ interface SettingsState {
     setDiscordRpcStrictNsfw: (value: boolean) => void;
     setDeveloperMode: (value: boolean) => void;
     setBetaUpdates: (value: boolean) => void;
+    setLanUpdateSharing: (value: boolean) => void;
     setBrowseViewMode: (mode: BrowseViewMode) => void;
     addProxy: (proxy: ProxyConfig) => void;
     removeProxy: (index: number) => void;
@@ -93,6 +95,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     discordRpcStrictNsfw: true,
     developerMode: false,
     betaUpdates: false,
+    lanUpdateSharing: false,
     browseViewMode: 'grid',
     proxies: [],
     adaptivePrefetchEnabled: false,  // Opt-in beta feature
@@ -183,6 +186,11 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
         window.electronAPI.settings.set('betaUpdates', value);
     },
 
+    setLanUpdateSharing: (value) => {
+        set({ lanUpdateSharing: value });
+        window.electronAPI.app.setLanUpdateSharing(value);
+    },
+
     setDiscordRpcEnabled: (value) => {
         set({ discordRpcEnabled: value });
         window.electronAPI.settings.set('discordRpcEnabled', value);
@@ -265,6 +273,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
             if (stored.discordRpcStrictNsfw !== undefined) updates.discordRpcStrictNsfw = stored.discordRpcStrictNsfw;
             if (stored.developerMode !== undefined) updates.developerMode = stored.developerMode;
             if (stored.betaUpdates !== undefined) updates.betaUpdates = stored.betaUpdates;
+            if (stored.lanUpdateSharing !== undefined) updates.lanUpdateSharing = stored.lanUpdateSharing;
             if (stored.browseViewMode) updates.browseViewMode = stored.browseViewMode as BrowseViewMode;
             if (stored.proxies) updates.proxies = stored.proxies as ProxyConfig[];
             if (stored.adaptivePrefetchEnabled !== undefined) updates.adaptivePrefetchEnabled = stored.adaptivePrefetchEnabled;
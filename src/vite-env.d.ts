@@ -218,9 +218,13 @@ interface Window {
             }>;
             downloadUpdate: (url: string, fileName: string, blockmapUrl?: string, targetVersion?: string) => Promise<{ success: boolean; filePath?: string; error?: string; isDifferential?: boolean }>;
             installUpdate: () => Promise<{ success: boolean; error?: string }>;
+            setLanUpdateSharing: (enabled: boolean) => Promise<{ success: boolean; error?: string }>;
+            uninstallPortable: () => Promise<{ success: boolean; error?: string }>;
             onDownloadProgress: (callback: (event: any, data: { percent: number; bytesDownloaded: number; totalBytes: number; isDifferential?: boolean }) => void) => () => void;
             onDownloadComplete: (callback: (event: any, data: { success: boolean; filePath?: string; error?: string }) => void) => () => void;
             onFileOpened: (callback: (event: any, filePath: string) => void) => () => void;
+            takeLaunchRoute: () => Promise<string | null>;
+            onNavigate: (callback: (event: any, route: string) => void) => () => void;
             log: (level: 'error' | 'warn' | 'info' | 'debug' | 'verbose', context: string, message: string) => Promise<void>;
             isGpuDisabled: () => Promise<boolean>;
             resetGpuFlag: () => Promise<{ success: boolean; needsRestart: boolean }>;